@@ -0,0 +1,290 @@
+use std::env;
+use std::vec::Vec;
+
+use network_config::{DistributionConfig,NetworkConfig,QueueKind,RoutingConfig,ServiceConfig};
+
+// Mean Value Analysis of closed product-form networks
+// (Reiser & Lavenberg for the exact single-class case, Schweitzer's approximation for several classes)
+
+#[derive(Debug,Clone,Copy,PartialEq)]
+pub enum StationKind {
+    // Single server, FCFS with exponential service or PS with any service (eg, MG1PS)
+    Queueing,
+    // Infinite server: requests never wait (eg, MGINF, or the think time of clients)
+    Delay,
+}
+
+#[derive(Debug,Clone)]
+pub struct Station {
+    pub name: String,
+    pub kind: StationKind,
+    // Mean number of visits per cycle of each class (relative to the reference station)
+    pub visits: Vec<f64>,
+    // Mean service time of each class, per visit
+    pub service_times: Vec<f64>,
+}
+
+impl Station {
+    pub fn new(name: &str, kind: StationKind, visits: Vec<f64>, service_times: Vec<f64>) -> Self {
+        assert_eq!(visits.len(), service_times.len(), "Station {}: one visit ratio and service time per class", name);
+        Station {
+            name: name.to_owned(),
+            kind,
+            visits,
+            service_times,
+        }
+    }
+
+    pub fn queueing(name: &str, visits: f64, service_time: f64) -> Self {
+        Station::new(name, StationKind::Queueing, vec![visits], vec![service_time])
+    }
+
+    pub fn delay(name: &str, visits: f64, service_time: f64) -> Self {
+        Station::new(name, StationKind::Delay, vec![visits], vec![service_time])
+    }
+
+    // Total service time requested by a class per cycle
+    pub fn demand(&self, class: usize) -> f64 {
+        self.visits[class] * self.service_times[class]
+    }
+}
+
+#[derive(Debug,Clone)]
+pub struct MvaSolution {
+    // Cycles per unit of time of each class, ie throughput at the reference station
+    pub throughputs: Vec<f64>,
+    // Time spent at each station per cycle: residence_times[class][station]
+    pub residence_times: Vec<Vec<f64>>,
+    // Mean number of requests: queue_lengths[class][station]
+    pub queue_lengths: Vec<Vec<f64>>,
+    // Utilization of each station by all classes (mean number of busy servers for delay stations)
+    pub utilizations: Vec<f64>,
+}
+
+impl MvaSolution {
+    fn new(stations: &[Station], throughputs: Vec<f64>, residence_times: Vec<Vec<f64>>) -> Self {
+        let queue_lengths: Vec<Vec<f64>> = residence_times.iter().zip(&throughputs)
+            .map(|(r, x)| r.iter().map(|r| x * r).collect()).collect();
+        let utilizations = stations.iter()
+            .map(|s| throughputs.iter().enumerate().map(|(c, x)| x * s.demand(c)).sum()).collect();
+        MvaSolution {
+            throughputs,
+            residence_times,
+            queue_lengths,
+            utilizations,
+        }
+    }
+
+    // Time for a request of 'class' to go once around the network
+    pub fn cycle_time(&self, class: usize) -> f64 {
+        self.residence_times[class].iter().sum()
+    }
+
+    // Mean response time of a single visit to 'station'
+    pub fn response_time(&self, stations: &[Station], class: usize, station: usize) -> f64 {
+        self.residence_times[class][station] / stations[station].visits[class]
+    }
+
+    // Throughput of 'class' at 'station'
+    pub fn station_throughput(&self, stations: &[Station], class: usize, station: usize) -> f64 {
+        self.throughputs[class] * stations[station].visits[class]
+    }
+}
+
+fn residence_time(station: &Station, class: usize, queue_length: f64) -> f64 {
+    match station.kind {
+        StationKind::Queueing => station.demand(class) * (1. + queue_length),
+        StationKind::Delay => station.demand(class),
+    }
+}
+
+// Exact MVA of a single-class network with 'population' requests
+pub fn exact_mva(stations: &[Station], population: usize) -> MvaSolution {
+    assert!(stations.iter().all(|s| s.visits.len() == 1), "Exact MVA is only implemented for a single class");
+
+    let mut queue_lengths = vec![0.; stations.len()];
+    let mut residence_times = vec![0.; stations.len()];
+    let mut throughput = 0.;
+    for n in 1..=population {
+        for (k, s) in stations.iter().enumerate() {
+            residence_times[k] = residence_time(s, 0, queue_lengths[k]);
+        }
+        throughput = n as f64 / residence_times.iter().sum::<f64>();
+        for k in 0..stations.len() {
+            queue_lengths[k] = throughput * residence_times[k];
+        }
+    }
+    MvaSolution::new(stations, vec![throughput], vec![residence_times])
+}
+
+// Iterations after which Schweitzer's MVA gives up
+const SCHWEITZER_MAX_ITERATIONS: usize = 100_000;
+
+// Schweitzer's approximate MVA for several classes: the queue seen at arrival by a request of class c
+// is the steady-state one, minus 1/N_c of its own class. Iterates until queue lengths change by less than 'tolerance'
+pub fn schweitzer_mva(stations: &[Station], populations: &[usize], tolerance: f64) -> Result<MvaSolution, String> {
+    let nb_classes = populations.len();
+    assert!(stations.iter().all(|s| s.visits.len() == nb_classes), "Every station needs one visit ratio per class");
+
+    let nb_queueing = stations.iter().filter(|s| s.kind == StationKind::Queueing).count().max(1);
+    let mut queue_lengths: Vec<Vec<f64>> = populations.iter()
+        .map(|&n| stations.iter().map(|s| if s.kind == StationKind::Queueing { n as f64 / nb_queueing as f64 } else { 0. }).collect())
+        .collect();
+    let mut residence_times = vec![vec![0.; stations.len()]; nb_classes];
+    let mut throughputs = vec![0.; nb_classes];
+
+    let mut change = f64::INFINITY;
+    for _ in 0..SCHWEITZER_MAX_ITERATIONS {
+        let totals: Vec<f64> = (0..stations.len()).map(|k| queue_lengths.iter().map(|q| q[k]).sum()).collect();
+        change = 0.;
+
+        for c in 0..nb_classes {
+            if populations[c] == 0 {
+                continue;
+            }
+            let n = populations[c] as f64;
+            for (k, s) in stations.iter().enumerate() {
+                residence_times[c][k] = residence_time(s, c, totals[k] - queue_lengths[c][k] / n);
+            }
+            throughputs[c] = n / residence_times[c].iter().sum::<f64>();
+            for k in 0..stations.len() {
+                let q = throughputs[c] * residence_times[c][k];
+                change = change.max((q - queue_lengths[c][k]).abs());
+                queue_lengths[c][k] = q;
+            }
+        }
+
+        if change < tolerance {
+            return Ok(MvaSolution::new(stations, throughputs, residence_times));
+        }
+    }
+    Err(format!("Schweitzer's MVA did not converge after {} iterations (last change {})", SCHWEITZER_MAX_ITERATIONS, change))
+}
+
+// Solves a x = b by Gaussian elimination with partial pivoting
+fn solve_linear(mut a: Vec<Vec<f64>>, mut b: Vec<f64>) -> Option<Vec<f64>> {
+    let n = b.len();
+    for col in 0..n {
+        let pivot = (col..n).max_by(|&i, &j| a[i][col].abs().partial_cmp(&a[j][col].abs()).unwrap())?;
+        if a[pivot][col].abs() < 1e-12 {
+            return None;
+        }
+        a.swap(col, pivot);
+        b.swap(col, pivot);
+        let (top, bottom) = a.split_at_mut(col + 1);
+        let pivot_row = &top[col];
+        for (offset, row) in bottom.iter_mut().enumerate() {
+            let factor = row[col] / pivot_row[col];
+            row.iter_mut().zip(pivot_row).skip(col).for_each(|(x, p)| *x -= factor * p);
+            b[col + 1 + offset] -= factor * b[col];
+        }
+    }
+
+    let mut x = vec![0.; n];
+    for row in (0..n).rev() {
+        let sum: f64 = (row + 1..n).map(|k| a[row][k] * x[k]).sum();
+        x[row] = (b[row] - sum) / a[row][row];
+    }
+    Some(x)
+}
+
+// Stations of a network description, for a single class. Visit ratios are computed from the routing,
+// relative to 'reference' (typically the clients or the think-time station). Only fixed and
+// probabilistic routings keep the product form; queues that are never visited are ignored
+pub fn stations_from_config(config: &NetworkConfig, reference: &str) -> Result<Vec<Station>, String> {
+    let n = config.queues.len();
+    let index = |name: &String| config.queues.iter().position(|q| &q.name == name).ok_or(format!("Unknown queue: {}", name));
+    let reference = index(&reference.to_owned())?;
+
+    // routing[i][j]: probability to go from queue i to queue j
+    let mut routing = vec![vec![0.; n]; n];
+    for (i, q) in config.queues.iter().enumerate() {
+        match q.routing {
+            None => (),
+            Some(RoutingConfig::Fixed { ref to, .. }) => routing[i][index(to)?] += 1.,
+            Some(RoutingConfig::Probabilistic { ref to, ref probabilities }) => {
+                for (dest, p) in to.iter().zip(probabilities) {
+                    routing[i][index(dest)?] += p;
+                }
+            },
+            Some(_) => return Err(format!("Queue {}: state-dependent routing does not have a product form", q.name)),
+        }
+    }
+    // Requests arriving at the completion queue of a population go back to it
+    let mut completions = Vec::new();
+    for (i, q) in config.queues.iter().enumerate() {
+        if let QueueKind::ClientPopulation { completion: Some(ref completion), .. } = q.kind {
+            let c = index(completion)?;
+            if config.queues[c].routing.is_some() {
+                return Err(format!("Queue {}: the completion queue of a population cannot route requests further", completion));
+            }
+            routing[c][i] += 1.;
+            completions.push(c);
+        }
+    }
+
+    // Traffic equations: v_j = sum_i v_i routing[i][j], with v_reference = 1
+    let a: Vec<Vec<f64>> = (0..n).map(|j| (0..n).map(|i| {
+        if j == reference { if i == j { 1. } else { 0. } }
+        else { (if i == j { 1. } else { 0. }) - routing[i][j] }
+    }).collect()).collect();
+    let b: Vec<f64> = (0..n).map(|j| if j == reference { 1. } else { 0. }).collect();
+    let visits = solve_linear(a, b).ok_or("The traffic equations have no unique solution")?;
+
+    let mut stations = Vec::new();
+    for (i, (q, &v)) in config.queues.iter().zip(&visits).enumerate() {
+        if v.abs() < 1e-12 {
+            continue;
+        }
+        if q.balking.is_some() {
+            return Err(format!("Queue {}: balking does not have a product form", q.name));
+        }
+        let service_time = |mean: Option<f64>, work_rate: f64| {
+            mean.map(|m| m / work_rate).ok_or(format!("Queue {}: the mean of the service distribution is unknown", q.name))
+        };
+        let station = match q.kind {
+            QueueKind::Mg1ps { work_rate, ref service, capacity: None, patience: None, breakdowns: None, vacation: None } => Station::queueing(&q.name, v, service_time(service.mean(), work_rate)?),
+            QueueKind::Mgkfifo { servers: 1, work_rate, ref service, capacity: None, patience: None, speeds: None, breakdowns: None, vacation: None, .. } => {
+                // FCFS stations only have a product form with exponential service
+                if !matches!(*service, ServiceConfig::Distribution(DistributionConfig::Exp { .. })) {
+                    return Err(format!("Queue {}: a single-server mgkfifo needs an exponential service", q.name));
+                }
+                Station::queueing(&q.name, v, service_time(service.mean(), work_rate)?)
+            },
+            QueueKind::Mginf { work_rate, ref service, breakdowns: None } => Station::delay(&q.name, v, service_time(service.mean(), work_rate)?),
+            QueueKind::ClientPopulation { ref think_time, .. } => Station::delay(&q.name, v, service_time(think_time.mean(), 1.)?),
+            QueueKind::Passthrough {} => continue,
+            // Completion queues take no time
+            QueueKind::FileLogger { .. } | QueueKind::Sink {} if completions.contains(&i) => continue,
+            _ => return Err(format!("Queue {}: this type of queue is not supported by MVA", q.name)),
+        };
+        stations.push(station);
+    }
+    Ok(stations)
+}
+
+pub fn run (mut args: env::Args) {
+    let filename = args.next().expect("No network description provided");
+    let config = NetworkConfig::from_file(&filename).unwrap_or_else(|e| panic!("{}", e));
+
+    // The population and reference default to those of the client population of the network, if any
+    let clients = config.queues.iter().find_map(|q| match q.kind {
+        QueueKind::ClientPopulation { clients, .. } => Some((clients, q.name.clone())),
+        _ => None
+    });
+    let population: usize = match args.next() {
+        Some(p) => p.parse().expect("Invalid population"),
+        None => clients.as_ref().expect("No population provided").0
+    };
+    let reference = args.next().or(clients.map(|(_, name)| name)).expect("No reference station provided");
+
+    let stations = stations_from_config(&config, &reference).unwrap_or_else(|e| panic!("{}", e));
+    let solution = exact_mva(&stations, population);
+
+    println!("throughput {} cycle_time {}", solution.throughputs[0], solution.cycle_time(0));
+    println!("{:<12} {:>10} {:>10} {:>10} {:>10} {:>10}", "station", "visits", "demand", "util", "mean_load", "response");
+    for (k, s) in stations.iter().enumerate() {
+        println!("{:<12} {:>10.4} {:>10.4} {:>10.4} {:>10.4} {:>10.4}", s.name, s.visits[0], s.demand(0),
+                 solution.utilizations[k], solution.queue_lengths[0][k], solution.response_time(&stations, 0, k));
+    }
+}
@@ -13,6 +13,8 @@ use queues::cm_poisson_generator::ContinuouslyModulatedPoissonGenerator;
 
 use rand::distributions::{Exp};
 use helpers::distribution::{ConstantDistribution};
+use helpers::parallel::{parallel_map,available_threads};
+use helpers::rng::SeedSequence;
 
 use queues::autoscaling_qnetwork::AutoscalingQNet;
 use queues::autoscaling_qnetwork::AutoscalingParameters;
@@ -21,8 +23,10 @@ use queues::centralized_autoscaling_qnetwork::CentralizedLBPolicy;
 use queues::centralized_autoscaling_qnetwork::CentralizedScalingPolicy;
 use queues::file_logger::FileLogger;
 use queues::trace_generator::TraceGenerator;
+use queues::simulation::{Simulation,StopCondition};
+use queues::warmup::Warmup;
 
-fn centralized_lb_noautoscaling_sim(n_servers: usize, rho: f64)
+fn centralized_lb_noautoscaling_sim(n_servers: usize, rho: f64, warmup: bool, seed: Option<u64>)
 {
     let mu = 1./0.100; //100 ms
     let lambda = rho * mu;
@@ -34,19 +38,24 @@ fn centralized_lb_noautoscaling_sim(n_servers: usize, rho: f64)
                                       ConstantDistribution::new(tau_network),
                                       Exp::new(mu),
                                       CentralizedLBPolicy::RND,
-                                      CentralizedScalingPolicy::NoAutoscaling);
+                                      CentralizedScalingPolicy::NoAutoscaling,
+                                      seed);
 
 
-    // Run simulation
-    let mut t = 0.;
-    while t < 400. {
-        t = qn.make_transition().unwrap().time;
+    // Discard the initial transient from the results, if asked
+    if warmup {
+        let sink = qn.get_file_logger();
+        qn.get_qnet_mut().set_warmup(Warmup::Mser5 { sink, min_observations: 1000 });
     }
+
+    // Run simulation
+    let report = qn.run_until(StopCondition::Time(400.));
+    assert!(!report.is_error(), "Simulation failed: {:?}", report.stop_reason);
     println!("Done");
 
 }
 
-fn centralized_lb_with_schedule_sim(n_servers: usize)
+fn centralized_lb_with_schedule_sim(n_servers: usize, seed: Option<u64>)
 {
     let mu = 1./0.100; //100 ms
     let tau_network = 0.000_000; //200 μs
@@ -59,21 +68,20 @@ fn centralized_lb_with_schedule_sim(n_servers: usize)
                                       ConstantDistribution::new(tau_network),
                                       Exp::new(mu),
                                       CentralizedLBPolicy::RND,
-                                      CentralizedScalingPolicy::Schedule("schedule.csv", ' ') );
+                                      CentralizedScalingPolicy::Schedule("schedule.csv", ' '),
+                                      seed);
 
 
     // Run simulation
-    let mut t = 0.;
-    while t < 86400. {
-        t = qn.make_transition().unwrap().time;
-    }
+    let report = qn.run_until(StopCondition::Time(86400.));
+    assert!(!report.is_error(), "Simulation failed: {:?}", report.stop_reason);
     println!("Done");
 
 }
 
 
 
-fn centralized_lb_with_autoscaling_sim(n_servers: usize)
+fn centralized_lb_with_autoscaling_sim(n_servers: usize, seed: Option<u64>)
 {
     let mu = 1./0.100; //100 ms
     let tau_network = 0.000_000; //200 μs
@@ -86,20 +94,19 @@ fn centralized_lb_with_autoscaling_sim(n_servers: usize)
                                       ConstantDistribution::new(tau_network),
                                       Exp::new(mu),
                                       CentralizedLBPolicy::RND,
-                                      CentralizedScalingPolicy::Autoscaling(0.110, 0.104, 60.) );
+                                      CentralizedScalingPolicy::Autoscaling(0.110, 0.104, 60.),
+                                      seed);
 
 
     // Run simulation
-    let mut t = 0.;
-    while t < 86400. {
-        t = qn.make_transition().unwrap().time;
-    }
+    let report = qn.run_until(StopCondition::Time(86400.));
+    assert!(!report.is_error(), "Simulation failed: {:?}", report.stop_reason);
     println!("Done");
 
 }
 
 
-fn sr_noautoscaling_sim(n_servers: usize, rho: f64)
+fn sr_noautoscaling_sim(n_servers: usize, rho: f64, warmup: bool, seed: Option<u64>)
 {
     let mu = 1./0.100; //100 ms
     let lambda = rho * mu;
@@ -110,19 +117,32 @@ fn sr_noautoscaling_sim(n_servers: usize, rho: f64)
                                       n_servers,
                                       ConstantDistribution::new(tau_network),
                                       Exp::new(mu),
-                                      None);
+                                      None,
+                                      seed);
 
 
-    // Run simulation
-    let mut t = 0.;
-    while t < 400. {
-        t = qn.make_transition().unwrap().time;
+    // Discard the initial transient from the results, if asked
+    if warmup {
+        let sink = qn.get_file_logger();
+        qn.get_qnet_mut().set_warmup(Warmup::Mser5 { sink, min_observations: 1000 });
     }
+
+    // Run simulation
+    let report = qn.run_until(StopCondition::Time(400.));
+    assert!(!report.is_error(), "Simulation failed: {:?}", report.stop_reason);
     println!("Done");
 
 }
 
-fn sr_autoscaling_sim(n_servers: usize)
+// Runs sr_noautoscaling_sim for every load in parallel, each point with its own seed derived from 'seed'
+fn sr_noautoscaling_sweep(n_servers: usize, rhos: Vec<f64>, warmup: bool, seed: Option<u64>)
+{
+    let mut seeds = seed.map(SeedSequence::new);
+    let points: Vec<(f64, Option<u64>)> = rhos.into_iter().map(|rho| (rho, seeds.as_mut().map(|s| s.next_seed()))).collect();
+    parallel_map(points, available_threads(), |(rho, seed)| sr_noautoscaling_sim(n_servers, rho, warmup, seed));
+}
+
+fn sr_autoscaling_sim(n_servers: usize, seed: Option<u64>)
 {
     let mu = 1./0.100; //100 ms
     let tau_network = 0.000_000; //200 μs
@@ -134,19 +154,18 @@ fn sr_autoscaling_sim(n_servers: usize)
                                       n_servers,
                                       ConstantDistribution::new(tau_network),
                                       Exp::new(mu),
-                                      Some(AutoscalingParameters{proba_empty: 0.8, ewma_window_len: 100.}));
+                                      Some(AutoscalingParameters{proba_empty: 0.8, ewma_window_len: 100.}),
+                                      seed);
 
 
     // Run simulation
-    let mut t = 0.;
-    while t < 86400. {
-        t = qn.make_transition().unwrap().time;
-    }
+    let report = qn.run_until(StopCondition::Time(86400.));
+    assert!(!report.is_error(), "Simulation failed: {:?}", report.stop_reason);
     println!("Done");
 
 }
 
-fn sr_autoscaling_sim_with_trace(n_servers: usize)
+fn sr_autoscaling_sim_with_trace(n_servers: usize, seed: Option<u64>)
 {
     let mu = 1./0.100; //100 ms
     let tau_network = 0.000_000; //200 μs
@@ -156,11 +175,13 @@ fn sr_autoscaling_sim_with_trace(n_servers: usize)
                                       n_servers,
                                       ConstantDistribution::new(tau_network),
                                       ConstantDistribution::new(1./mu),
-                                      Some(AutoscalingParameters{proba_empty: 0.8, ewma_window_len: 100.}));
+                                      Some(AutoscalingParameters{proba_empty: 0.8, ewma_window_len: 100.}),
+                                      seed);
 
 
     // Run simulation
-    while let Ok(_) = qn.make_transition() {}
+    let report = qn.run_until(StopCondition::Exhaustion);
+    assert!(!report.is_error(), "Simulation failed: {:?}", report.stop_reason);
 
     println!("Done");
 
@@ -168,6 +189,17 @@ fn sr_autoscaling_sim_with_trace(n_servers: usize)
 
 
 
-pub fn run_autoscaling (_: env::Args) {
-    sr_autoscaling_sim_with_trace(40);
+// cargo run autoscaling [sweep [warmup]] [seed]
+pub fn run_autoscaling (args: env::Args) {
+    let mut args = args.peekable();
+    let sweep = args.next_if(|a| a == "sweep").is_some();
+    let warmup = sweep && args.next_if(|a| a == "warmup").is_some();
+    let seed = args.next().map(|s| s.parse().expect("Seed must be an unsigned integer"));
+    if sweep {
+        let rhos = (1..20).map(|i| i as f64 * 0.05).collect();
+        sr_noautoscaling_sweep(40, rhos, warmup, seed);
+    }
+    else {
+        sr_autoscaling_sim_with_trace(40, seed);
+    }
 }
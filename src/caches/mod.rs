@@ -5,29 +5,34 @@ pub mod abf_cache;
 pub mod abf_fpga_cache;
 
 use rand::Rng;
-use std::f64;
+use helpers::rng::{SimRng,rng_from_entropy,rng_from_seed};
 
 pub trait Cache<T> {
     fn contains (&mut self, entry: &T) -> bool;
     fn update (&mut self, entry: T);
 }
 
-pub struct RandomAccept(f64);
+pub struct RandomAccept(f64, SimRng);
 
 impl RandomAccept {
+    #[allow(clippy::result_unit_err)]
     pub fn from_value(f: f64) -> Result<RandomAccept,()> {
-        if 0. <= f && f <= 1. {
-            Ok(RandomAccept { 0: f })
+        if (0. ..=1.).contains(&f) {
+            Ok(RandomAccept(f, rng_from_entropy()))
         }
         else {
             Err(())
         }
     }
+
+    pub fn reseed(&mut self, seed: u64) {
+        self.1 = rng_from_seed(seed);
+    }
 }
 
 impl<T> Cache<T> for RandomAccept {
     fn contains (&mut self, _: &T) -> bool {
-        rand::thread_rng().gen_range(0.,1.) < self.0
+        self.1.gen_range(0.,1.) < self.0
     }
 
     fn update(&mut self, _: T) {}
@@ -37,7 +42,7 @@ pub struct PerfectLfu(usize);
 
 impl PerfectLfu {
     pub fn new(klfu: usize) -> Self {
-        PerfectLfu { 0: klfu }
+        PerfectLfu(klfu)
     }
 }
 
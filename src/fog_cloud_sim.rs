@@ -7,8 +7,7 @@ extern crate zipf;
 use zipf::ZipfDistribution;
 use rand::distributions::Distribution;
 
-use std::rc::Rc;
-use std::cell::RefCell;
+use std::sync::{Arc,Mutex};
 
 use std::env;
 
@@ -18,7 +17,8 @@ use helpers::distribution::ConstantDistribution;
 use queues::mg1ps::{AggregatingMG1PS,MG1PS};
 use queues::mginf::MGINF;
 use queues::zipfgen::ZipfGenerator;
-use queues::queueing_network::{QNet,TransitionError};
+use queues::queueing_network::QNet;
+use queues::simulation::{Simulation,StopCondition,StopReason};
 use queues::file_logger::FileLogger;
 
 
@@ -34,7 +34,7 @@ enum Filter {
     Abf(usize),
 }
 
-fn run_sim(mode: Filter, s_cachec: usize) {
+fn run_sim(mode: Filter, s_cachec: usize, seed: Option<u64>) {
 
     let catalogue_size = 10_000_000;
     let alpha = 1.0;
@@ -67,21 +67,29 @@ fn run_sim(mode: Filter, s_cachec: usize) {
         Filter::Lfu(_) => "result-lfu.csv",
     };
 
-    let filter_ptr: Rc<RefCell<Cache<usize>>> = match mode {
-        Filter::Lru(klru) => Rc::new(RefCell::new(LruCache::new(klru))),
-        Filter::Abf(k1) => Rc::new(RefCell::new(AgingBloomFilterFPGA::new(k1,0.01))),
-        Filter::Blind(phi) => Rc::new(RefCell::new(RandomAccept::from_value(phi).unwrap())),
-        Filter::Lfu(klfu) => Rc::new(RefCell::new(PerfectLfu::new(klfu))),
+    let mut qn = match seed {
+        Some(seed) => QNet::from_seed(seed),
+        None => QNet::new()
+    };
+
+    let filter_ptr: Arc<Mutex<dyn Cache<usize> + Send>> = match mode {
+        Filter::Lru(klru) => Arc::new(Mutex::new(LruCache::new(klru))),
+        Filter::Abf(k1) => Arc::new(Mutex::new(AgingBloomFilterFPGA::new(k1,0.01))),
+        Filter::Blind(phi) => {
+            let mut filter = RandomAccept::from_value(phi).unwrap();
+            filter.reseed(qn.derive_seed());
+            Arc::new(Mutex::new(filter))
+        },
+        Filter::Lfu(klfu) => Arc::new(Mutex::new(PerfectLfu::new(klfu))),
     };
 
     //let filter: LruCache<usize> = LruCache::new(k_lru as usize);
-    //let filter_ptr = Rc::new(RefCell::new(filter));
+    //let filter_ptr = Arc::new(Mutex::new(filter));
     let fog_cache: LruCache<usize> = LruCache::new(s_cachef as usize);
-    let fcache_ptr = Rc::new(RefCell::new(fog_cache));
-    let cloud_cache: LruCache<usize> = LruCache::new(s_cachec as usize);
-    let ccache_ptr = Rc::new(RefCell::new(cloud_cache));
+    let fcache_ptr = Arc::new(Mutex::new(fog_cache));
+    let cloud_cache: LruCache<usize> = LruCache::new(s_cachec);
+    let ccache_ptr = Arc::new(Mutex::new(cloud_cache));
 
-    let mut qn = QNet::new();
     let source = qn.add_queue(Box::new(
             ZipfGenerator::new(alpha, catalogue_size, Exp::new(lambda), nb_arrivals)));
 
@@ -107,8 +115,8 @@ fn run_sim(mode: Filter, s_cachec: usize) {
     let log = qn.add_queue(Box::new(FileLogger::new(1024, logfile)));
 
     let filter_clone = filter_ptr.clone();
-    qn.add_transition(source, Box::new(move |req,_| {
-        let mut cache = filter_clone.borrow_mut();
+    qn.add_transition(source, Box::new(move |req,_,_| {
+        let mut cache = filter_clone.lock().unwrap();
         //let content = (req.get_id(), req.get_content());
         let content = req.get_content();
         let ret =
@@ -120,8 +128,8 @@ fn run_sim(mode: Filter, s_cachec: usize) {
 
     //FOG
     let fcache_clone = fcache_ptr.clone();
-    qn.add_transition(tls_acc_u, Box::new(move |req,_| {
-        let mut cache = fcache_clone.borrow_mut();
+    qn.add_transition(tls_acc_u, Box::new(move |req,_,_| {
+        let mut cache = fcache_clone.lock().unwrap();
         if cache.contains(&req.get_content()) {
             cache.update(req.get_content());
             acc_d
@@ -130,22 +138,22 @@ fn run_sim(mode: Filter, s_cachec: usize) {
             tls_acc_d
         }
     }));
-    qn.add_transition(tls_acc_d, Box::new(move |_,_| acc_u));
-    qn.add_transition(acc_u, Box::new(move |_,_| acc_u_propagation));
-    qn.add_transition(acc_u_propagation, Box::new(move |_,_| fog_proc));
+    qn.add_transition(tls_acc_d, Box::new(move |_,_,_| acc_u));
+    qn.add_transition(acc_u, Box::new(move |_,_,_| acc_u_propagation));
+    qn.add_transition(acc_u_propagation, Box::new(move |_,_,_| fog_proc));
 
     let filter_clone = filter_ptr.clone();
-    qn.add_transition(fog_proc, Box::new(move |req,_| {
-        fcache_ptr.borrow_mut().update(req.get_content());
-        //filter_clone.borrow_mut().update((req.get_id(), req.get_content()));
-        filter_clone.borrow_mut().update(req.get_content());
+    qn.add_transition(fog_proc, Box::new(move |req,_,_| {
+        fcache_ptr.lock().unwrap().update(req.get_content());
+        //filter_clone.lock().unwrap().update((req.get_id(), req.get_content()));
+        filter_clone.lock().unwrap().update(req.get_content());
         acc_d
     }));
 
     //CLOUD
     let ccache_clone = ccache_ptr.clone();
-    qn.add_transition(tls_core_u, Box::new(move |req,_| {
-        let mut cache = ccache_clone.borrow_mut();
+    qn.add_transition(tls_core_u, Box::new(move |req,_,_| {
+        let mut cache = ccache_clone.lock().unwrap();
         if cache.contains(&req.get_content()) {
             cache.update(req.get_content());
             core_d
@@ -154,26 +162,22 @@ fn run_sim(mode: Filter, s_cachec: usize) {
             db_queue
         }
     }));
-    qn.add_transition(db_queue, Box::new(move | _,_ | cloud_proc));
-    qn.add_transition(cloud_proc, Box::new(move |req,_| {
-        ccache_ptr.borrow_mut().update(req.get_content());
+    qn.add_transition(db_queue, Box::new(move |_,_,_| cloud_proc));
+    qn.add_transition(cloud_proc, Box::new(move |req,_,_| {
+        ccache_ptr.lock().unwrap().update(req.get_content());
         core_d
     }));
 
     //let filter_ptr_1 = filter_ptr.clone();
-    qn.add_transition(core_d, Box::new(move |_,_| core_d_propagation));
-    qn.add_transition(core_d_propagation, Box::new(move |_,_| acc_d ));
+    qn.add_transition(core_d, Box::new(move |_,_,_| core_d_propagation));
+    qn.add_transition(core_d_propagation, Box::new(move |_,_,_| acc_d ));
 
-    qn.add_transition(acc_d, Box::new(move |_,_| acc_d_propagation));
-    qn.add_transition(acc_d_propagation, Box::new(move |_,_| log));
+    qn.add_transition(acc_d, Box::new(move |_,_,_| acc_d_propagation));
+    qn.add_transition(acc_d_propagation, Box::new(move |_,_,_| log));
 
     //qn.add_queue(Box::new(P2LruFilterCont::new(filter_ptr)));
-    let mut res = qn.make_transition();
-    while res.is_ok() {
-        res = qn.make_transition();
-    }
-    match res.unwrap_err() {
-        TransitionError::NoExitFound(_) => println!("Done"),
+    match qn.run_until(StopCondition::Exhaustion).stop_reason {
+        StopReason::NoMoreEvents => println!("Done"),
         _ => panic!("Unexpected error")
     };
 }
@@ -234,6 +238,7 @@ pub fn run (mut args: env::Args) {
         Filter::Lfu(_) => 2_962_421,
         Filter::Blind(_) => 2_823_474,
     };
+    let seed = args.next().map(|s| s.parse().expect("Seed must be an unsigned integer"));
 
-    run_sim(mode, s_cachec);
+    run_sim(mode, s_cachec, seed);
 }
@@ -0,0 +1,52 @@
+extern crate rand;
+
+use std::vec::Vec;
+
+use rand::Rng;
+
+//Walker's alias method (Vose's variant): samples an index with the given probabilities in O(1)
+pub struct AliasTable {
+    probabilities: Vec<f64>,
+    aliases: Vec<usize>,
+}
+
+impl AliasTable {
+    //Weights must be non-negative with a positive sum; they are normalized
+    pub fn new(weights: &[f64]) -> Self {
+        let n = weights.len();
+        let total: f64 = weights.iter().sum();
+        assert!(n > 0 && total > 0., "Alias table needs at least one positive weight");
+
+        let mut probabilities: Vec<f64> = weights.iter().map(|w| w * n as f64 / total).collect();
+        let mut aliases: Vec<usize> = (0..n).collect();
+        let (mut small, mut large): (Vec<usize>, Vec<usize>) = (0..n).partition(|&i| probabilities[i] < 1.);
+
+        while let (Some(s), Some(&l)) = (small.pop(), large.last()) {
+            aliases[s] = l;
+            probabilities[l] -= 1. - probabilities[s];
+            if probabilities[l] < 1. {
+                large.pop();
+                small.push(l);
+            }
+        }
+        // Leftovers only differ from 1 by rounding errors
+        for i in small.into_iter().chain(large) {
+            probabilities[i] = 1.;
+        }
+
+        AliasTable { probabilities, aliases }
+    }
+
+    pub fn len(&self) -> usize {
+        self.probabilities.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.probabilities.is_empty()
+    }
+
+    pub fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> usize {
+        let i = rng.gen_range(0, self.probabilities.len());
+        if rng.gen::<f64>() < self.probabilities[i] { i } else { self.aliases[i] }
+    }
+}
@@ -9,9 +9,9 @@ pub trait MutDistribution<T> {
     fn mut_sample<R:Rng + ?Sized> (&mut self, r: &mut R) -> T;
 }
 
-pub fn exponential_generator (lambda : f64) -> f64
+pub fn exponential_generator<R: Rng + ?Sized> (lambda : f64, r: &mut R) -> f64
 {
-    Exp::new(lambda).sample(&mut rand::thread_rng())
+    Exp::new(lambda).sample(r)
 }
 
 pub struct ConstantDistribution<T> where T: Copy{
@@ -16,7 +16,7 @@ impl<T> Eq for HeapEntry<T> where T: PartialEq {}
 
 impl<T> PartialOrd for HeapEntry<T> where T: PartialEq {
     fn partial_cmp(&self, other: &HeapEntry<T>) -> Option<Ordering> {
-        Some(other.key.cmp(&self.key))
+        Some(self.cmp(other))
     }
 }
 
@@ -63,10 +63,7 @@ impl<T> FloatBinaryHeap<T> where T: PartialEq {
     }
 
     pub fn peek(&self) -> Option<(f64, &T)> {
-        match self.heap.peek() {
-            None => None,
-            Some(r) => Some(r.to_tuple())
-        }
+        self.heap.peek().map(|r| r.to_tuple())
     }
 
     pub fn pop (&mut self) -> Option<(f64, T)> {
@@ -78,6 +75,11 @@ impl<T> FloatBinaryHeap<T> where T: PartialEq {
         }
     }
 
+    // Entries in no particular order
+    pub fn iter(&self) -> impl Iterator<Item=(f64, &T)> + '_ {
+        self.heap.iter().map(|r| r.to_tuple())
+    }
+
     pub fn len (&self) -> usize {
         self.heap.len()
     }
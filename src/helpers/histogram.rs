@@ -0,0 +1,81 @@
+use std::vec::Vec;
+
+//Histogram of positive values with geometrically growing bins: quantiles are
+//known within a relative error of 'growth - 1', in constant memory per order of magnitude
+#[derive(Debug,Clone)]
+pub struct LogHistogram {
+    min: f64,
+    log_growth: f64,
+    // counts[0] holds the values below 'min', counts[i] those in [min*growth^(i-1), min*growth^i)
+    counts: Vec<usize>,
+    count: usize,
+    sum: f64,
+    sum_squares: f64,
+    max: f64,
+}
+
+impl LogHistogram {
+    pub fn new(min: f64, growth: f64) -> Self {
+        assert!(min > 0. && growth > 1., "Invalid histogram bins");
+        LogHistogram {
+            min,
+            log_growth: growth.ln(),
+            counts: Vec::new(),
+            count: 0,
+            sum: 0.,
+            sum_squares: 0.,
+            max: 0.,
+        }
+    }
+
+    pub fn add(&mut self, value: f64) {
+        let bin = if value < self.min { 0 } else { ((value / self.min).ln() / self.log_growth) as usize + 1 };
+        if bin >= self.counts.len() {
+            self.counts.resize(bin + 1, 0);
+        }
+        self.counts[bin] += 1;
+        self.count += 1;
+        self.sum += value;
+        self.sum_squares += value * value;
+        self.max = self.max.max(value);
+    }
+
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    pub fn mean(&self) -> f64 {
+        if self.count > 0 { self.sum / self.count as f64 } else { f64::NAN }
+    }
+
+    pub fn variance(&self) -> f64 {
+        if self.count > 1 {
+            let n = self.count as f64;
+            ((self.sum_squares - self.sum * self.sum / n) / (n - 1.)).max(0.)
+        }
+        else {
+            f64::NAN
+        }
+    }
+
+    pub fn max(&self) -> f64 {
+        if self.count > 0 { self.max } else { f64::NAN }
+    }
+
+    //Upper bound of the bin holding the p-quantile (capped by the largest value)
+    pub fn quantile(&self, p: f64) -> f64 {
+        if self.count == 0 {
+            return f64::NAN;
+        }
+        let rank = ((p * self.count as f64).ceil() as usize).max(1);
+        let mut seen = 0;
+        for (bin, &c) in self.counts.iter().enumerate() {
+            seen += c;
+            if seen >= rank {
+                let upper = if bin == 0 { self.min } else { self.min * (bin as f64 * self.log_growth).exp() };
+                return upper.min(self.max);
+            }
+        }
+        self.max
+    }
+}
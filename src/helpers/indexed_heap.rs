@@ -0,0 +1,123 @@
+use std::vec::Vec;
+
+//Min-heap of float keys addressed by a dense index, whose keys can be changed or removed in O(log n)
+//Ties are broken in favor of the highest index
+#[derive(Default)]
+pub struct IndexedFloatHeap {
+    heap: Vec<usize>,
+    keys: Vec<f64>,
+    positions: Vec<Option<usize>>,
+}
+
+impl IndexedFloatHeap {
+    pub fn new () -> Self {
+        IndexedFloatHeap {
+            heap: Vec::new(),
+            keys: Vec::new(),
+            positions: Vec::new(),
+        }
+    }
+
+    //Sets the key of 'index', or removes it from the heap if 'key' is None
+    pub fn set (&mut self, index: usize, key: Option<f64>) {
+        if index >= self.positions.len() {
+            self.positions.resize(index + 1, None);
+            self.keys.resize(index + 1, 0.);
+        }
+
+        match (self.positions[index], key) {
+            (None, None) => (),
+            (Some(pos), None) => self.remove_at(pos),
+            (None, Some(k)) => {
+                assert!(!k.is_nan(), "Float is Nan in Heap");
+                self.keys[index] = k;
+                self.heap.push(index);
+                let pos = self.heap.len() - 1;
+                self.positions[index] = Some(pos);
+                self.sift_up(pos);
+            },
+            (Some(pos), Some(k)) => {
+                assert!(!k.is_nan(), "Float is Nan in Heap");
+                self.keys[index] = k;
+                self.sift_up(pos);
+                let pos = self.positions[index].unwrap();
+                self.sift_down(pos);
+            }
+        }
+    }
+
+    pub fn get (&self, index: usize) -> Option<f64> {
+        match self.positions.get(index) {
+            Some(&Some(_)) => Some(self.keys[index]),
+            _ => None
+        }
+    }
+
+    pub fn peek (&self) -> Option<(f64, usize)> {
+        self.heap.first().map(|&i| (self.keys[i], i))
+    }
+
+    pub fn len (&self) -> usize {
+        self.heap.len()
+    }
+
+    pub fn is_empty (&self) -> bool {
+        self.heap.is_empty()
+    }
+
+    fn remove_at (&mut self, pos: usize) {
+        let last = self.heap.len() - 1;
+        self.swap(pos, last);
+        let index = self.heap.pop().unwrap();
+        self.positions[index] = None;
+        if pos < self.heap.len() {
+            self.sift_up(pos);
+            let moved = self.heap[pos];
+            let pos = self.positions[moved].unwrap();
+            self.sift_down(pos);
+        }
+    }
+
+    fn before (&self, a: usize, b: usize) -> bool {
+        let (ia, ib) = (self.heap[a], self.heap[b]);
+        self.keys[ia] < self.keys[ib] || (self.keys[ia] == self.keys[ib] && ia > ib)
+    }
+
+    fn swap (&mut self, a: usize, b: usize) {
+        self.heap.swap(a, b);
+        self.positions[self.heap[a]] = Some(a);
+        self.positions[self.heap[b]] = Some(b);
+    }
+
+    fn sift_up (&mut self, mut pos: usize) {
+        while pos > 0 {
+            let parent = (pos - 1) / 2;
+            if self.before(pos, parent) {
+                self.swap(pos, parent);
+                pos = parent;
+            }
+            else {
+                break;
+            }
+        }
+    }
+
+    fn sift_down (&mut self, mut pos: usize) {
+        loop {
+            let left = 2 * pos + 1;
+            let right = left + 1;
+            let mut first = pos;
+            if left < self.heap.len() && self.before(left, first) {
+                first = left;
+            }
+            if right < self.heap.len() && self.before(right, first) {
+                first = right;
+            }
+            if first == pos {
+                break;
+            }
+            self.swap(pos, first);
+            pos = first;
+        }
+    }
+}
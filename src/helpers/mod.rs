@@ -1,5 +1,12 @@
+pub mod alias;
 pub mod distribution;
 pub mod ewma;
 pub mod float_binaryheap;
+pub mod histogram;
+pub mod indexed_heap;
+pub mod parallel;
+pub mod rng;
+pub mod statistics;
+pub mod steady_state;
 //TODO: update with new OrderedFloat
 //pub mod p2;
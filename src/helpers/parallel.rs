@@ -0,0 +1,37 @@
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize,Ordering};
+use std::thread;
+
+pub fn available_threads() -> usize
+{
+    thread::available_parallelism().map(|n| n.get()).unwrap_or(1)
+}
+
+//Applies 'f' to every input on a pool of 'threads' workers.
+//Results are returned in the order of the inputs, whatever the thread that computed them
+pub fn parallel_map<T,R,F> (inputs: Vec<T>, threads: usize, f: F) -> Vec<R>
+    where T: Send, R: Send, F: Fn(T) -> R + Sync
+{
+    let nb_inputs = inputs.len();
+    let inputs: Vec<Mutex<Option<T>>> = inputs.into_iter().map(|i| Mutex::new(Some(i))).collect();
+    let results: Vec<Mutex<Option<R>>> = (0..nb_inputs).map(|_| Mutex::new(None)).collect();
+    let next_input = AtomicUsize::new(0);
+
+    thread::scope(|s| {
+        for _ in 0..threads.clamp(1, nb_inputs.max(1)) {
+            s.spawn(|| {
+                loop {
+                    let i = next_input.fetch_add(1, Ordering::Relaxed);
+                    if i >= nb_inputs {
+                        break;
+                    }
+                    let input = inputs[i].lock().unwrap().take().unwrap();
+                    let result = f(input);
+                    *results[i].lock().unwrap() = Some(result);
+                }
+            });
+        }
+    });
+
+    results.into_iter().map(|r| r.into_inner().unwrap().unwrap()).collect()
+}
@@ -0,0 +1,38 @@
+extern crate rand;
+
+use rand::{FromEntropy, RngCore, SeedableRng};
+use rand::prng::XorShiftRng;
+
+// The algorithm is pinned (rather than using SmallRng/StdRng) so that
+// a given seed reproduces the same run across rand versions
+pub type SimRng = XorShiftRng;
+
+pub fn rng_from_seed (seed: u64) -> SimRng
+{
+    SimRng::seed_from_u64(seed)
+}
+
+pub fn rng_from_entropy () -> SimRng
+{
+    SimRng::from_entropy()
+}
+
+//Master stream from which every component derives the seed of its own stream
+pub struct SeedSequence(SimRng);
+
+impl SeedSequence {
+    pub fn new (seed: u64) -> Self
+    {
+        SeedSequence(rng_from_seed(seed))
+    }
+
+    pub fn from_entropy () -> Self
+    {
+        SeedSequence(rng_from_entropy())
+    }
+
+    pub fn next_seed (&mut self) -> u64
+    {
+        self.0.next_u64()
+    }
+}
@@ -0,0 +1,96 @@
+extern crate statistical;
+
+use std::f64::consts::PI;
+
+//Inverse CDF of the standard normal distribution (Acklam's algorithm, relative error < 1.2e-9)
+pub fn normal_quantile(p: f64) -> f64
+{
+    assert!(0. < p && p < 1., "Quantile must be in (0,1)");
+
+    let a = [-3.969683028665376e+01, 2.209460984245205e+02, -2.759285104469687e+02,
+             1.38357751867269e+02, -3.066479806614716e+01, 2.506628277459239e+00];
+    let b = [-5.447609879822406e+01, 1.615858368580409e+02, -1.556989798598866e+02,
+             6.680131188771972e+01, -1.328068155288572e+01];
+    let c = [-7.784894002430293e-03, -3.223964580411365e-01, -2.400758277161838e+00,
+             -2.549732539343734e+00, 4.374664141464968e+00, 2.938163982698783e+00];
+    let d = [7.784695709041462e-03, 3.224671290700398e-01, 2.445134137142996e+00,
+             3.754408661907416e+00];
+    let p_low = 0.02425;
+
+    if p < p_low {
+        let q = (-2. * p.ln()).sqrt();
+        (((((c[0]*q + c[1])*q + c[2])*q + c[3])*q + c[4])*q + c[5]) /
+            ((((d[0]*q + d[1])*q + d[2])*q + d[3])*q + 1.)
+    }
+    else if p <= 1. - p_low {
+        let q = p - 0.5;
+        let r = q * q;
+        (((((a[0]*r + a[1])*r + a[2])*r + a[3])*r + a[4])*r + a[5])*q /
+            (((((b[0]*r + b[1])*r + b[2])*r + b[3])*r + b[4])*r + 1.)
+    }
+    else {
+        -normal_quantile(1. - p)
+    }
+}
+
+//Inverse CDF of Student's t distribution with 'dof' degrees of freedom.
+//Exact for 1 and 2 degrees of freedom, Cornish-Fisher expansion otherwise (relative error < 0.2% for dof >= 3)
+pub fn student_t_quantile(p: f64, dof: usize) -> f64
+{
+    assert!(dof > 0, "Student's t distribution needs at least one degree of freedom");
+    match dof {
+        1 => (PI * (p - 0.5)).tan(),
+        2 => (2. * p - 1.) / (2. * p * (1. - p)).sqrt(),
+        _ => {
+            let z = normal_quantile(p);
+            let n = dof as f64;
+            let (z2, z3) = (z * z, z * z * z);
+            let (z5, z7, z9) = (z3 * z2, z3 * z2 * z2, z3 * z3 * z3);
+            z + (z3 + z) / (4. * n)
+              + (5. * z5 + 16. * z3 + 3. * z) / (96. * n * n)
+              + (3. * z7 + 19. * z5 + 17. * z3 - 15. * z) / (384. * n * n * n)
+              + (79. * z9 + 776. * z7 + 1482. * z5 - 1920. * z3 - 945. * z) / (92160. * n * n * n * n)
+        }
+    }
+}
+
+//Mean of i.i.d. samples with its Student-t confidence interval
+#[derive(Debug,Clone)]
+pub struct Estimate {
+    pub samples: usize,
+    pub mean: f64,
+    pub variance: f64,
+    pub half_width: f64,
+}
+
+impl Estimate {
+    pub fn from_samples(samples: &[f64], confidence: f64) -> Self
+    {
+        let n = samples.len();
+        let mean = if n > 0 { statistical::mean(samples) } else { f64::NAN };
+        let (variance, half_width) = if n > 1 {
+            let variance = statistical::variance(samples, Some(mean));
+            (variance, student_t_quantile(0.5 + confidence / 2., n - 1) * (variance / n as f64).sqrt())
+        }
+        else {
+            (f64::NAN, f64::INFINITY)
+        };
+
+        Estimate {
+            samples: n,
+            mean,
+            variance,
+            half_width,
+        }
+    }
+
+    pub fn relative_half_width(&self) -> f64
+    {
+        self.half_width / self.mean.abs()
+    }
+
+    pub fn interval(&self) -> (f64, f64)
+    {
+        (self.mean - self.half_width, self.mean + self.half_width)
+    }
+}
@@ -0,0 +1,68 @@
+//Truncation point detection for the initial transient of a simulation output series
+
+static MSER_BATCH_SIZE: usize = 5;
+
+//MSER-5 (White, 1997): observations are averaged in batches of 5, and the number of batches to delete
+//is the one minimizing the standard error of the mean of the remaining batches.
+//Returns the number of observations to delete, if the optimum lies in the first half of the series
+//(otherwise the series is deemed too short to have reached steady state)
+pub fn mser5_truncation(observations: &[f64]) -> Option<usize>
+{
+    let batches: Vec<f64> = observations.chunks(MSER_BATCH_SIZE)
+        .filter(|c| c.len() == MSER_BATCH_SIZE)
+        .map(|c| c.iter().sum::<f64>() / MSER_BATCH_SIZE as f64)
+        .collect();
+    let m = batches.len();
+    if m < 2 {
+        return None;
+    }
+
+    // Suffix sums give the mean and variance of batches[d..] in O(1) for every d
+    let mut sum = vec![0.; m + 1];
+    let mut sum_sq = vec![0.; m + 1];
+    for d in (0..m).rev() {
+        sum[d] = sum[d+1] + batches[d];
+        sum_sq[d] = sum_sq[d+1] + batches[d] * batches[d];
+    }
+
+    let mut best = (0, f64::INFINITY);
+    for d in 0..(m/2 + 1) {
+        let n = (m - d) as f64;
+        let mean = sum[d] / n;
+        let mser = (sum_sq[d] - n * mean * mean).max(0.) / (n * n);
+        if mser < best.1 {
+            best = (d, mser);
+        }
+    }
+
+    if best.0 < m / 2 { Some(best.0 * MSER_BATCH_SIZE) } else { None }
+}
+
+//Single-replication variant of Welch's graphical procedure: the series is smoothed with a moving average
+//of half-width 'window', and the warm-up ends at the first point after which the smoothed series stays within
+//'tolerance' (relative) of the mean of the second half of the series.
+//Returns the number of observations to delete, if this point lies in the first half of the series
+pub fn welch_truncation(observations: &[f64], window: usize, tolerance: f64) -> Option<usize>
+{
+    let n = observations.len();
+    if n < 2 * (2 * window + 1) {
+        return None;
+    }
+
+    let reference = observations[n/2..].iter().sum::<f64>() / (n - n/2) as f64;
+
+    let mut prefix = vec![0.; n + 1];
+    for i in 0..n {
+        prefix[i+1] = prefix[i] + observations[i];
+    }
+    let moving_average = |i: usize| (prefix[i + window + 1] - prefix[i - window]) / (2 * window + 1) as f64;
+
+    let mut truncation = window;
+    for i in window..(n - window) {
+        if (moving_average(i) - reference).abs() > tolerance * reference.abs() {
+            truncation = i + 1;
+        }
+    }
+
+    if truncation <= n / 2 { Some(truncation) } else { None }
+}
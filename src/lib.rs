@@ -0,0 +1,17 @@
+#![allow(dead_code)]
+#![allow(clippy::upper_case_acronyms)]
+#![warn(unused_imports)]
+
+pub mod analytic;
+pub mod queues;
+pub mod caches;
+pub mod fog_cloud_sim;
+pub mod autoscaling_sim;
+pub mod helpers;
+pub mod network_config;
+
+extern crate rand;
+extern crate zipf;
+extern crate serde;
+#[macro_use]
+extern crate serde_derive;
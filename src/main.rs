@@ -1,17 +1,8 @@
-#![allow(dead_code)]
-#![warn(unused_imports)]
-
-mod queues;
-mod caches;
-mod fog_cloud_sim;
-mod autoscaling_sim;
-mod helpers;
+extern crate queue_sim;
 
 use std::env;
 
-extern crate rand;
-extern crate zipf;
-
+use queue_sim::{analytic,autoscaling_sim,fog_cloud_sim,network_config};
 
 
 fn main() {
@@ -24,6 +15,12 @@ fn main() {
     else if exp == "fog" {
         fog_cloud_sim::run(args);
     }
+    else if exp == "run" {
+        network_config::run(args);
+    }
+    else if exp == "mva" {
+        analytic::mva::run(args);
+    }
     else {
         panic!("Could not recognize experiment: {}", exp);
     }
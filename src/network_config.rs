@@ -0,0 +1,1065 @@
+extern crate rand;
+extern crate serde_json;
+extern crate toml;
+
+use std::collections::{BTreeMap,HashMap};
+use std::env;
+use std::fs::File;
+use std::io::{Read,Write};
+use std::sync::{Arc,Mutex};
+use std::time::Duration;
+
+use rand::Rng;
+use rand::distributions::{Distribution,Exp};
+
+use helpers::distribution::{ConstantDistribution,MutDistribution,OffsetExp,MMPP2};
+
+use queues::{Queue,sampler_from};
+use queues::queueing_network::{Overflow,QNet};
+use queues::retry::{Backoff,Jitter,RetryPolicy};
+use queues::service::{FromAttribute,PerClass,PerContent,ServiceDistribution};
+use queues::simulation::{Simulation,StopCondition};
+use queues::warmup::Warmup;
+use queues::poisson_generator::PoissonGenerator;
+use queues::generator::Generator;
+use queues::client_population::ClientPopulation;
+use queues::zipfgen::ZipfGenerator;
+use queues::trace_generator::TraceGenerator;
+use queues::breakdowns::{Breakdowns,Interruption};
+use queues::mg1ps::{AggregatingMG1PS,MG1PS};
+use queues::mginf::MGINF;
+use queues::mgkfifo::{MGKFIFO,ServerSelection};
+use queues::mgklifo::MGKLIFO;
+use queues::mgkps::MGKPS;
+use queues::mgkpriority::{MGKPriority,Preemption};
+use queues::round_robin::RoundRobin;
+use queues::size_based::{MG1LAS,MG1SizeBased,SizePolicy};
+use queues::weighted_ps::{MG1DPS,MG1GPS};
+use queues::file_logger::FileLogger;
+use queues::passthrough::PassthroughQueue;
+use queues::classifier::{AttributeValue,Classifier};
+use queues::request::{Attribute,Request};
+use queues::sink::Sink;
+
+use caches::Cache;
+use caches::lru_cache::LruCache;
+use caches::abf_fpga_cache::AgingBloomFilterFPGA;
+use caches::{PerfectLfu,RandomAccept};
+
+/* Description of a network, read from a TOML (or JSON) file. Queues are referred to by name, eg:
+ *
+ *   [run]
+ *   seed = 42
+ *   until = 1000.0
+ *
+ *   [[queue]]
+ *   name = "source"
+ *   type = "poisson_generator"
+ *   rate = 0.8
+ *   routing = { type = "fixed", to = "server" }
+ *
+ *   [[queue]]
+ *   name = "server"
+ *   type = "mg1ps"
+ *   service = { type = "exp", rate = 1.0 }
+ *   routing = { type = "fixed", to = "log" }
+ *
+ *   [[queue]]
+ *   name = "log"
+ *   type = "file_logger"
+ *   file = "results.csv"
+ */
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct NetworkConfig {
+    #[serde(default)]
+    pub run: RunConfig,
+    #[serde(default, rename = "cache")]
+    pub caches: Vec<CacheConfig>,
+    #[serde(rename = "queue")]
+    pub queues: Vec<QueueConfig>,
+}
+
+#[derive(Deserialize,Default)]
+#[serde(deny_unknown_fields)]
+pub struct RunConfig {
+    pub seed: Option<u64>,
+    // Simulated time horizon
+    pub until: Option<f64>,
+    // Queue at which departures and response times are measured
+    pub sink: Option<String>,
+    // Number of arrivals at the sink after which the run stops
+    pub departures: Option<usize>,
+    // Wall-clock budget, in seconds
+    pub wall_clock: Option<f64>,
+    pub warmup: Option<WarmupConfig>,
+    // Collects the statistics of every queue and prints them at the end of the run
+    #[serde(default)]
+    pub statistics: bool,
+    // JSON file to which the statistics are written (implies 'statistics')
+    pub report: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum WarmupConfig {
+    Time { until: f64 },
+    Departures { count: usize },
+    Mser5 { min_observations: usize },
+    Welch { window: usize, tolerance: f64, min_observations: usize },
+}
+
+pub struct CacheConfig {
+    pub name: String,
+    pub kind: CacheKind,
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum CacheKind {
+    Lru { size: usize },
+    Abf { size: usize, #[serde(default = "default_miss_rate")] miss_rate: f64 },
+    Lfu { size: usize },
+    RandomAccept { probability: f64 },
+}
+
+pub struct QueueConfig {
+    pub name: String,
+    pub kind: QueueKind,
+    pub routing: Option<RoutingConfig>,
+    // What happens to requests routed to this queue once it reached its capacity
+    pub overflow: Option<OverflowConfig>,
+    // Whether requests refuse to join this queue, given its load
+    pub balking: Option<BalkingConfig>,
+    // Where the requests that abandon or balk at this queue go (they are discarded otherwise)
+    pub abandonment_sink: Option<String>,
+    // Client-side retries of the requests leaving this queue
+    pub retry: Option<RetryConfig>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RetryConfig {
+    // Queue whose arrival completes a request (typically the file logger)
+    pub completion: String,
+    pub timeout: Option<f64>,
+    pub max_attempts: usize,
+    pub backoff: BackoffConfig,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BackoffConfig {
+    pub initial: f64,
+    #[serde(default = "default_backoff_multiplier")]
+    pub multiplier: f64,
+    pub max: Option<f64>,
+    #[serde(default)]
+    pub jitter: JitterConfig,
+}
+
+#[derive(Deserialize,Clone,Copy,PartialEq,Default)]
+#[serde(rename_all = "snake_case")]
+pub enum JitterConfig {
+    #[default]
+    None,
+    Full,
+    Equal,
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum BalkingConfig {
+    // Requests always refuse to join once the load reached 'load'
+    Threshold { load: usize },
+    // Requests refuse to join with probability load / max_load
+    Proportional { max_load: usize },
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum OverflowConfig {
+    // Dropped requests go to 'sink' if given, and are discarded otherwise
+    Drop { sink: Option<String> },
+    BlockAfterService,
+    BlockBeforeService,
+}
+
+// Keys that every queue has, whatever its type
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CommonQueueFields {
+    name: String,
+    routing: Option<RoutingConfig>,
+    overflow: Option<OverflowConfig>,
+    balking: Option<BalkingConfig>,
+    abandonment_sink: Option<String>,
+    retry: Option<RetryConfig>,
+}
+
+static COMMON_QUEUE_KEYS: [&str; 6] = ["name", "routing", "overflow", "balking", "abandonment_sink", "retry"];
+
+type Fields = serde_json::Map<String, serde_json::Value>;
+
+// Serde does not reject the unknown keys of a struct with a flattened field (a typo would be silently ignored):
+// the keys common to all kinds are taken out, and the remaining ones are deserialized as the kind, which rejects
+// the unknown ones
+fn split_kind<'de, D, C, K>(deserializer: D, common_keys: &[&str]) -> Result<(C, K), D::Error>
+    where D: serde::Deserializer<'de>, C: serde::de::DeserializeOwned, K: serde::de::DeserializeOwned
+{
+    use serde::de::Error;
+
+    let mut kind: Fields = serde::Deserialize::deserialize(deserializer)?;
+    let common: Fields = common_keys.iter().filter_map(|&k| kind.remove(k).map(|v| (k.to_string(), v))).collect();
+    let name = common.get("name").and_then(|n| n.as_str()).map_or(String::new(), |n| format!("{}: ", n));
+    let common = serde_json::from_value(serde_json::Value::Object(common)).map_err(D::Error::custom)?;
+    let kind = serde_json::from_value(serde_json::Value::Object(kind)).map_err(|e| D::Error::custom(format!("{}{}", name, e)))?;
+    Ok((common, kind))
+}
+
+impl<'de> serde::Deserialize<'de> for QueueConfig {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error> where D: serde::Deserializer<'de> {
+        let (common, kind): (CommonQueueFields, QueueKind) = split_kind(deserializer, &COMMON_QUEUE_KEYS)?;
+        Ok(QueueConfig {
+            name: common.name,
+            kind,
+            routing: common.routing,
+            overflow: common.overflow,
+            balking: common.balking,
+            abandonment_sink: common.abandonment_sink,
+            retry: common.retry,
+        })
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CommonCacheFields {
+    name: String,
+}
+
+impl<'de> serde::Deserialize<'de> for CacheConfig {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error> where D: serde::Deserializer<'de> {
+        let (common, kind): (CommonCacheFields, CacheKind) = split_kind(deserializer, &["name"])?;
+        Ok(CacheConfig { name: common.name, kind })
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum QueueKind {
+    PoissonGenerator { rate: f64, #[serde(default = "default_content")] content: usize },
+    Generator { interarrival: DistributionConfig },
+    // Requests complete when they are routed back to the population, or when they arrive at 'completion' if given
+    ClientPopulation { clients: usize, think_time: DistributionConfig, completion: Option<String> },
+    ZipfGenerator { alpha: f64, catalogue_size: usize, interarrival: DistributionConfig, arrivals: usize },
+    TraceGenerator { file: String, #[serde(default = "default_delimiter")] delimiter: char },
+    // Requests in service abandon once their 'patience' has elapsed
+    // The server can have 'breakdowns', and take a 'vacation' (a distribution) whenever it is idle
+    Mg1ps { #[serde(default = "default_work_rate")] work_rate: f64, service: ServiceConfig, capacity: Option<usize>, patience: Option<DistributionConfig>,
+            breakdowns: Option<BreakdownsConfig>, vacation: Option<DistributionConfig> },
+    AggregatingMg1ps { #[serde(default = "default_work_rate")] work_rate: f64, service: ServiceConfig, capacity: Option<usize> },
+    // The server of every request can have 'breakdowns'
+    Mginf { #[serde(default = "default_work_rate")] work_rate: f64, service: ServiceConfig, breakdowns: Option<BreakdownsConfig> },
+    // Requests waiting for a server abandon once their 'patience' has elapsed
+    // Servers can have different 'speeds' (relative to the work rate), and free ones are taken following 'selection'
+    // Servers can have 'breakdowns', and take a 'vacation' whenever they are idle
+    Mgkfifo { servers: usize, #[serde(default = "default_work_rate")] work_rate: f64, service: ServiceConfig, capacity: Option<usize>, patience: Option<DistributionConfig>,
+              speeds: Option<Vec<f64>>, #[serde(default)] selection: SelectionConfig,
+              breakdowns: Option<BreakdownsConfig>, vacation: Option<DistributionConfig> },
+    // Requests are served by the priority of their class (class 0 first), preempting lower ones or not
+    Priority { servers: usize, #[serde(default = "default_work_rate")] work_rate: f64, service: ServiceConfig, #[serde(default)] preemption: PreemptionConfig, capacity: Option<usize> },
+    // Processor sharing over 'servers' servers, with at most 'limit' requests in service (the others wait FIFO)
+    Mgkps { servers: usize, #[serde(default = "default_work_rate")] work_rate: f64, service: ServiceConfig, limit: Option<usize>, capacity: Option<usize> },
+    // Latest arrived requests first, preempting the earliest ones in service or not
+    Lifo { servers: usize, #[serde(default = "default_work_rate")] work_rate: f64, service: ServiceConfig, #[serde(default)] preemption: PreemptionConfig, capacity: Option<usize> },
+    // Processor sharing with a weight per class: per request (dps), or per class with requests served FIFO in their class (gps)
+    Dps { weights: Vec<f64>, #[serde(default = "default_work_rate")] work_rate: f64, service: ServiceConfig, capacity: Option<usize> },
+    Gps { weights: Vec<f64>, #[serde(default = "default_work_rate")] work_rate: f64, service: ServiceConfig, capacity: Option<usize> },
+    // Single server serving its requests in turn for a 'quantum' of time, losing an 'overhead' at every switch
+    RoundRobin { quantum: f64, #[serde(default = "default_work_rate")] work_rate: f64, service: ServiceConfig, overhead: Option<DistributionConfig>, capacity: Option<usize> },
+    // Single server scheduling by size: srpt, sjf, fsp or las
+    SizeBased { policy: SizePolicyConfig, #[serde(default = "default_work_rate")] work_rate: f64, service: ServiceConfig, capacity: Option<usize> },
+    FileLogger { file: String, #[serde(default = "default_buffer_size")] buffer_size: usize },
+    Passthrough {},
+    // Draws the class of every request with the weights of 'classes', and sets the attributes of its class
+    Classifier { classes: Vec<ClassConfig> },
+    Sink {},
+}
+
+impl QueueKind {
+    // Checks the parameters that the queues only check with assertions, in a network with 'classes' request classes
+    pub fn check(&self, classes: usize) -> Result<(), String> {
+        let (servers, work_rate, service) = match *self {
+            QueueKind::Mgkfifo { servers, work_rate, ref service, .. } |
+            QueueKind::Priority { servers, work_rate, ref service, .. } |
+            QueueKind::Mgkps { servers, work_rate, ref service, .. } |
+            QueueKind::Lifo { servers, work_rate, ref service, .. } => (Some(servers), Some(work_rate), Some(service)),
+            QueueKind::Mg1ps { work_rate, ref service, .. } |
+            QueueKind::AggregatingMg1ps { work_rate, ref service, .. } |
+            QueueKind::Mginf { work_rate, ref service, .. } |
+            QueueKind::Dps { work_rate, ref service, .. } |
+            QueueKind::Gps { work_rate, ref service, .. } |
+            QueueKind::RoundRobin { work_rate, ref service, .. } |
+            QueueKind::SizeBased { work_rate, ref service, .. } => (None, Some(work_rate), Some(service)),
+            QueueKind::PoissonGenerator { rate, .. } => (None, Some(rate), None),
+            _ => (None, None, None),
+        };
+        if servers == Some(0) {
+            return Err("there must be at least one server".to_string());
+        }
+        if work_rate.is_some_and(|r| r <= 0. || !r.is_finite()) {
+            return Err("the rate must be positive".to_string());
+        }
+        if let Some(service) = service {
+            service.check(classes).map_err(|e| format!("service: {}", e))?;
+        }
+        match *self {
+            QueueKind::Dps { ref weights, .. } |
+            QueueKind::Gps { ref weights, .. } if weights.len() < classes =>
+                return Err(format!("no weight for class {}", weights.len())),
+            QueueKind::Mg1ps { vacation: Some(ref v), .. } |
+            QueueKind::Mgkfifo { vacation: Some(ref v), .. } if v.mean() == Some(0.) =>
+                return Err("vacation: vacations must last".to_string()),
+            _ => {},
+        }
+
+        let distributions: Vec<(&str, &DistributionConfig)> = match *self {
+            QueueKind::Generator { ref interarrival } |
+            QueueKind::ZipfGenerator { ref interarrival, .. } => vec![("interarrival", interarrival)],
+            QueueKind::ClientPopulation { ref think_time, .. } => vec![("think_time", think_time)],
+            QueueKind::Mg1ps { ref patience, ref breakdowns, ref vacation, .. } |
+            QueueKind::Mgkfifo { ref patience, ref breakdowns, ref vacation, .. } =>
+                patience.iter().map(|d| ("patience", d))
+                    .chain(breakdowns.iter().flat_map(|b| vec![("up_time", &b.up_time), ("repair_time", &b.repair_time)]))
+                    .chain(vacation.iter().map(|d| ("vacation", d)))
+                    .collect(),
+            QueueKind::Mginf { ref breakdowns, .. } =>
+                breakdowns.iter().flat_map(|b| vec![("up_time", &b.up_time), ("repair_time", &b.repair_time)]).collect(),
+            QueueKind::RoundRobin { ref overhead, .. } => overhead.iter().map(|d| ("overhead", d)).collect(),
+            QueueKind::Classifier { ref classes } => classes.iter().flat_map(|c| c.attributes.iter()).filter_map(|(key, a)| match *a {
+                AttributeConfig::Random(ref d) => Some((key.as_str(), d)),
+                _ => None,
+            }).collect(),
+            _ => Vec::new(),
+        };
+        distributions.into_iter().try_for_each(|(key, d)| d.check().map_err(|e| format!("{}: {}", key, e)))
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ClassConfig {
+    pub weight: f64,
+    #[serde(default)]
+    pub attributes: BTreeMap<String, AttributeConfig>,
+}
+
+// Constants, or a distribution sampled for every request
+#[derive(Deserialize)]
+#[serde(untagged)]
+pub enum AttributeConfig {
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    Text(String),
+    Random(DistributionConfig),
+}
+
+// Work brought by a request: i.i.d. samples of a distribution, a distribution per class (class i uses
+// per_class[i], other classes 'default') or per content, or the value of a numeric attribute times 'scale'.
+// The default is required unless every request is known to have a distribution (see check)
+#[derive(Deserialize,Clone)]
+#[serde(untagged, deny_unknown_fields)]
+pub enum ServiceConfig {
+    Distribution(DistributionConfig),
+    PerClass { per_class: Vec<DistributionConfig>, default: Option<DistributionConfig> },
+    PerContent { per_content: Vec<ContentServiceConfig>, default: Option<DistributionConfig> },
+    Attribute { attribute: String, #[serde(default = "default_scale")] scale: f64, default: Option<f64> },
+}
+
+#[derive(Deserialize,Clone)]
+#[serde(deny_unknown_fields)]
+pub struct ContentServiceConfig {
+    pub content: usize,
+    pub service: DistributionConfig,
+}
+
+#[derive(Deserialize,Clone)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum DistributionConfig {
+    Exp { rate: f64 },
+    Constant { value: f64 },
+    OffsetExp { offset: f64, rate: f64 },
+    Mmpp2 { lambda1: f64, mu1: f64, lambda2: f64, mu2: f64 },
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum RoutingConfig {
+    // Always goes to 'to', updating the given caches with the content of the request
+    Fixed { to: String, #[serde(default)] update_caches: Vec<String> },
+    // Goes to to[i] with probability probabilities[i]
+    Probabilistic { to: Vec<String>, probabilities: Vec<f64> },
+    // Goes to 'hit' if the content of the request is in 'cache', to 'miss' otherwise
+    Cache { cache: String, hit: String, miss: String, #[serde(default)] update: CacheUpdate },
+    // Goes to the destination whose queue in 'load_of' (defaults to 'to') has the smallest load,
+    // among 'choices' destinations drawn at random (defaults to all of them)
+    LeastLoaded { to: Vec<String>, load_of: Option<Vec<String>>, choices: Option<usize> },
+    // Requests of class i go to to[i], and those of other classes to 'default'
+    ByClass { to: Vec<String>, default: Option<String> },
+}
+
+// Servers fail after an 'up_time', and are repaired after a 'repair_time'
+#[derive(Deserialize,Clone)]
+#[serde(deny_unknown_fields)]
+pub struct BreakdownsConfig {
+    pub up_time: DistributionConfig,
+    pub repair_time: DistributionConfig,
+    #[serde(default)]
+    pub interruption: InterruptionConfig,
+}
+
+#[derive(Deserialize,Clone,Copy,PartialEq,Default)]
+#[serde(rename_all = "snake_case")]
+pub enum InterruptionConfig {
+    #[default]
+    Resume,
+    Restart,
+    Reroute,
+}
+
+impl BreakdownsConfig {
+    pub fn build(&self) -> Breakdowns {
+        let interruption = match self.interruption {
+            InterruptionConfig::Resume => Interruption::Resume,
+            InterruptionConfig::Restart => Interruption::Restart,
+            InterruptionConfig::Reroute => Interruption::Reroute,
+        };
+        Breakdowns::new(sampler_from(self.up_time.build()), sampler_from(self.repair_time.build()), interruption)
+    }
+}
+
+#[derive(Deserialize,Clone,Copy,PartialEq,Default)]
+#[serde(rename_all = "snake_case")]
+pub enum SelectionConfig {
+    #[default]
+    FastestFree,
+    RandomFree,
+    RoundRobin,
+}
+
+#[derive(Deserialize,Clone,Copy,PartialEq,Default)]
+#[serde(rename_all = "snake_case")]
+pub enum PreemptionConfig {
+    #[default]
+    None,
+    Resume,
+    Repeat,
+}
+
+impl PreemptionConfig {
+    pub fn build(self) -> Preemption {
+        match self {
+            PreemptionConfig::None => Preemption::None,
+            PreemptionConfig::Resume => Preemption::Resume,
+            PreemptionConfig::Repeat => Preemption::Repeat,
+        }
+    }
+}
+
+#[derive(Deserialize,Clone,Copy,PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum SizePolicyConfig {
+    Srpt,
+    Sjf,
+    Fsp,
+    Las,
+}
+
+#[derive(Deserialize,Clone,Copy,PartialEq,Default)]
+#[serde(rename_all = "snake_case")]
+pub enum CacheUpdate {
+    #[default]
+    Never,
+    OnHit,
+    Always,
+}
+
+fn default_miss_rate() -> f64 { 0.01 }
+fn default_content() -> usize { 1 }
+fn default_delimiter() -> char { ' ' }
+fn default_work_rate() -> f64 { 1. }
+fn default_buffer_size() -> usize { 1024 }
+fn default_backoff_multiplier() -> f64 { 2. }
+fn default_scale() -> f64 { 1. }
+
+// Service and inter-arrival distributions chosen at runtime
+pub enum AnyDistribution {
+    Exp(Exp),
+    Constant(ConstantDistribution<f64>),
+    OffsetExp(OffsetExp),
+    Mmpp2(MMPP2),
+}
+
+impl MutDistribution<f64> for AnyDistribution {
+    fn mut_sample<R: Rng + ?Sized> (&mut self, r: &mut R) -> f64 {
+        match *self {
+            AnyDistribution::Exp(ref d) => d.sample(r),
+            AnyDistribution::Constant(ref d) => d.sample(r),
+            AnyDistribution::OffsetExp(ref d) => d.sample(r),
+            AnyDistribution::Mmpp2(ref mut d) => d.mut_sample(r),
+        }
+    }
+}
+
+impl DistributionConfig {
+    pub fn build(&self) -> AnyDistribution {
+        match *self {
+            DistributionConfig::Exp { rate } => AnyDistribution::Exp(Exp::new(rate)),
+            DistributionConfig::Constant { value } => AnyDistribution::Constant(ConstantDistribution::new(value)),
+            DistributionConfig::OffsetExp { offset, rate } => AnyDistribution::OffsetExp(OffsetExp::new(offset, rate)),
+            DistributionConfig::Mmpp2 { lambda1, mu1, lambda2, mu2 } => AnyDistribution::Mmpp2(MMPP2::new(lambda1, mu1, lambda2, mu2)),
+        }
+    }
+
+    // Whether the parameters are valid, as the distributions panic otherwise
+    pub fn check(&self) -> Result<(), String> {
+        let positive = |x: f64| x > 0. && x.is_finite();
+        let valid = match *self {
+            DistributionConfig::Exp { rate } => positive(rate),
+            DistributionConfig::Constant { value } => value >= 0. && value.is_finite(),
+            DistributionConfig::OffsetExp { offset, rate } => offset >= 0. && offset.is_finite() && positive(rate),
+            DistributionConfig::Mmpp2 { lambda1, mu1, lambda2, mu2 } => [lambda1, mu1, lambda2, mu2].iter().all(|&x| positive(x)),
+        };
+        if valid { Ok(()) } else { Err("invalid distribution parameters".to_string()) }
+    }
+
+    // Mean of the distribution, when it is known in closed form
+    pub fn mean(&self) -> Option<f64> {
+        match *self {
+            DistributionConfig::Exp { rate } => Some(1. / rate),
+            DistributionConfig::Constant { value } => Some(value),
+            DistributionConfig::OffsetExp { offset, rate } => Some(offset + 1. / rate),
+            DistributionConfig::Mmpp2 { .. } => None,
+        }
+    }
+}
+
+pub enum AnyService {
+    Distribution(AnyDistribution),
+    PerClass(PerClass<AnyDistribution>),
+    PerContent(PerContent<AnyDistribution>),
+    Attribute(FromAttribute),
+}
+
+impl ServiceDistribution for AnyService {
+    fn sample_work<R: Rng + ?Sized> (&mut self, req: &Request, r: &mut R) -> f64 {
+        match *self {
+            AnyService::Distribution(ref mut d) => d.mut_sample(r),
+            AnyService::PerClass(ref mut d) => d.sample_work(req, r),
+            AnyService::PerContent(ref mut d) => d.sample_work(req, r),
+            AnyService::Attribute(ref mut d) => d.sample_work(req, r),
+        }
+    }
+}
+
+impl ServiceConfig {
+    pub fn build(&self) -> AnyService {
+        match *self {
+            ServiceConfig::Distribution(ref d) => AnyService::Distribution(d.build()),
+            ServiceConfig::PerClass { ref per_class, ref default } =>
+                AnyService::PerClass(PerClass::new(per_class.iter().map(|d| d.build()).collect(), default.as_ref().map(|d| d.build()))),
+            ServiceConfig::PerContent { ref per_content, ref default } => {
+                let default = default.as_ref().expect("A per-content service needs a default (see check)");
+                AnyService::PerContent(PerContent::new(per_content.iter().map(|c| (c.content, c.service.build())).collect(), default.build()))
+            },
+            ServiceConfig::Attribute { ref attribute, scale, default } => {
+                let default = default.expect("An attribute service needs a default (see check)");
+                AnyService::Attribute(FromAttribute::new(attribute, scale, default))
+            },
+        }
+    }
+
+    // With requests of classes 0 to classes-1: contents and attributes are not known in advance
+    pub fn check(&self, classes: usize) -> Result<(), String> {
+        match *self {
+            ServiceConfig::Distribution(ref d) => d.check(),
+            ServiceConfig::PerClass { ref per_class, ref default } => {
+                if default.is_none() && per_class.len() < classes {
+                    return Err(format!("no distribution for class {}, and no default", per_class.len()));
+                }
+                per_class.iter().chain(default.iter()).try_for_each(|d| d.check())
+            },
+            ServiceConfig::PerContent { ref per_content, ref default } => {
+                let default = default.as_ref().ok_or("a per-content service needs a default")?;
+                per_content.iter().map(|c| &c.service).chain(Some(default)).try_for_each(|d| d.check())
+            },
+            ServiceConfig::Attribute { scale, default, .. } => {
+                if default.is_none_or(|d| d < 0. || !d.is_finite()) {
+                    return Err("an attribute service needs a non-negative default".to_string());
+                }
+                if scale >= 0. && scale.is_finite() { Ok(()) } else { Err("the scale must be non-negative".to_string()) }
+            },
+        }
+    }
+
+    // Mean work, when it does not depend on the requests
+    pub fn mean(&self) -> Option<f64> {
+        match *self {
+            ServiceConfig::Distribution(ref d) => d.mean(),
+            _ => None,
+        }
+    }
+}
+
+type SharedCache = Arc<Mutex<dyn Cache<usize> + Send>>;
+
+impl NetworkConfig {
+    pub fn from_file(filename: &str) -> Result<Self, String> {
+        let mut content = String::new();
+        File::open(filename).and_then(|mut f| f.read_to_string(&mut content))
+            .map_err(|e| format!("Could not read {}: {}", filename, e))?;
+
+        if filename.ends_with(".json") {
+            serde_json::from_str(&content).map_err(|e| format!("Invalid network description {}: {}", filename, e))
+        }
+        else {
+            toml::from_str(&content).map_err(|e| format!("Invalid network description {}: {}", filename, e))
+        }
+    }
+
+    pub fn build(&self) -> Result<QNet, String> {
+        let mut qn = match self.run.seed {
+            Some(seed) => QNet::from_seed(seed),
+            None => QNet::new()
+        };
+
+        let mut caches: HashMap<String, SharedCache> = HashMap::new();
+        for c in &self.caches {
+            let cache: SharedCache = match c.kind {
+                CacheKind::Lru { size } => Arc::new(Mutex::new(LruCache::new(size))),
+                CacheKind::Abf { size, miss_rate } => Arc::new(Mutex::new(AgingBloomFilterFPGA::new(size, miss_rate))),
+                CacheKind::Lfu { size } => Arc::new(Mutex::new(PerfectLfu::new(size))),
+                CacheKind::RandomAccept { probability } => {
+                    let mut filter = RandomAccept::from_value(probability)
+                        .map_err(|_| format!("Cache {}: probability must be in [0,1]", c.name))?;
+                    filter.reseed(qn.derive_seed());
+                    Arc::new(Mutex::new(filter))
+                }
+            };
+            if caches.insert(c.name.clone(), cache).is_some() {
+                return Err(format!("Duplicate cache name: {}", c.name));
+            }
+        }
+
+        // Requests are of class 0 unless a classifier draws another one
+        let classes = self.queues.iter().filter_map(|q| match q.kind {
+            QueueKind::Classifier { ref classes } => Some(classes.len()),
+            _ => None,
+        }).max().unwrap_or(1);
+
+        let mut indices: HashMap<String, usize> = HashMap::new();
+        for q in &self.queues {
+            q.kind.check(classes).map_err(|e| format!("Queue {}: {}", q.name, e))?;
+            let queue: Box<dyn Queue> = match q.kind {
+                QueueKind::PoissonGenerator { rate, content } =>
+                    Box::new(PoissonGenerator::new(rate, ConstantDistribution::new(content))),
+                QueueKind::Generator { ref interarrival } =>
+                    Box::new(Generator::new(interarrival.build())),
+                QueueKind::ClientPopulation { clients, ref think_time, .. } =>
+                    Box::new(ClientPopulation::new(clients, think_time.build())),
+                QueueKind::ZipfGenerator { alpha, catalogue_size, ref interarrival, arrivals } =>
+                    Box::new(ZipfGenerator::new(alpha, catalogue_size, interarrival.build(), arrivals)),
+                QueueKind::TraceGenerator { ref file, delimiter } =>
+                    Box::new(TraceGenerator::new(file, delimiter)),
+                QueueKind::Mg1ps { work_rate, ref service, capacity, ref patience, ref breakdowns, ref vacation } => {
+                    let mut q = MG1PS::new(work_rate, service.build());
+                    if let Some(c) = capacity {
+                        q.set_capacity(c);
+                    }
+                    if let Some(ref p) = *patience {
+                        q.set_patience(sampler_from(p.build()));
+                    }
+                    if let Some(ref b) = *breakdowns {
+                        q.set_breakdowns(b.build());
+                    }
+                    if let Some(ref v) = *vacation {
+                        q.set_vacation(sampler_from(v.build()));
+                    }
+                    Box::new(q)
+                },
+                QueueKind::AggregatingMg1ps { work_rate, ref service, capacity } => {
+                    let mut q = AggregatingMG1PS::new(work_rate, service.build());
+                    if let Some(c) = capacity {
+                        q.set_capacity(c);
+                    }
+                    Box::new(q)
+                },
+                QueueKind::Mginf { work_rate, ref service, ref breakdowns } => {
+                    let mut q = MGINF::new(work_rate, service.build());
+                    if let Some(ref b) = *breakdowns {
+                        q.set_breakdowns(b.build());
+                    }
+                    Box::new(q)
+                },
+                QueueKind::Mgkfifo { servers, work_rate, ref service, capacity, ref patience, ref speeds, selection, ref breakdowns, ref vacation } => {
+                    let mut fifo = MGKFIFO::new(servers, work_rate, service.build());
+                    if let Some(ref speeds) = *speeds {
+                        if speeds.len() != servers || speeds.iter().any(|&s| s <= 0. || s.is_nan()) {
+                            return Err(format!("Queue {}: there must be one positive speed per server", q.name));
+                        }
+                        fifo.set_speeds(speeds);
+                    }
+                    fifo.set_selection(match selection {
+                        SelectionConfig::FastestFree => ServerSelection::FastestFree,
+                        SelectionConfig::RandomFree => ServerSelection::RandomFree,
+                        SelectionConfig::RoundRobin => ServerSelection::RoundRobin,
+                    });
+                    if let Some(c) = capacity {
+                        fifo.set_capacity(c);
+                    }
+                    if let Some(ref p) = *patience {
+                        fifo.set_patience(sampler_from(p.build()));
+                    }
+                    if let Some(ref b) = *breakdowns {
+                        fifo.set_breakdowns(b.build());
+                    }
+                    if let Some(ref v) = *vacation {
+                        fifo.set_vacation(sampler_from(v.build()));
+                    }
+                    Box::new(fifo)
+                },
+                QueueKind::Mgkps { servers, work_rate, ref service, limit, capacity } => {
+                    if limit == Some(0) {
+                        return Err(format!("Queue {}: the limit of requests in service must be positive", q.name));
+                    }
+                    let mut ps = MGKPS::new(servers, work_rate, service.build());
+                    if let Some(m) = limit {
+                        ps.set_limit(m);
+                    }
+                    if let Some(c) = capacity {
+                        ps.set_capacity(c);
+                    }
+                    Box::new(ps)
+                },
+                QueueKind::Priority { servers, work_rate, ref service, preemption, capacity } => {
+                    let mut q = MGKPriority::new(servers, work_rate, preemption.build(), service.build());
+                    if let Some(c) = capacity {
+                        q.set_capacity(c);
+                    }
+                    Box::new(q)
+                },
+                QueueKind::Lifo { servers, work_rate, ref service, preemption, capacity } => {
+                    let mut q = MGKLIFO::new(servers, work_rate, preemption.build(), service.build());
+                    if let Some(c) = capacity {
+                        q.set_capacity(c);
+                    }
+                    Box::new(q)
+                },
+                QueueKind::Dps { ref weights, work_rate, ref service, capacity } => {
+                    if weights.iter().any(|&w| w <= 0. || w.is_nan()) {
+                        return Err(format!("Queue {}: weights must be positive", q.name));
+                    }
+                    let mut q = MG1DPS::new(work_rate, weights.clone(), service.build());
+                    if let Some(c) = capacity {
+                        q.set_capacity(c);
+                    }
+                    Box::new(q)
+                },
+                QueueKind::Gps { ref weights, work_rate, ref service, capacity } => {
+                    if weights.iter().any(|&w| w <= 0. || w.is_nan()) {
+                        return Err(format!("Queue {}: weights must be positive", q.name));
+                    }
+                    let mut q = MG1GPS::new(work_rate, weights.clone(), service.build());
+                    if let Some(c) = capacity {
+                        q.set_capacity(c);
+                    }
+                    Box::new(q)
+                },
+                QueueKind::RoundRobin { quantum, work_rate, ref service, ref overhead, capacity } => {
+                    if quantum <= 0. || quantum.is_nan() {
+                        return Err(format!("Queue {}: the quantum must be positive", q.name));
+                    }
+                    let mut rr = RoundRobin::new(quantum, work_rate, service.build());
+                    if let Some(ref o) = *overhead {
+                        rr.set_switch_overhead(sampler_from(o.build()));
+                    }
+                    if let Some(c) = capacity {
+                        rr.set_capacity(c);
+                    }
+                    Box::new(rr)
+                },
+                QueueKind::SizeBased { policy, work_rate, ref service, capacity } => {
+                    let policy = match policy {
+                        SizePolicyConfig::Srpt => Some(SizePolicy::Srpt),
+                        SizePolicyConfig::Sjf => Some(SizePolicy::Sjf),
+                        SizePolicyConfig::Fsp => Some(SizePolicy::Fsp),
+                        SizePolicyConfig::Las => None,
+                    };
+                    match policy {
+                        Some(policy) => {
+                            let mut q = MG1SizeBased::new(work_rate, policy, service.build());
+                            if let Some(c) = capacity {
+                                q.set_capacity(c);
+                            }
+                            Box::new(q)
+                        },
+                        None => {
+                            let mut q = MG1LAS::new(work_rate, service.build());
+                            if let Some(c) = capacity {
+                                q.set_capacity(c);
+                            }
+                            Box::new(q)
+                        },
+                    }
+                },
+                QueueKind::FileLogger { ref file, buffer_size } => {
+                    let mut logger = FileLogger::new(buffer_size, file);
+                    if self.queues.iter().any(|q| q.retry.is_some()) {
+                        logger.log_attempts();
+                    }
+                    if self.queues.iter().any(|q| matches!(q.kind, QueueKind::Classifier { .. })) {
+                        logger.log_classes();
+                    }
+                    Box::new(logger)
+                },
+                QueueKind::Passthrough {} =>
+                    Box::new(PassthroughQueue::new()),
+                QueueKind::Classifier { ref classes } => {
+                    if classes.is_empty() || classes.iter().any(|c| c.weight < 0. || c.weight.is_nan()) || classes.iter().all(|c| c.weight == 0.) {
+                        return Err(format!("Queue {}: class weights must be non-negative, with a positive sum", q.name));
+                    }
+                    let weights: Vec<f64> = classes.iter().map(|c| c.weight).collect();
+                    let mut classifier = Classifier::new(&weights);
+                    for (i, c) in classes.iter().enumerate() {
+                        for (key, value) in &c.attributes {
+                            let value = match *value {
+                                AttributeConfig::Bool(b) => AttributeValue::Constant(Attribute::Bool(b)),
+                                AttributeConfig::Int(n) => AttributeValue::Constant(Attribute::Int(n)),
+                                AttributeConfig::Float(f) => AttributeValue::Constant(Attribute::Float(f)),
+                                AttributeConfig::Text(ref t) => AttributeValue::Constant(Attribute::Text(t.clone())),
+                                AttributeConfig::Random(ref d) => AttributeValue::Random(sampler_from(d.build())),
+                            };
+                            classifier.set_attribute(i, key, value);
+                        }
+                    }
+                    Box::new(classifier)
+                },
+                QueueKind::Sink {} =>
+                    Box::new(Sink::new()),
+            };
+            if indices.insert(q.name.clone(), qn.add_queue(queue)).is_some() {
+                return Err(format!("Duplicate queue name: {}", q.name));
+            }
+        }
+
+        let queue = |name: &String| indices.get(name).cloned().ok_or(format!("Unknown queue: {}", name));
+        let queues = |names: &Vec<String>| names.iter().map(&queue).collect::<Result<Vec<usize>,String>>();
+        let cache = |name: &String| caches.get(name).cloned().ok_or(format!("Unknown cache: {}", name));
+
+        for q in &self.queues {
+            let source = indices[&q.name];
+            let overflow = match q.overflow {
+                None => None,
+                Some(OverflowConfig::Drop { sink: None }) => Some(Overflow::Drop(None)),
+                Some(OverflowConfig::Drop { sink: Some(ref sink) }) => Some(Overflow::Drop(Some(queue(sink)?))),
+                Some(OverflowConfig::BlockAfterService) => Some(Overflow::BlockAfterService),
+                Some(OverflowConfig::BlockBeforeService) => Some(Overflow::BlockBeforeService),
+            };
+            if let Some(overflow) = overflow {
+                qn.set_overflow(source, overflow);
+            }
+            if let Some(ref sink) = q.abandonment_sink {
+                qn.set_abandonment_sink(source, queue(sink)?);
+            }
+            if let QueueKind::ClientPopulation { completion: Some(ref completion), .. } = q.kind {
+                qn.set_population_completion(source, queue(completion)?);
+            }
+            if let Some(ref retry) = q.retry {
+                let jitter = match retry.backoff.jitter {
+                    JitterConfig::None => Jitter::None,
+                    JitterConfig::Full => Jitter::Full,
+                    JitterConfig::Equal => Jitter::Equal,
+                };
+                let backoff = Backoff::exponential(retry.backoff.initial, retry.backoff.multiplier,
+                                                   retry.backoff.max.unwrap_or(f64::INFINITY), jitter);
+                qn.set_retry_policy(source, RetryPolicy {
+                    completion: queue(&retry.completion)?,
+                    timeout: retry.timeout,
+                    max_attempts: retry.max_attempts.max(1),
+                    backoff,
+                });
+            }
+            match q.balking {
+                None => (),
+                Some(BalkingConfig::Threshold { load }) =>
+                    qn.set_balking(source, Box::new(move |l| if l >= load { 1. } else { 0. })),
+                Some(BalkingConfig::Proportional { max_load }) =>
+                    qn.set_balking(source, Box::new(move |l| (l as f64 / max_load as f64).min(1.))),
+            }
+
+            match q.routing {
+                None => (),
+                Some(RoutingConfig::Fixed { ref to, ref update_caches }) => {
+                    let dest = queue(to)?;
+                    let to_update = update_caches.iter().map(&cache).collect::<Result<Vec<SharedCache>,String>>()?;
+                    qn.add_transition(source, Box::new(move |req,_,_| {
+                        for c in &to_update {
+                            c.lock().unwrap().update(req.get_content());
+                        }
+                        dest
+                    }));
+                },
+                Some(RoutingConfig::Probabilistic { ref to, ref probabilities }) => {
+                    qn.add_routing(source, queues(to)?, probabilities)
+                        .map_err(|e| format!("Queue {}: invalid routing: {:?}", q.name, e))?;
+                },
+                Some(RoutingConfig::Cache { cache: ref cache_name, ref hit, ref miss, update }) => {
+                    let c = cache(cache_name)?;
+                    let (hit, miss) = (queue(hit)?, queue(miss)?);
+                    qn.add_transition(source, Box::new(move |req,_,_| {
+                        let mut c = c.lock().unwrap();
+                        let content = req.get_content();
+                        let is_hit = c.contains(&content);
+                        if update == CacheUpdate::Always || (update == CacheUpdate::OnHit && is_hit) {
+                            c.update(content);
+                        }
+                        if is_hit { hit } else { miss }
+                    }));
+                },
+                Some(RoutingConfig::LeastLoaded { ref to, ref load_of, choices }) => {
+                    let dests = queues(to)?;
+                    let loaded = match *load_of {
+                        Some(ref l) => queues(l)?,
+                        None => dests.clone()
+                    };
+                    if dests.is_empty() || dests.len() != loaded.len() {
+                        return Err(format!("Queue {}: 'to' and 'load_of' must have the same non-zero length", q.name));
+                    }
+                    let choices = choices.unwrap_or(dests.len()).clamp(1, dests.len());
+                    qn.add_transition(source, Box::new(move |_,qn,rng| {
+                        let candidates: Vec<usize> = if choices == dests.len() {
+                            (0..dests.len()).collect()
+                        }
+                        else {
+                            rand::seq::sample_indices(rng, dests.len(), choices)
+                        };
+                        let best = candidates.into_iter().min_by_key(|&i| qn.get_queue(loaded[i]).read_load()).unwrap();
+                        dests[best]
+                    }));
+                },
+                Some(RoutingConfig::ByClass { ref to, ref default }) => {
+                    let dests = queues(to)?;
+                    // Without a default, requests of unexpected classes stop the simulation with an error
+                    let default = match *default {
+                        Some(ref d) => queue(d)?,
+                        None => usize::MAX
+                    };
+                    qn.add_transition(source, Box::new(move |req,_,_| dests.get(req.get_class()).cloned().unwrap_or(default)));
+                },
+            }
+        }
+
+        Ok(qn)
+    }
+
+    pub fn stop_condition(&self, sink: Option<usize>) -> StopCondition {
+        let mut conditions = Vec::new();
+        if let Some(until) = self.run.until {
+            conditions.push(StopCondition::Time(until));
+        }
+        if let (Some(count), Some(sink)) = (self.run.departures, sink) {
+            conditions.push(StopCondition::Departures(sink, count));
+        }
+        if let Some(budget) = self.run.wall_clock {
+            conditions.push(StopCondition::WallClock(Duration::from_secs_f64(budget)));
+        }
+
+        if conditions.is_empty() { StopCondition::Exhaustion } else { StopCondition::Any(conditions) }
+    }
+
+    pub fn warmup(&self, sink: Option<usize>) -> Result<Option<Warmup>, String> {
+        let warmup = match self.run.warmup {
+            None => return Ok(None),
+            Some(ref w) => w
+        };
+        if let WarmupConfig::Time { until } = *warmup {
+            return Ok(Some(Warmup::Time(until)));
+        }
+
+        let sink = sink.ok_or("This warm-up needs a sink to be specified in [run]")?;
+        Ok(Some(match *warmup {
+            WarmupConfig::Departures { count } => Warmup::Departures(sink, count),
+            WarmupConfig::Mser5 { min_observations } => Warmup::Mser5 { sink, min_observations },
+            WarmupConfig::Welch { window, tolerance, min_observations } => Warmup::Welch { sink, window, tolerance, min_observations },
+            WarmupConfig::Time { .. } => unreachable!(),
+        }))
+    }
+}
+
+pub fn run_network(filename: &str) -> Result<(), String> {
+    let config = NetworkConfig::from_file(filename)?;
+    let mut qn = config.build()?;
+
+    let sink = match config.run.sink {
+        Some(ref name) => Some(config.queues.iter().position(|q| &q.name == name).ok_or(format!("Unknown sink: {}", name))?),
+        None => None
+    };
+    if config.run.departures.is_some() && sink.is_none() {
+        return Err("A number of departures needs a sink to be specified in [run]".to_owned());
+    }
+    let statistics = config.run.statistics || config.run.report.is_some();
+    if statistics {
+        qn.collect_all_statistics();
+    }
+    if let Some(warmup) = config.warmup(sink)? {
+        qn.set_warmup(warmup);
+    }
+
+    let report = qn.run_until(config.stop_condition(sink));
+    if report.warmup_pending {
+        return Err("The warm-up was not over when the simulation stopped: make the run longer".to_string());
+    }
+    if report.is_error() {
+        return Err(format!("Simulation failed: {:?}", report.stop_reason));
+    }
+
+    println!("t {} events {} wall_time {:.3}s", report.end_time, report.events, report.wall_time.as_secs_f64());
+    if let Some(sink) = sink {
+        println!("sink {} departures {} throughput {} mean_response_time {}",
+                 config.queues[sink].name, report.arrivals[sink], report.throughput(sink), report.mean_response_time(sink));
+    }
+    for (q, c) in config.queues.iter().enumerate() {
+        if report.losses[q] + report.abandonments[q] + report.balks[q] > 0 {
+            println!("queue {} losses {} abandonments {} balks {}", c.name, report.losses[q], report.abandonments[q], report.balks[q]);
+        }
+        if c.retry.is_some() {
+            let r = qn.get_retry_statistics(q);
+            println!("queue {} requests {} retries {} successes {} late_responses {} exhausted {}",
+                     c.name, r.requests, r.retries, r.successes, r.late_responses, r.exhausted);
+        }
+    }
+
+    if statistics {
+        let mut stats = qn.report();
+        for q in stats.queues.iter_mut() {
+            q.name = config.queues[q.queue].name.clone();
+        }
+        print!("{}", stats);
+        if let Some(ref filename) = config.run.report {
+            let json = serde_json::to_string_pretty(&stats).map_err(|e| format!("Could not serialize the report: {}", e))?;
+            File::create(filename).and_then(|mut f| f.write_all(json.as_bytes()))
+                .map_err(|e| format!("Could not write {}: {}", filename, e))?;
+        }
+    }
+    Ok(())
+}
+
+pub fn run (mut args: env::Args) {
+    let filename = args.next().expect("No network description provided");
+    if let Err(e) = run_network(&filename) {
+        panic!("{}", e);
+    }
+    println!("Done");
+}
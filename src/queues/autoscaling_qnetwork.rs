@@ -1,4 +1,5 @@
 use queues::queueing_network::{QNet,Transition,TransitionError};
+use queues::simulation::Simulation;
 use queues::Queue;
 use std::vec::Vec;
 use queues::mg1ps::MG1PS;
@@ -84,12 +85,12 @@ impl AutoscalingTracker {
 
     fn needs_keepalive(&self, time: f64) -> bool 
     {
-        return time > self.time_last_tracking_event + self.keepalive_time;
+        time > self.time_last_tracking_event + self.keepalive_time
     }
 }
 
 
-pub struct AutoscalingQNet<T1: 'static+ MutDistribution<f64>+Clone,T2: 'static+ MutDistribution<f64>+Clone> {
+pub struct AutoscalingQNet<T1: 'static+ MutDistribution<f64>+Clone+Send,T2: 'static+ MutDistribution<f64>+Clone+Send> {
     qn: QNet,
     n_servers: usize,
     ptraffic_source: usize,
@@ -103,15 +104,19 @@ pub struct AutoscalingQNet<T1: 'static+ MutDistribution<f64>+Clone,T2: 'static+
     server_distribution: T2,
 }
 
-impl<T1,T2> AutoscalingQNet<T1,T2> where T1:MutDistribution<f64>+Clone, T2:MutDistribution<f64>+Clone {
-    pub fn new (traffic_source: Box<Queue>,
-                file_logger: Box<Queue>,
+impl<T1,T2> AutoscalingQNet<T1,T2> where T1:MutDistribution<f64>+Clone+Send, T2:MutDistribution<f64>+Clone+Send {
+    pub fn new (traffic_source: Box<dyn Queue>,
+                file_logger: Box<dyn Queue>,
                 n_servers: usize,
                 link_distribution: T1,
                 server_distribution: T2,
-                autoscaling_parameters: Option<AutoscalingParameters>) -> Self {
+                autoscaling_parameters: Option<AutoscalingParameters>,
+                seed: Option<u64>) -> Self {
         let n = 0;
-        let mut qn = QNet::new();
+        let mut qn = match seed {
+            Some(seed) => QNet::from_seed(seed),
+            None => QNet::new()
+        };
         let ptraffic_source = qn.add_queue(traffic_source);
         let pfile_logger = qn.add_queue(file_logger);
         let mut ret = AutoscalingQNet {
@@ -119,11 +124,11 @@ impl<T1,T2> AutoscalingQNet<T1,T2> where T1:MutDistribution<f64>+Clone, T2:MutDi
             n_servers: 0,
             ptraffic_source,
             pfile_logger,
-            pservers: vec![0 as usize; n],
-            pnetwork_arcs: vec![0 as usize; n],
+            pservers: vec![0; n],
+            pnetwork_arcs: vec![0; n],
             autoscaling_parameters,
             autoscaling_tracker: None,
-            pserver_with_tracker: 0 as usize,
+            pserver_with_tracker: 0,
             link_distribution,
             server_distribution,
         };
@@ -134,6 +139,11 @@ impl<T1,T2> AutoscalingQNet<T1,T2> where T1:MutDistribution<f64>+Clone, T2:MutDi
 
     }
 
+    pub fn get_file_logger (&self) -> usize
+    {
+        self.pfile_logger
+    }
+
     pub fn make_transition (&mut self) -> Result<Transition, TransitionError>
     {
         let trans = self.qn.make_transition()?;
@@ -143,7 +153,7 @@ impl<T1,T2> AutoscalingQNet<T1,T2> where T1:MutDistribution<f64>+Clone, T2:MutDi
         let arrival_event = self.autoscaling_tracker.is_some() && (trans.destination == self.pserver_with_tracker);
         let keepalive_event = self.autoscaling_tracker.is_some() && self.autoscaling_tracker.as_ref().unwrap().needs_keepalive(trans.time);
 
-        let mut load_before_event = self.qn.queues[self.pserver_with_tracker].read_load();
+        let mut load_before_event = self.qn.get_queue(self.pserver_with_tracker).read_load();
         if leave_event {
             load_before_event += 1;
         } else if arrival_event {
@@ -180,18 +190,18 @@ impl<T1,T2> AutoscalingQNet<T1,T2> where T1:MutDistribution<f64>+Clone, T2:MutDi
             let source = self.pnetwork_arcs[second_to_last_server_idx];
             let potential_dest = self.pservers[second_to_last_server_idx];
             let fallback_dest = self.pnetwork_arcs[last_server_idx];
-            self.qn.add_transition(source, Box::new(move |ref _req, ref qn| {
+            self.qn.add_transition(source, Box::new(move |_req, qn, _| {
                 let load = qn.get_queue(potential_dest).read_load();
                 if load == 0 { potential_dest } else { fallback_dest }
             }));
         // Transition traffic_source -> link(src, server 0)
         } else if self.n_servers == 1 {
             let dest = self.pnetwork_arcs[0];
-            self.qn.add_transition(self.ptraffic_source,  Box::new(move |_,_| dest ));
+            self.qn.add_transition(self.ptraffic_source,  Box::new(move |_,_,_| dest ));
         } else {
             // If there are no servers, sink the traffic source to /dev/null
             let dest = self.pfile_logger;
-            self.qn.add_transition(self.ptraffic_source,  Box::new(move |_,_| dest ));
+            self.qn.add_transition(self.ptraffic_source,  Box::new(move |_,_,_| dest ));
         }
 
         if self.n_servers > 0 {
@@ -203,21 +213,21 @@ impl<T1,T2> AutoscalingQNet<T1,T2> where T1:MutDistribution<f64>+Clone, T2:MutDi
                 let JIQ = false;
                 if JIQ {
                     let fallback_dests = self.pservers.clone();
-                    self.qn.add_transition(source, Box::new(move |ref _req, ref qn| {
+                    self.qn.add_transition(source, Box::new(move |_req, qn, _| {
                         let load = qn.get_queue(potential_dest).read_load();
                         if load == 0 { potential_dest } else { *rand::thread_rng().choose(&fallback_dests).unwrap() }
                     }));
                 } else {
-                    self.qn.add_transition(source, Box::new(move |_,_| potential_dest ));
+                    self.qn.add_transition(source, Box::new(move |_,_,_| potential_dest ));
                 } */
-                self.qn.add_transition(source, Box::new(move |_,_| potential_dest ));
+                self.qn.add_transition(source, Box::new(move |_,_,_| potential_dest ));
             }
 
             // Transition server n -> file_logger
             {
                 let source = self.pservers[last_server_idx];
                 let dest = self.pfile_logger;
-                self.qn.add_transition(source, Box::new(move |_,_| dest ));
+                self.qn.add_transition(source, Box::new(move |_,_,_| dest ));
             }
         }
     }
@@ -284,3 +294,17 @@ impl<T1,T2> AutoscalingQNet<T1,T2> where T1:MutDistribution<f64>+Clone, T2:MutDi
 
     }
 }
+
+impl<T1,T2> Simulation for AutoscalingQNet<T1,T2> where T1:MutDistribution<f64>+Clone+Send, T2:MutDistribution<f64>+Clone+Send {
+    fn make_transition (&mut self) -> Result<Transition, TransitionError> {
+        AutoscalingQNet::make_transition(self)
+    }
+
+    fn get_qnet (&self) -> &QNet {
+        &self.qn
+    }
+
+    fn get_qnet_mut (&mut self) -> &mut QNet {
+        &mut self.qn
+    }
+}
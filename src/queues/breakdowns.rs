@@ -0,0 +1,111 @@
+use std::collections::VecDeque;
+
+use queues::Sampler;
+use helpers::rng::SimRng;
+
+// What happens to a request in service when its server fails
+#[derive(Debug,Clone,Copy,PartialEq)]
+pub enum Interruption {
+    // It keeps the work done, and goes on once the server is repaired
+    Resume,
+    // It loses the work done, and starts over once the server is repaired
+    Restart,
+    // It leaves the queue when the server fails, like a request giving up: QNet routes it to the abandonment sink
+    Reroute,
+}
+
+// Down periods of a server. The server is up between them, whether it serves requests or not
+#[derive(Clone,PartialEq)]
+pub struct Timeline {
+    // (start, end) of the down periods drawn so far, in order
+    downs: VecDeque<(f64, f64)>,
+    // End of the last down period drawn (or start of the timeline)
+    drawn: f64,
+}
+
+impl Timeline {
+    // Server up from 'time'
+    pub fn new(time: f64) -> Self {
+        Timeline {
+            downs: VecDeque::new(),
+            drawn: time,
+        }
+    }
+
+    //Drops the down periods over by 'time'
+    pub fn forget(&mut self, time: f64) {
+        while self.downs.front().is_some_and(|&(_, end)| end <= time) {
+            self.downs.pop_front();
+        }
+    }
+
+    //Whether the server is down at 'time', among the down periods drawn so far
+    pub fn is_down(&self, time: f64) -> bool {
+        self.downs.iter().any(|&(start, end)| start <= time && time < end)
+    }
+
+    //Failures and repairs drawn so far
+    pub fn changes(&self) -> Vec<f64> {
+        self.downs.iter().flat_map(|&(start, end)| vec![start, end]).collect()
+    }
+}
+
+// Servers failing after an up-time, and repaired after a repair time. The down periods of a server are drawn
+// ahead of time, so that the completion of a service is known when it starts
+pub struct Breakdowns {
+    up_time: Sampler,
+    repair_time: Sampler,
+    interruption: Interruption,
+}
+
+impl Breakdowns {
+    pub fn new(up_time: Sampler, repair_time: Sampler, interruption: Interruption) -> Self {
+        Breakdowns {
+            up_time,
+            repair_time,
+            interruption,
+        }
+    }
+
+    pub fn interruption(&self) -> Interruption {
+        self.interruption
+    }
+
+    //Down period of 'timeline' that ends after 'time': the current one if the server is down, the next one otherwise
+    pub fn next_down(&mut self, timeline: &mut Timeline, time: f64, rng: &mut SimRng) -> (f64, f64) {
+        loop {
+            if let Some(&down) = timeline.downs.iter().find(|&&(_, end)| end > time) {
+                return down;
+            }
+            let start = timeline.drawn + (self.up_time)(rng);
+            let end = start + (self.repair_time)(rng);
+            timeline.downs.push_back((start, end));
+            timeline.drawn = end;
+        }
+    }
+
+    //Earliest time from 'time' at which the server is up
+    pub fn available(&mut self, timeline: &mut Timeline, time: f64, rng: &mut SimRng) -> f64 {
+        let (start, end) = self.next_down(timeline, time, rng);
+        if start <= time { end } else { time }
+    }
+
+    //End of a service needing 'duration' of up time from 'time', and whether the request leaves then because
+    //the server fails (see Interruption::Reroute)
+    pub fn finish(&mut self, timeline: &mut Timeline, mut time: f64, mut duration: f64, rng: &mut SimRng) -> (f64, bool) {
+        loop {
+            let (start, end) = self.next_down(timeline, time, rng);
+            if time + duration <= start {
+                return (time + duration, false);
+            }
+            if start > time {
+                match self.interruption {
+                    Interruption::Resume => duration -= start - time,
+                    Interruption::Restart => {},
+                    Interruption::Reroute => return (start, true),
+                }
+            }
+            time = end;
+        }
+    }
+}
@@ -4,6 +4,7 @@ use std::io::BufRead;
 use std::fs::File;
 
 use queues::queueing_network::{QNet,Transition,TransitionError};
+use queues::simulation::Simulation;
 use queues::Queue;
 use queues::mg1ps::MG1PS;
 use queues::mginf::MGINF;
@@ -15,6 +16,7 @@ use helpers::distribution::MutDistribution;
 
 use helpers::float_binaryheap::FloatBinaryHeap;
 use helpers::ewma::TimeWindowedEwma;
+use helpers::rng::SimRng;
 
 use rand::Rng;
 
@@ -35,10 +37,7 @@ struct ScalingSchedule(FloatBinaryHeap<usize>, Option<(f64,Request)>);
 impl ScalingSchedule {
 
     pub fn from_csv (filename: &'static str, delimiter: char) -> Self {
-        let mut ret = ScalingSchedule {
-            0: FloatBinaryHeap::new(),
-            1: None
-        };
+        let mut ret = ScalingSchedule(FloatBinaryHeap::new(), None);
 
         let sched_csv = File::open(filename).unwrap();
         let buf_read = BufReader::new(sched_csv);
@@ -135,7 +134,7 @@ impl Queue for AutoscalingFileLogger
 
         if service_time > self.upscale_threshold
                 && self.time >= self.time_last_autoscale_event + self.time_before_accepting_new_autoscale_event
-                && self.nb_servers < std::usize::MAX {
+                && self.nb_servers < usize::MAX {
             self.time_last_autoscale_event = self.time;
             self.nb_servers += 1;
             println!("t {} upscale_to {}", self.time, self.nb_servers);
@@ -178,9 +177,19 @@ impl Queue for AutoscalingFileLogger
             None => 0
         }
     }
+
+    fn begin_warmup (&mut self)
+    {
+        self.log.begin_warmup();
+    }
+
+    fn end_warmup (&mut self, end: f64)
+    {
+        self.log.end_warmup(end);
+    }
 }
 
-pub struct CentralizedLoadBalancingQNet<T1: 'static+ MutDistribution<f64>+Clone,T2: 'static+ MutDistribution<f64>+Clone> {
+pub struct CentralizedLoadBalancingQNet<T1: 'static+ MutDistribution<f64>+Clone+Send,T2: 'static+ MutDistribution<f64>+Clone+Send> {
     qn: QNet,
     n_servers: usize,
     ptraffic_source: usize,
@@ -193,16 +202,21 @@ pub struct CentralizedLoadBalancingQNet<T1: 'static+ MutDistribution<f64>+Clone,
     server_distribution: T2
 }
 
-impl<T1,T2> CentralizedLoadBalancingQNet<T1,T2> where T1:MutDistribution<f64>+Clone, T2:MutDistribution<f64>+Clone {
-    pub fn new (traffic_source: Box<Queue>,
+impl<T1,T2> CentralizedLoadBalancingQNet<T1,T2> where T1:MutDistribution<f64>+Clone+Send, T2:MutDistribution<f64>+Clone+Send {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new (traffic_source: Box<dyn Queue>,
                 file_logger: Box<FileLogger>,
                 n_servers: usize,
                 link_distribution: T1,
                 server_distribution: T2,
                 lb_policy: CentralizedLBPolicy,
-                autoscaling_policy: CentralizedScalingPolicy) -> Self {
+                autoscaling_policy: CentralizedScalingPolicy,
+                seed: Option<u64>) -> Self {
         let n = 0;
-        let mut qn = QNet::new();
+        let mut qn = match seed {
+            Some(seed) => QNet::from_seed(seed),
+            None => QNet::new()
+        };
         let ptraffic_source = qn.add_queue(traffic_source);
 
         let pfile_logger = match autoscaling_policy {
@@ -218,12 +232,12 @@ impl<T1,T2> CentralizedLoadBalancingQNet<T1,T2> where T1:MutDistribution<f64>+Cl
             CentralizedScalingPolicy::Autoscaling(_,_,_) => pfile_logger,
             CentralizedScalingPolicy::Schedule(filename, delimiter) =>
                 qn.add_queue(Box::new(ScalingSchedule::from_csv(filename, delimiter))),
-            _ => std::usize::MAX
+            _ => usize::MAX
         };
 
-        if scaling_queue != std::usize::MAX {
-            //std::usize::MAX makes sure that we raise an Error in make_transition()
-            qn.add_transition(scaling_queue, Box::new(|_,_| std::usize::MAX));
+        if scaling_queue != usize::MAX {
+            //usize::MAX makes sure that we raise an Error in make_transition()
+            qn.add_transition(scaling_queue, Box::new(|_,_,_| usize::MAX));
         }
 
         let mut ret = CentralizedLoadBalancingQNet {
@@ -231,12 +245,12 @@ impl<T1,T2> CentralizedLoadBalancingQNet<T1,T2> where T1:MutDistribution<f64>+Cl
             n_servers: 0,
             ptraffic_source,
             pfile_logger,
-            pservers: vec![0 as usize; n],
-            pnetwork_arcs: vec![0 as usize; n],
+            pservers: vec![0; n],
+            pnetwork_arcs: vec![0; n],
             lb_policy,
             scaling_queue,
-            link_distribution: link_distribution.clone(),
-            server_distribution: server_distribution.clone()
+            link_distribution,
+            server_distribution
         };
         for _ in 0..n_servers {
             ret.add_server();
@@ -246,6 +260,11 @@ impl<T1,T2> CentralizedLoadBalancingQNet<T1,T2> where T1:MutDistribution<f64>+Cl
     }
 
 
+    pub fn get_file_logger (&self) -> usize
+    {
+        self.pfile_logger
+    }
+
     pub fn make_transition (&mut self) -> Result<Transition, TransitionError>
     {
         let ret = self.qn.make_transition();
@@ -288,15 +307,15 @@ impl<T1,T2> CentralizedLoadBalancingQNet<T1,T2> where T1:MutDistribution<f64>+Cl
                 //NB: the following removes stale transitions as well, since we use a new version of pnetwork_arcs
                 match self.lb_policy {
                     CentralizedLBPolicy::RND =>
-                        self.qn.add_transition(source, Box::new(move |ref _req, ref _qn| {
-                            dests[rand::thread_rng().gen_range(0, n_servers)]
+                        self.qn.add_transition(source, Box::new(move |_req, _qn, rng: &mut SimRng| {
+                            dests[rng.gen_range(0, n_servers)]
                         })),
                     CentralizedLBPolicy::JSQ2 =>
-                        self.qn.add_transition(source, Box::new(move |ref _req, ref qn| {
-                            let choice_1 = rand::thread_rng().gen_range(0, n_servers);
+                        self.qn.add_transition(source, Box::new(move |_req, qn, rng: &mut SimRng| {
+                            let choice_1 = rng.gen_range(0, n_servers);
                             let mut choice_2 = choice_1;
                             while choice_2 == choice_1 && n_servers > 1 {
-                                choice_2 = rand::thread_rng().gen_range(0, n_servers);
+                                choice_2 = rng.gen_range(0, n_servers);
                             }
                             let load_1 = qn.get_queue(servers[choice_1]).read_load();
                             let load_2 = qn.get_queue(servers[choice_2]).read_load();
@@ -308,7 +327,7 @@ impl<T1,T2> CentralizedLoadBalancingQNet<T1,T2> where T1:MutDistribution<f64>+Cl
 
                         })),
                     CentralizedLBPolicy::JIQ =>
-                        self.qn.add_transition(source, Box::new(move |ref _req, ref qn| {
+                        self.qn.add_transition(source, Box::new(move |_req, qn, rng: &mut SimRng| {
                             // Join an idle queue...
                             for i in 0..n_servers {
                                 if qn.get_queue(servers[i]).read_load() == 0 {
@@ -316,7 +335,7 @@ impl<T1,T2> CentralizedLoadBalancingQNet<T1,T2> where T1:MutDistribution<f64>+Cl
                                 }
                             }
                             // ... or go to a random one, if none is available
-                            dests[rand::thread_rng().gen_range(0, n_servers)]
+                            dests[rng.gen_range(0, n_servers)]
                         }))
                 }
 
@@ -326,7 +345,7 @@ impl<T1,T2> CentralizedLoadBalancingQNet<T1,T2> where T1:MutDistribution<f64>+Cl
             {
                 let source = self.pnetwork_arcs[last_server_idx];
                 let dest = self.pservers[last_server_idx];
-                self.qn.add_transition(source, Box::new(move |ref _req, ref _qn| dest ));
+                self.qn.add_transition(source, Box::new(move |_,_,_| dest ));
             }
 
 
@@ -334,13 +353,13 @@ impl<T1,T2> CentralizedLoadBalancingQNet<T1,T2> where T1:MutDistribution<f64>+Cl
             {
                 let source = self.pservers[last_server_idx];
                 let dest = self.pfile_logger;
-                self.qn.add_transition(source, Box::new(move |_,_| dest ));
+                self.qn.add_transition(source, Box::new(move |_,_,_| dest ));
             }
 
         } else {
             // If there are no servers, sink the traffic source to /dev/null
             let dest = self.pfile_logger;
-            self.qn.add_transition(self.ptraffic_source,  Box::new(move |_,_| dest ));
+            self.qn.add_transition(self.ptraffic_source,  Box::new(move |_,_,_| dest ));
         }
 
     }
@@ -390,3 +409,17 @@ impl<T1,T2> CentralizedLoadBalancingQNet<T1,T2> where T1:MutDistribution<f64>+Cl
 
     }
 }
+
+impl<T1,T2> Simulation for CentralizedLoadBalancingQNet<T1,T2> where T1:MutDistribution<f64>+Clone+Send, T2:MutDistribution<f64>+Clone+Send {
+    fn make_transition (&mut self) -> Result<Transition, TransitionError> {
+        CentralizedLoadBalancingQNet::make_transition(self)
+    }
+
+    fn get_qnet (&self) -> &QNet {
+        &self.qn
+    }
+
+    fn get_qnet_mut (&mut self) -> &mut QNet {
+        &mut self.qn
+    }
+}
@@ -0,0 +1,72 @@
+use std::collections::VecDeque;
+
+use helpers::alias::AliasTable;
+use helpers::rng::{SimRng,rng_from_entropy,rng_from_seed};
+use queues::{Queue,Sampler};
+use queues::request::{Attribute,Request};
+
+// Value given to an attribute by a Classifier: a constant, or a sample of a distribution for every request
+pub enum AttributeValue {
+    Constant(Attribute),
+    Random(Sampler),
+}
+
+// Draws the class of every request passing through it with the given weights (class i has weight weights[i]),
+// and sets the attributes of that class. Requests leave immediately, like in a PassthroughQueue
+pub struct Classifier {
+    time: f64,
+    requests: VecDeque<Request>,
+    classes: AliasTable,
+    attributes: Vec<Vec<(String, AttributeValue)>>,
+    rng: SimRng,
+}
+
+impl Classifier {
+    pub fn new(weights: &[f64]) -> Self {
+        Classifier {
+            time: 0.,
+            requests: VecDeque::new(),
+            classes: AliasTable::new(weights),
+            attributes: weights.iter().map(|_| Vec::new()).collect(),
+            rng: rng_from_entropy(),
+        }
+    }
+
+    pub fn set_attribute(&mut self, class: usize, key: &str, value: AttributeValue) {
+        self.attributes[class].push((key.to_owned(), value));
+    }
+}
+
+impl Queue for Classifier {
+    fn arrival (&mut self, mut req: Request) {
+        let class = self.classes.sample(&mut self.rng);
+        req.set_class(class);
+        for &mut (ref key, ref mut value) in self.attributes[class].iter_mut() {
+            match *value {
+                AttributeValue::Constant(ref a) => req.set_attribute(key, a.clone()),
+                AttributeValue::Random(ref mut sampler) => req.set_attribute(key, sampler(&mut self.rng)),
+            }
+        }
+        self.requests.push_back(req);
+    }
+
+    fn update_time (&mut self, time: f64) {
+        self.time = time;
+    }
+
+    fn read_next_exit (&self) -> Option<(f64, &Request)> {
+        self.requests.front().map(|r| (self.time, r))
+    }
+
+    fn pop_next_exit (&mut self) -> Option<(f64, Request)> {
+        self.requests.pop_front().map(|r| (self.time, r))
+    }
+
+    fn read_load (&self) -> usize {
+        self.requests.len()
+    }
+
+    fn reseed (&mut self, seed: u64) {
+        self.rng = rng_from_seed(seed);
+    }
+}
@@ -0,0 +1,92 @@
+use helpers::distribution::MutDistribution;
+use helpers::float_binaryheap::FloatBinaryHeap;
+use helpers::rng::{SimRng,rng_from_entropy,rng_from_seed};
+use queues::Queue;
+use queues::request::Request;
+
+// Closed population of 'clients' clients. Each client thinks for a sampled time, then issues a request
+// whose content is the index of the client. Requests must eventually be routed back to the population, or arrive
+// at its completion queue (see QNet::set_population_completion, eg to log them): this arrival is the completion
+// of the request, after which its client thinks again. All clients start thinking at time 0
+pub struct ClientPopulation<T> where T: MutDistribution<f64> {
+    time: f64,
+    clients: usize,
+    think_time: T,
+    thinking: FloatBinaryHeap<Request>,
+    completions: usize,
+    rng: SimRng,
+}
+
+impl<T> ClientPopulation<T> where T: MutDistribution<f64> {
+    pub fn new(clients: usize, think_time: T) -> Self {
+        let mut ret = ClientPopulation {
+            time: 0.,
+            clients,
+            think_time,
+            thinking: FloatBinaryHeap::new(),
+            completions: 0,
+            rng: rng_from_entropy(),
+        };
+        ret.draw_think_times();
+        ret
+    }
+
+    fn draw_think_times(&mut self) {
+        self.thinking = FloatBinaryHeap::new();
+        for client in 0..self.clients {
+            self.think(client);
+        }
+    }
+
+    fn think(&mut self, client: usize) {
+        let issue = self.time + self.think_time.mut_sample(&mut self.rng);
+        self.thinking.push(issue, Request::new(client));
+    }
+
+    pub fn get_clients(&self) -> usize {
+        self.clients
+    }
+
+    // Number of requests that came back to the population
+    pub fn get_completions(&self) -> usize {
+        self.completions
+    }
+}
+
+impl<T> Queue for ClientPopulation<T> where T: MutDistribution<f64> + Send {
+    fn arrival(&mut self, req: Request) {
+        self.completions += 1;
+        self.think(req.get_content());
+    }
+
+    fn update_time(&mut self, time: f64) {
+        self.time = time;
+    }
+
+    fn read_next_exit(&self) -> Option<(f64, &Request)> {
+        self.thinking.peek()
+    }
+
+    fn pop_next_exit(&mut self) -> Option<(f64, Request)> {
+        self.thinking.pop()
+    }
+
+    // Clients currently thinking
+    fn read_load(&self) -> usize {
+        self.thinking.len()
+    }
+
+    fn read_busy_servers(&self) -> usize {
+        self.thinking.len()
+    }
+
+    fn read_servers(&self) -> Option<usize> {
+        None
+    }
+
+    // Pending think times are redrawn from the new stream
+    fn reseed(&mut self, seed: u64) {
+        self.rng = rng_from_seed(seed);
+        self.draw_think_times();
+    }
+}
@@ -4,15 +4,18 @@ use self::rand::distributions::Distribution;
 use helpers::distribution::exponential_generator;
 use queues::request::Request;
 use queues::Queue;
+use helpers::rng::{SimRng,rng_from_entropy,rng_from_seed};
 
 pub struct ContinuouslyModulatedPoissonGenerator<T> where T: Distribution<usize> {
+    last_exit: f64,
     next_exit: f64,
     next_request: Option<Request>,
     pop_distribution: T,
-    lambdas: Box<Fn(f64)->f64> //lambda(t)
+    lambdas: Box<dyn Fn(f64)->f64 + Send>, //lambda(t)
+    rng: SimRng,
 }
 
-impl<T> Queue for ContinuouslyModulatedPoissonGenerator<T> where T: Distribution<usize> {
+impl<T> Queue for ContinuouslyModulatedPoissonGenerator<T> where T: Distribution<usize> + Send {
     fn arrival (&mut self, _req: Request) {
         panic!("You should not arrive at a generator");
     }
@@ -28,6 +31,7 @@ impl<T> Queue for ContinuouslyModulatedPoissonGenerator<T> where T: Distribution
 
     fn pop_next_exit  (&mut self) -> Option<(f64,Request)> {
         let ret = (self.next_exit, self.next_request.take());
+        self.last_exit = self.next_exit;
         self.generate_next_exit();
         match ret.1 {
             None => None,
@@ -38,24 +42,32 @@ impl<T> Queue for ContinuouslyModulatedPoissonGenerator<T> where T: Distribution
     fn read_load (&self) -> usize {
         1
     }    
+
+    // The pending request is redrawn from the new stream
+    fn reseed (&mut self, seed: u64) {
+        self.rng = rng_from_seed(seed);
+        self.generate_next_exit();
+    }
 }
 
 impl<T> ContinuouslyModulatedPoissonGenerator<T> where T: Distribution<usize> {
-    pub fn new (_lambdas: Box<Fn(f64)->f64>, distribution: T) -> Self{
+    pub fn new (_lambdas: Box<dyn Fn(f64)->f64 + Send>, distribution: T) -> Self{
         let mut ret = ContinuouslyModulatedPoissonGenerator {
+            last_exit: 0.,
             next_exit: 0.,
             next_request: None,
             pop_distribution: distribution,
             lambdas: _lambdas,
+            rng: rng_from_entropy(),
         };
         ret.generate_next_exit ();
         ret
     }
 
     fn generate_next_exit(&mut self) {
-        let t = self.next_exit;
-        self.next_exit += exponential_generator((self.lambdas)(t));
-        self.next_request = Some(Request::new(self.pop_distribution.sample(&mut rand::thread_rng())));
+        let t = self.last_exit;
+        self.next_exit = t + exponential_generator((self.lambdas)(t), &mut self.rng);
+        self.next_request = Some(Request::new(self.pop_distribution.sample(&mut self.rng)));
     }
 }
 
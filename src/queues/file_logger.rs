@@ -12,6 +12,9 @@ pub struct FileLogger {
     buffer_size: usize,
     file: File,
     init: bool,
+    warmup: bool,
+    attempts: bool,
+    classes: bool,
 }
 
 impl FileLogger {
@@ -21,11 +24,24 @@ impl FileLogger {
         FileLogger {
             buffer: Vec::with_capacity(buffer_size),
             buffer_size,
-            file: File::create(filename).expect(&("Could not open file ".to_owned() + filename)),
+            file: File::create(filename).unwrap_or_else(|_| panic!("Could not open file {}", filename)),
             init: true,
+            warmup: false,
+            attempts: false,
+            classes: false,
         }
     }
 
+    // Appends the attempt of each request (see RetryPolicy) to its line
+    pub fn log_attempts(&mut self) {
+        self.attempts = true;
+    }
+
+    // Appends the class of each request to its line, last
+    pub fn log_classes(&mut self) {
+        self.classes = true;
+    }
+
     fn dump_log (&mut self) -> Result<()>
     {
         if self.init {
@@ -34,13 +50,21 @@ impl FileLogger {
         else { // If lines have already been dumped, we need to return
             self.file.write_all("\n".as_bytes())?;
         }
+        let (attempts, classes) = (self.attempts, self.classes);
         let s: Vec<String> = self.buffer.drain(..).map(|req: Request| { 
             let log_str: Vec<String> = req.get_log().into_iter().map(|(key,(orig,dest))| {
                 format!("{}:{}:{}", key, orig, dest)
             }).collect();
             let log_str: String = log_str.join(";");
             let total_time = req.get_current_lifetime();
-            format!("{},{},{},{}", req.get_id(), req.get_content(), log_str, total_time)
+            let mut line = format!("{},{},{},{}", req.get_id(), req.get_content(), log_str, total_time);
+            if attempts {
+                line += &format!(",{}", req.get_attempt());
+            }
+            if classes {
+                line += &format!(",{}", req.get_class());
+            }
+            line
         }).collect();
         self.file.write_all(s.join("\n").as_bytes())
     }
@@ -51,7 +75,7 @@ impl Queue for FileLogger {
     fn arrival(&mut self, req: Request)
     {
         self.buffer.push(req);
-        if self.buffer.len() >= self.buffer_size {
+        if self.buffer.len() >= self.buffer_size && !self.warmup {
             self.dump_log().expect("Failed to write log");
         }
     }
@@ -65,11 +89,29 @@ impl Queue for FileLogger {
     fn read_load (&self) -> usize {
         self.buffer.len()
     }
+
+    // Nothing is written during the warm-up: requests are held until we know which ones to discard.
+    // If the warm-up never ends (see RunReport::warmup_pending), they are all discarded when the logger is dropped
+    fn begin_warmup(&mut self) {
+        self.warmup = true;
+    }
+
+    fn end_warmup(&mut self, end: f64) {
+        self.warmup = false;
+        self.buffer.retain(|req| req.get_log().last().is_some_and(|&(t,_)| t >= end));
+        if self.buffer.len() >= self.buffer_size {
+            self.dump_log().expect("Failed to write log");
+        }
+    }
 }
 
 impl Drop for FileLogger {
 
     fn drop(&mut self) {
+        if self.warmup {
+            eprintln!("The warm-up was not over: {} logged requests were discarded", self.buffer.len());
+            return;
+        }
         self.dump_log().expect("Failed to write log on drop");
     }
 }
@@ -3,13 +3,15 @@ extern crate rand;
 use helpers::distribution::MutDistribution;
 use queues::Queue;
 use queues::request::Request;
+use helpers::rng::{SimRng,rng_from_entropy,rng_from_seed};
 use std::mem;
 
 pub struct Generator<T> where T: MutDistribution<f64> {
     iat_distribution: T,
     time: f64,
     next_req: Request,
-    next_arrival: f64
+    next_arrival: f64,
+    rng: SimRng,
 }
 
 impl<T> Generator<T> where T: MutDistribution<f64> {
@@ -18,7 +20,8 @@ impl<T> Generator<T> where T: MutDistribution<f64> {
             iat_distribution,
             time: 0.,
             next_req: Request::new(0),
-            next_arrival: 0.
+            next_arrival: 0.,
+            rng: rng_from_entropy(),
         };
         ret.draw_arrival();
 
@@ -26,11 +29,11 @@ impl<T> Generator<T> where T: MutDistribution<f64> {
     }
 
     fn draw_arrival(&mut self) {
-        self.next_arrival = self.time + self.iat_distribution.mut_sample(&mut rand::thread_rng());
+        self.next_arrival = self.time + self.iat_distribution.mut_sample(&mut self.rng);
     }
 }
 
-impl<T> Queue for Generator<T> where T: MutDistribution<f64> {
+impl<T> Queue for Generator<T> where T: MutDistribution<f64> + Send {
     fn arrival(&mut self, _req: Request) {
         panic!("You should not arrive at a generator");
     }
@@ -54,4 +57,10 @@ impl<T> Queue for Generator<T> where T: MutDistribution<f64> {
     fn read_load (&self) -> usize {
         1
     }    
+
+    // The pending arrival is redrawn from the new stream
+    fn reseed (&mut self, seed: u64) {
+        self.rng = rng_from_seed(seed);
+        self.draw_arrival();
+    }
 }
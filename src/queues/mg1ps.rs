@@ -1,57 +1,327 @@
 use std::collections::{HashMap,VecDeque};
 
 use queues::request::Request;
-use queues::Queue;
+use queues::{Queue,Sampler,abandonment_time,busy_changes};
 use helpers::float_binaryheap::FloatBinaryHeap;
 
-use helpers::distribution::MutDistribution;
+use queues::service::ServiceDistribution;
+use queues::breakdowns::{Breakdowns,Interruption,Timeline};
+use helpers::rng::{SimRng,rng_from_entropy,rng_from_seed};
 
 //#[derive(Clone)]
-pub struct MG1PS<T,Process> where T: MutDistribution<f64>, Process: PartialEq {
+pub struct MG1PS<T,Process> where T: ServiceDistribution, Process: PartialEq {
     time: f64,
     work_rate: f64,
-    processes: FloatBinaryHeap<Process>,
+    // Work targets of the processes in service, by ticket (those of processes that abandoned are skipped)
+    processes: FloatBinaryHeap<usize>,
+    active: HashMap<usize, Process>,
+    // Times at which processes in service abandon, by ticket
+    deadlines: FloatBinaryHeap<usize>,
+    next_ticket: usize,
+    patience: Option<Sampler>,
     applied_work: f64,
     distribution: T,
+    rng: SimRng,
+    capacity: Option<usize>,
+    // Blocked processes (see Queue::block_next_exit), and whether they stop the dispatch
+    held: VecDeque<(Process, bool)>,
+    stopped_dispatch: usize,
+    // Processes arrived while the dispatch is stopped, with their work and deadline
+    waiting: VecDeque<(Process, f64, Option<f64>)>,
+    // Time and ticket of the next service completion, and whether the processes in service are interrupted
+    // by a failure instead (see plan)
+    completion: Option<(f64, usize, bool)>,
+    breakdowns: Option<Breakdowns>,
+    timeline: Timeline,
+    // Whole works of the processes in service, by ticket, when they restart after a failure
+    works: HashMap<usize, f64>,
+    // Processes interrupted by a failure, leaving now (see Interruption::Reroute)
+    interrupted: VecDeque<Process>,
+    // Once idle, the server is on vacation until then, and goes on another one if no request is there when it comes back
+    vacation: Option<Sampler>,
+    vacation_end: f64,
 }
 
-impl<T,Process> MG1PS<T,Process> where T: MutDistribution<f64>, Process: PartialEq {
+impl<T,Process> MG1PS<T,Process> where T: ServiceDistribution, Process: PartialEq {
     pub fn new (work_rate: f64, distribution: T) -> MG1PS<T,Process> {
         MG1PS{
             time: 0.,
             work_rate,
             processes: FloatBinaryHeap::new(),
+            active: HashMap::new(),
+            deadlines: FloatBinaryHeap::new(),
+            next_ticket: 0,
+            patience: None,
             applied_work: 0.,
-            distribution
+            distribution,
+            rng: rng_from_entropy(),
+            capacity: None,
+            held: VecDeque::new(),
+            stopped_dispatch: 0,
+            waiting: VecDeque::new(),
+            completion: None,
+            breakdowns: None,
+            timeline: Timeline::new(0.),
+            works: HashMap::new(),
+            interrupted: VecDeque::new(),
+            vacation: None,
+            vacation_end: 0.,
+        }
+    }
+
+    pub fn set_capacity (&mut self, capacity: usize) {
+        self.capacity = Some(capacity);
+    }
+
+    // Requests in service abandon once their patience has elapsed
+    pub fn set_patience (&mut self, patience: Sampler) {
+        self.patience = Some(patience);
+    }
+
+    // The server fails and gets repaired, whether it serves or not. A failure interrupts every process in service
+    pub fn set_breakdowns (&mut self, breakdowns: Breakdowns) {
+        self.breakdowns = Some(breakdowns);
+    }
+
+    // Length of the vacations of the server once idle
+    pub fn set_vacation (&mut self, vacation: Sampler) {
+        self.vacation = Some(vacation);
+    }
+
+    //Computes the next service completion, the down periods it depends on being drawn in advance
+    fn plan (&mut self) {
+        self.completion = match self.processes.peek() {
+            Some((w, &k)) if self.interrupted.is_empty() => {
+                let n = (self.active.len() + self.held.len()) as f64;
+                // No work is done before the end of the vacation
+                let start = self.time.max(self.vacation_end);
+                let duration = (w-self.applied_work) / self.work_rate * n;
+                match self.breakdowns {
+                    None => Some((start + duration, k, false)),
+                    Some(ref mut b) if b.interruption() == Interruption::Restart => {
+                        let (failure, repair) = b.next_down(&mut self.timeline, start, &mut self.rng);
+                        if failure > start && start + duration > failure {
+                            // The failure sets every process back to its whole work: the smallest one leaves first
+                            let (&k, &work) = self.works.iter().min_by(|a, b| a.1.partial_cmp(b.1).unwrap().then(a.0.cmp(b.0))).unwrap();
+                            Some((b.finish(&mut self.timeline, repair, work / self.work_rate * n, &mut self.rng).0, k, false))
+                        }
+                        else {
+                            Some((b.finish(&mut self.timeline, start, duration, &mut self.rng).0, k, false))
+                        }
+                    },
+                    Some(ref mut b) => {
+                        let (t, interrupted) = b.finish(&mut self.timeline, start, duration, &mut self.rng);
+                        Some((t, k, interrupted))
+                    },
+                }
+            },
+            _ => None
+        };
+    }
+
+    // Time and ticket of the next exit, and whether it is an abandonment (or a failure interrupting the processes)
+    fn next_exit(&self) -> Option<(f64, usize, bool)> {
+        let abandonment = self.deadlines.peek().map(|(t,&k)| (t.max(self.time), k));
+        match (self.completion, abandonment) {
+            (Some((s, k, i)), Some((a, _))) if s <= a => Some((s, k, i)),
+            (_, Some((a, k))) => Some((a, k, true)),
+            (service, None) => service,
         }
     }
 
     fn peek(&self) -> Option<(f64, &Process)> {
-        self.processes.peek().map(|(w,r)| (self.time + (w-self.applied_work) / self.work_rate * self.processes.len() as f64, r))
+        match self.interrupted.front() {
+            Some(proc) => Some((self.time, proc)),
+            None => self.next_exit().map(|(t,k,_)| (t, &self.active[&k])),
+        }
+    }
+
+    fn take(&mut self) -> Option<(f64,Process)> {
+        match self.interrupted.pop_front() {
+            Some(proc) => Some((self.time, proc)),
+            None => {
+                let (t, k, _) = self.next_exit()?;
+                self.works.remove(&k);
+                let proc = self.active.remove(&k).unwrap();
+                self.discard_tickets();
+                Some((t, proc))
+            }
+        }
     }
 
     fn pop(&mut self) -> Option<(f64,Process)> {
-        let nb_processes = self.processes.len() as f64;
-        self.processes.pop().map(|(w,r)| (self.time + (w-self.applied_work) / self.work_rate * nb_processes, r))
+        let (t, proc) = self.take()?;
+        self.rest(t);
+        self.plan();
+        Some((t, proc))
+    }
+
+    //Nothing in service, held processes included
+    fn idle(&self) -> bool {
+        self.active.is_empty() && self.interrupted.is_empty() && self.held.is_empty()
+    }
+
+    //Whether the server serves at 'time', which can be ahead of 'time' until the next exit: it holds processes,
+    //is back from vacation, and up
+    fn busy_at(&self, time: f64) -> usize {
+        usize::from(!self.idle() && time >= self.vacation_end && !self.timeline.is_down(time))
+    }
+
+    fn busy_changes(&self, until: f64) -> Vec<(f64, usize)> {
+        if self.breakdowns.is_none() && self.vacation.is_none() {
+            return Vec::new();
+        }
+        let mut times = self.timeline.changes();
+        times.push(self.vacation_end);
+        busy_changes(times, self.time, until, |t| self.busy_at(t))
+    }
+
+    //Goes on vacation from 't' if the server is idle
+    fn rest(&mut self, t: f64) {
+        if self.idle() {
+            if let Some(ref mut vacation) = self.vacation {
+                self.vacation_end = t + vacation(&mut self.rng);
+            }
+        }
+    }
+
+    //The next exit is held in the server, until release
+    fn hold(&mut self, stop_dispatch: bool) -> bool {
+        match self.take() {
+            Some((_, proc)) => {
+                self.held.push_back((proc, stop_dispatch));
+                if stop_dispatch {
+                    self.stopped_dispatch += 1;
+                }
+                self.plan();
+                true
+            },
+            None => false
+        }
+    }
+
+    fn release(&mut self) -> Option<Process> {
+        let (proc, stop_dispatch) = self.held.pop_front()?;
+        if stop_dispatch {
+            self.stopped_dispatch -= 1;
+        }
+        self.resume();
+        Some(proc)
+    }
+
+    //The waiting processes are admitted once the dispatch is no longer stopped
+    fn resume(&mut self) {
+        if self.stopped_dispatch == 0 {
+            while let Some((proc, work, deadline)) = self.waiting.pop_front() {
+                self.insert_process(proc, work, deadline);
+            }
+        }
+        let t = self.time;
+        self.rest(t);
+        self.plan();
+    }
+
+    //New processes wait while the dispatch is stopped
+    fn admit (&mut self, proc: Process, work: f64, deadline: Option<f64>) {
+        if self.stopped_dispatch > 0 {
+            self.waiting.push_back((proc, work, deadline));
+        }
+        else {
+            self.insert_process(proc, work, deadline);
+        }
+    }
+
+    fn abandons (&self) -> bool {
+        !self.interrupted.is_empty() || self.next_exit().is_some_and(|(_,_,abandons)| abandons)
     }
 
-    fn insert_process (&mut self, proc: Process) {
-        let work_target = self.distribution.mut_sample(&mut rand::thread_rng()) + self.applied_work;
-        self.processes.push(work_target, proc);
+    //Drops the work targets and deadlines of the processes that left, until the earliest ones are valid
+    fn discard_tickets (&mut self) {
+        while self.processes.peek().is_some_and(|(_, k)| !self.active.contains_key(k)) {
+            self.processes.pop();
+        }
+        while self.deadlines.peek().is_some_and(|(_, k)| !self.active.contains_key(k)) {
+            self.deadlines.pop();
+        }
+    }
+
+    fn insert_process (&mut self, proc: Process, work: f64, deadline: Option<f64>) {
+        // Vacations that ended while the server was idle are followed by another one
+        if self.idle() {
+            if let Some(ref mut vacation) = self.vacation {
+                while self.vacation_end < self.time {
+                    // A vacation of no length ends it, as drawing more would not move it forward
+                    let length = vacation(&mut self.rng);
+                    self.vacation_end = if length > 0. { self.vacation_end + length } else { self.time };
+                }
+            }
+        }
+        let work_target = work + self.applied_work;
+        let ticket = self.next_ticket;
+        self.next_ticket += 1;
+        self.processes.push(work_target, ticket);
+        if let Some(d) = deadline {
+            self.deadlines.push(d.max(self.time), ticket);
+        }
+        if self.breakdowns.as_ref().is_some_and(|b| b.interruption() == Interruption::Restart) {
+            self.works.insert(ticket, work);
+        }
+        self.active.insert(ticket, proc);
+        self.plan();
     }
 
     fn advance_time (&mut self, time: f64) {
-        if !self.processes.is_empty() {
-            self.applied_work += (time-self.time) * self.work_rate / (self.processes.len() as f64);
+        if !self.active.is_empty() {
+            let rate = self.work_rate / ((self.active.len() + self.held.len()) as f64);
+            let mut start = self.time.max(self.vacation_end);
+            match self.breakdowns {
+                None => self.applied_work += (time-start).max(0.) * rate,
+                Some(ref mut b) => while start < time {
+                    let (failure, repair) = b.next_down(&mut self.timeline, start, &mut self.rng);
+                    self.applied_work += (failure.min(time)-start).max(0.) * rate;
+                    if failure > time {
+                        break;
+                    }
+                    if failure > start {
+                        match b.interruption() {
+                            Interruption::Resume => {},
+                            Interruption::Restart => {
+                                self.processes = FloatBinaryHeap::new();
+                                for (&k, &w) in &self.works {
+                                    self.processes.push(self.applied_work + w, k);
+                                }
+                            },
+                            Interruption::Reroute => {
+                                while let Some((_, k)) = self.processes.pop() {
+                                    if let Some(proc) = self.active.remove(&k) {
+                                        self.interrupted.push_back(proc);
+                                    }
+                                }
+                                self.deadlines = FloatBinaryHeap::new();
+                                break;
+                            },
+                        }
+                    }
+                    start = repair;
+                },
+            }
         }
+        self.timeline.forget(time);
 
-        self.time = time
+        self.time = time;
+        self.plan();
+    }
+
+    fn set_seed (&mut self, seed: u64) {
+        self.rng = rng_from_seed(seed);
     }
 }
 
-impl<T> Queue for MG1PS<T,Request> where T: MutDistribution<f64> {
+impl<T> Queue for MG1PS<T,Request> where T: ServiceDistribution + Send {
     fn arrival (&mut self, req: Request) {
-        self.insert_process(req);
+        let deadline = abandonment_time(&req, self.time, &mut self.patience, &mut self.rng);
+        let work = self.distribution.sample_work(&req, &mut self.rng);
+        self.admit(req, work, deadline);
     }
 
     fn update_time (&mut self, time: f64) {
@@ -67,41 +337,81 @@ impl<T> Queue for MG1PS<T,Request> where T: MutDistribution<f64> {
     }
 
     fn read_load (&self) -> usize {
-        self.processes.len()
+        self.active.len() + self.held.len() + self.waiting.len()
+    }
+
+    fn read_busy_servers (&self) -> usize {
+        self.busy_at(self.time)
+    }
+
+    fn read_busy_changes (&self, until: f64) -> Vec<(f64, usize)> {
+        self.busy_changes(until)
+    }
+
+    fn next_exit_abandons (&self) -> bool {
+        self.abandons()
+    }
+
+    fn read_capacity (&self) -> Option<usize> {
+        self.capacity
+    }
+
+    fn block_next_exit (&mut self, stop_dispatch: bool) -> bool {
+        self.hold(stop_dispatch)
+    }
+
+    fn unblock_exit (&mut self) -> Option<Request> {
+        self.release()
+    }
+
+    fn reseed (&mut self, seed: u64) {
+        self.set_seed(seed);
     }
 }
 
-pub struct AggregatingMG1PS<T> where T: MutDistribution<f64> {
+pub struct AggregatingMG1PS<T> where T: ServiceDistribution {
     queue: MG1PS<T, usize>,
     pit: HashMap<usize, VecDeque<Request>>,
     to_release: VecDeque<Request>,
+    // Blocked requests, and whether they stopped the dispatch
+    held: VecDeque<(Request, bool)>,
+    // Blocked requests per content: the inner queue holds each content once, for a single share
+    held_contents: HashMap<usize, usize>,
     load: usize
 }
 
-impl<T> AggregatingMG1PS<T> where T: MutDistribution<f64> {
+impl<T> AggregatingMG1PS<T> where T: ServiceDistribution {
     pub fn new(work_rate: f64, distribution: T) -> Self {
         AggregatingMG1PS {
             queue: MG1PS::new(work_rate, distribution),
             pit: HashMap::new(),
             to_release: VecDeque::new(),
+            held: VecDeque::new(),
+            held_contents: HashMap::new(),
             load: 0,
         }
     }
+
+    pub fn set_capacity (&mut self, capacity: usize) {
+        self.queue.set_capacity(capacity);
+    }
 }
 
-impl<T> Queue for AggregatingMG1PS<T> where T: MutDistribution<f64> {
+impl<T> Queue for AggregatingMG1PS<T> where T: ServiceDistribution + Send {
     fn arrival (&mut self, req: Request) {
         let content = req.get_content();
-        let to_aggregate = self.pit.contains_key(&content) && !&self.pit[&content].is_empty();
+        let to_aggregate = self.pit.contains_key(&content) && !self.pit[&content].is_empty();
 
         if to_aggregate {
             self.pit.get_mut(&content).unwrap().push_back(req);
         }
+        // The work of an aggregate is that of its first request
         else {
+            let work = self.queue.distribution.sample_work(&req, &mut self.queue.rng);
             let mut v = VecDeque::new();
             v.push_back(req);
             self.pit.insert(content,v);
-            self.queue.insert_process(content);
+            self.queue.admit(content, work, None);
         }
         self.load += 1;
     }
@@ -115,7 +425,7 @@ impl<T> Queue for AggregatingMG1PS<T> where T: MutDistribution<f64> {
             Some((self.queue.time, self.to_release.front().unwrap()))
         }
         else {
-            self.queue.peek().map(|(t,c)| (t,(&self.pit[c]).front().unwrap()))
+            self.queue.peek().map(|(t,c)| (t,self.pit[c].front().unwrap()))
         }
     }
 
@@ -140,4 +450,58 @@ impl<T> Queue for AggregatingMG1PS<T> where T: MutDistribution<f64> {
         self.load
     }
 
+    fn read_busy_servers (&self) -> usize {
+        self.queue.busy_at(self.queue.time)
+    }
+
+    fn read_busy_changes (&self, until: f64) -> Vec<(f64, usize)> {
+        self.queue.busy_changes(until)
+    }
+
+    fn read_capacity (&self) -> Option<usize> {
+        self.queue.capacity
+    }
+
+    fn block_next_exit (&mut self, stop_dispatch: bool) -> bool {
+        match self.pop_next_exit() {
+            Some((_, req)) => {
+                self.load += 1;
+                let count = self.held_contents.entry(req.get_content()).or_insert(0);
+                *count += 1;
+                if *count == 1 {
+                    self.queue.held.push_back((req.get_content(), false));
+                }
+                if stop_dispatch {
+                    self.queue.stopped_dispatch += 1;
+                }
+                self.queue.plan();
+                self.held.push_back((req, stop_dispatch));
+                true
+            },
+            None => false
+        }
+    }
+
+    fn unblock_exit (&mut self) -> Option<Request> {
+        let (req, stop_dispatch) = self.held.pop_front()?;
+        self.load -= 1;
+        if stop_dispatch {
+            self.queue.stopped_dispatch -= 1;
+        }
+        let content = req.get_content();
+        let count = self.held_contents.get_mut(&content).unwrap();
+        *count -= 1;
+        if *count == 0 {
+            self.held_contents.remove(&content);
+            // Held entries only count the shares: which one leaves does not matter
+            self.queue.held.pop_front();
+        }
+        self.queue.resume();
+        Some(req)
+    }
+
+    fn reseed (&mut self, seed: u64) {
+        self.queue.set_seed(seed);
+    }
+
 }
@@ -1,31 +1,56 @@
 use queues::request::Request;
-use queues::Queue;
+use queues::{Queue,busy_changes};
 use helpers::float_binaryheap::FloatBinaryHeap;
 
-use helpers::distribution::MutDistribution;
+use queues::service::ServiceDistribution;
+use queues::breakdowns::{Breakdowns,Timeline};
+use helpers::rng::{SimRng,rng_from_entropy,rng_from_seed};
 
-pub struct MGINF<T> where T: MutDistribution<f64> {
+pub struct MGINF<T> where T: ServiceDistribution {
     time: f64,
     work_rate: f64,
-    processes: FloatBinaryHeap<Request>,
+    // Requests by exit time, whether they leave because their server failed, and the down periods of their server
+    processes: FloatBinaryHeap<(bool, Timeline, Request)>,
+    breakdowns: Option<Breakdowns>,
     distribution: T,
+    rng: SimRng,
 }
 
-impl<T> MGINF<T> where T: MutDistribution<f64> {
+impl<T> MGINF<T> where T: ServiceDistribution {
     pub fn new (work_rate: f64, distribution: T) -> MGINF<T> {
         MGINF {
             time: 0.,
             work_rate,
             processes: FloatBinaryHeap::new(),
-            distribution
+            breakdowns: None,
+            distribution,
+            rng: rng_from_entropy(),
+        }
+    }
+
+    // Every request has its own server, up when its service starts, which fails and gets repaired
+    pub fn set_breakdowns(&mut self, breakdowns: Breakdowns) {
+        self.breakdowns = Some(breakdowns);
+    }
+
+    //Requests served at 'time', which can be ahead of 'time' until the next exit: those whose server is up
+    fn busy_at(&self, time: f64) -> usize {
+        match self.breakdowns {
+            Some(_) => self.processes.iter().filter(|(_, (_, timeline, _))| !timeline.is_down(time)).count(),
+            None => self.processes.len(),
         }
     }
 }
 
-impl<T> Queue for MGINF<T> where T: MutDistribution<f64> {
+impl<T> Queue for MGINF<T> where T: ServiceDistribution + Send {
     fn arrival (&mut self, req: Request) {
-        let exit = self.distribution.mut_sample(&mut rand::thread_rng()) / self.work_rate + self.time;
-        self.processes.push(exit, req)
+        let duration = self.distribution.sample_work(&req, &mut self.rng) / self.work_rate;
+        let mut timeline = Timeline::new(self.time);
+        let (exit, interrupted) = match self.breakdowns {
+            Some(ref mut b) => b.finish(&mut timeline, self.time, duration, &mut self.rng),
+            None => (self.time + duration, false),
+        };
+        self.processes.push(exit, (interrupted, timeline, req))
     }
 
     fn update_time (&mut self, time: f64) {
@@ -33,14 +58,38 @@ impl<T> Queue for MGINF<T> where T: MutDistribution<f64> {
     }
 
     fn read_next_exit(&self) -> Option<(f64, &Request)> {
-        self.processes.peek()
+        self.processes.peek().map(|(t, (_, _, req))| (t, req))
     }
 
     fn pop_next_exit  (&mut self) -> Option<(f64,Request)> {
-        self.processes.pop()
+        self.processes.pop().map(|(t, (_, _, req))| (t, req))
+    }
+
+    fn next_exit_abandons (&self) -> bool {
+        self.processes.peek().is_some_and(|(_, &(interrupted, _, _))| interrupted)
     }
 
     fn read_load (&self) -> usize {
         self.processes.len()
     }    
+
+    fn read_busy_servers (&self) -> usize {
+        self.busy_at(self.time)
+    }
+
+    fn read_busy_changes (&self, until: f64) -> Vec<(f64, usize)> {
+        if self.breakdowns.is_none() {
+            return Vec::new();
+        }
+        let times = self.processes.iter().flat_map(|(_, (_, timeline, _))| timeline.changes()).collect();
+        busy_changes(times, self.time, until, |t| self.busy_at(t))
+    }
+
+    fn read_servers (&self) -> Option<usize> {
+        None
+    }
+
+    fn reseed (&mut self, seed: u64) {
+        self.rng = rng_from_seed(seed);
+    }
 }
@@ -1,78 +1,310 @@
 use helpers::float_binaryheap::FloatBinaryHeap;
-use helpers::distribution::MutDistribution;
-use std::collections::VecDeque;
+use queues::service::ServiceDistribution;
+use helpers::rng::{SimRng,rng_from_entropy,rng_from_seed};
+use std::collections::{HashMap,VecDeque};
 use queues::request::Request;
+use rand::Rng;
 
-use queues::{Queue,Process};
+use queues::{Queue,Process,Sampler,abandonment_time,busy_changes};
+use queues::breakdowns::{Breakdowns,Timeline};
 
-pub struct MGKFIFO<T> where T: MutDistribution<f64> {
+// Server taken by a waiting process when several of them are free
+#[derive(Debug,Clone,Copy,PartialEq)]
+pub enum ServerSelection {
+    // The fastest one, the first one among those as fast
+    FastestFree,
+    // One of them, uniformly at random
+    RandomFree,
+    // The first one after the last server taken, in cyclic order
+    RoundRobin,
+}
+
+pub struct MGKFIFO<T> where T: ServiceDistribution {
     time: f64,
     work_rate: f64,
-    queue: VecDeque<Process>,
+    // Speed of each server, relative to the work rate, and whether they are all the same
+    speeds: Vec<f64>,
+    identical: bool,
+    selection: ServerSelection,
+    // Server after which the round-robin selection starts
+    last_server: usize,
+    // Tickets of the waiting processes in arrival order (those of processes that abandoned are skipped)
+    queue: VecDeque<usize>,
+    waiting: HashMap<usize, Process>,
+    // Times at which waiting processes abandon, by ticket. Processes that are served no longer abandon
+    deadlines: FloatBinaryHeap<usize>,
+    next_ticket: usize,
+    patience: Option<Sampler>,
     servers: Vec<Option<Process>>,
+    // Time at which the service of each server starts, which can be ahead of 'time' (see dispatch)
+    starts: Vec<f64>,
     free_servers: Vec<usize>,
     next_exits: FloatBinaryHeap<usize>,
+    // Whether the service of each server ends with a failure, the request leaving unserved
+    interrupted: Vec<bool>,
+    breakdowns: Option<Breakdowns>,
+    timelines: Vec<Timeline>,
+    // Servers that become idle with no request waiting go on vacation, and on another one if still none waits
+    // when they come back
+    vacation: Option<Sampler>,
+    vacation_ends: Vec<f64>,
+    // Since when requests have been waiting without interruption
+    waiting_since: f64,
     distribution: T,
+    rng: SimRng,
+    capacity: Option<usize>,
+    // Servers holding a served process that cannot leave yet, and whether it stops the dispatch of new ones
+    held: VecDeque<(usize, bool)>,
+    stopped_dispatch: usize,
 }
 
-impl<T> MGKFIFO<T> where T: MutDistribution<f64> {
+impl<T> MGKFIFO<T> where T: ServiceDistribution {
     pub fn new(k: usize, work_rate: f64, distribution: T) -> Self {
         MGKFIFO {
             time: 0.,
             work_rate,
+            speeds: vec![1.; k],
+            identical: true,
+            selection: ServerSelection::FastestFree,
+            last_server: k.saturating_sub(1),
             queue: VecDeque::new(),
+            waiting: HashMap::new(),
+            deadlines: FloatBinaryHeap::new(),
+            next_ticket: 0,
+            patience: None,
             servers: vec![None; k],
-            free_servers: Vec::new(),
+            starts: vec![0.; k],
+            free_servers: (0..k).rev().collect(),
             next_exits: FloatBinaryHeap::new(),
-            distribution
+            interrupted: vec![false; k],
+            breakdowns: None,
+            timelines: vec![Timeline::new(0.); k],
+            vacation: None,
+            vacation_ends: vec![0.; k],
+            waiting_since: 0.,
+            distribution,
+            rng: rng_from_entropy(),
+            capacity: None,
+            held: VecDeque::new(),
+            stopped_dispatch: 0,
+        }
+    }
+
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = Some(capacity);
+    }
+
+    // Requests waiting for a server abandon once their patience has elapsed
+    pub fn set_patience(&mut self, patience: Sampler) {
+        self.patience = Some(patience);
+    }
+
+    // Heterogeneous servers: server i works at speeds[i] times the work rate of the queue
+    pub fn set_speeds(&mut self, speeds: &[f64]) {
+        assert_eq!(speeds.len(), self.servers.len(), "One speed per server");
+        self.speeds = speeds.to_vec();
+        self.identical = speeds.iter().all(|&s| s == speeds[0]);
+    }
+
+    pub fn set_selection(&mut self, selection: ServerSelection) {
+        self.selection = selection;
+    }
+
+    // Every server fails and gets repaired, whether it serves or not
+    pub fn set_breakdowns(&mut self, breakdowns: Breakdowns) {
+        self.breakdowns = Some(breakdowns);
+    }
+
+    // Length of the vacations of idle servers
+    pub fn set_vacation(&mut self, vacation: Sampler) {
+        self.vacation = Some(vacation);
+    }
+
+    //Earliest time from now at which the free 'server' can start a service: back from vacation, and up
+    fn available(&mut self, server: usize) -> f64 {
+        let mut time = self.time;
+        if let Some(ref mut vacation) = self.vacation {
+            // Vacations that end before a request waits are followed by another one
+            let end = &mut self.vacation_ends[server];
+            while *end < self.waiting_since {
+                // A vacation of no length ends it, as drawing more would not move it forward
+                let length = vacation(&mut self.rng);
+                *end = if length > 0. { *end + length } else { self.waiting_since };
+            }
+            time = time.max(*end);
+        }
+        if let Some(ref mut b) = self.breakdowns {
+            time = b.available(&mut self.timelines[server], time, &mut self.rng);
+        }
+        time
+    }
+
+    //Free server that can start a service the earliest (its index in free_servers), and when. Among those
+    //available at the same time, it follows the selection rule
+    fn next_free_server(&mut self) -> Option<(usize, f64)> {
+        if self.free_servers.is_empty() {
+            return None;
+        }
+        // Without breakdowns or vacations, every free server can start now, and identical ones are interchangeable
+        if self.breakdowns.is_none() && self.vacation.is_none() {
+            let n = self.free_servers.len();
+            let i = match self.selection {
+                ServerSelection::FastestFree if self.identical => n - 1,
+                ServerSelection::RandomFree => self.rng.gen_range(0, n),
+                _ => self.select(&(0..n).collect::<Vec<usize>>()),
+            };
+            return Some((i, self.time));
+        }
+        let mut times = Vec::with_capacity(self.free_servers.len());
+        for i in 0..self.free_servers.len() {
+            let server = self.free_servers[i];
+            times.push(self.available(server));
+        }
+        let start = times.iter().cloned().fold(f64::INFINITY, f64::min);
+        let candidates: Vec<usize> = (0..times.len()).filter(|&i| times[i] == start).collect();
+        Some((self.select(&candidates), start))
+    }
+
+    //Candidate taken following the selection rule, among indices in free_servers
+    fn select(&mut self, candidates: &[usize]) -> usize {
+        let k = self.servers.len();
+        match self.selection {
+            ServerSelection::FastestFree => *candidates.iter()
+                .max_by(|&&a, &&b| {
+                    let (sa, sb) = (self.free_servers[a], self.free_servers[b]);
+                    self.speeds[sa].partial_cmp(&self.speeds[sb]).unwrap().then(sb.cmp(&sa))
+                }).unwrap(),
+            ServerSelection::RandomFree => candidates[self.rng.gen_range(0, candidates.len())],
+            ServerSelection::RoundRobin => *candidates.iter()
+                .min_by_key(|&&i| (self.free_servers[i] + k - self.last_server - 1) % k).unwrap(),
         }
     }
 
-    //Sets 'process' as the active job in 'server'
-    fn enqueue(&mut self, server: usize, process: Process) {
+    //Sets 'process' as the active job in 'server', from 'start'
+    fn enqueue(&mut self, server: usize, mut process: Process, start: f64) {
         assert!(self.servers[server].is_none());
 
-        self.next_exits.push(self.time + process.work / self.work_rate, server);
+        process.req.set_service_start(Some(start));
+        let duration = process.work / (self.work_rate * self.speeds[server]);
+        let (end, interrupted) = match self.breakdowns {
+            Some(ref mut b) => b.finish(&mut self.timelines[server], start, duration, &mut self.rng),
+            None => (start + duration, false),
+        };
+        self.next_exits.push(end, server);
+        self.interrupted[server] = interrupted;
+        self.starts[server] = start;
         self.servers[server] = Some(process);
     }
 
     //Returns the active process in 'server'
     fn exit(&mut self, server: usize) -> Option<Request> {
         let ret = self.servers[server].take().map(|x| x.req);
+        if let Some(ref mut vacation) = self.vacation {
+            self.vacation_ends[server] = self.time + if self.waiting.is_empty() { vacation(&mut self.rng) } else { 0. };
+        }
+        self.free_servers.push(server);
+        self.dispatch();
+        ret
+    }
 
-        if let Some(process) = self.queue.pop_front() {
-            self.enqueue(server, process);
+    //Starts serving waiting processes on free servers, unless the dispatch is stopped
+    fn dispatch(&mut self) {
+        self.discard_deadlines();
+        while self.stopped_dispatch == 0 && !self.waiting.is_empty() {
+            let (i, start) = match self.next_free_server() {
+                Some(free) => free,
+                None => break
+            };
+            // A service starting later (after a vacation or a repair) is only set once no exit can come first:
+            // it could free another server, or be the abandonment of the request
+            if start > self.time && self.next_event().is_some_and(|t| t < start) {
+                break;
+            }
+            let server = self.free_servers.swap_remove(i);
+            self.last_server = server;
+            let process = self.next_waiting();
+            self.enqueue(server, process, start);
+            self.discard_deadlines();
         }
-        else {
-            self.free_servers.push(server);
+    }
+
+    fn next_event(&self) -> Option<f64> {
+        match (self.deadlines.peek(), self.next_exits.peek()) {
+            (Some((a, _)), Some((s, _))) => Some(a.min(s)),
+            (a, s) => a.or(s).map(|(t, _)| t),
         }
+    }
 
-        ret
+    //Removes the first waiting process
+    fn next_waiting(&mut self) -> Process {
+        loop {
+            let ticket = self.queue.pop_front().expect("Waiting process without a ticket");
+            if let Some(process) = self.waiting.remove(&ticket) {
+                return process;
+            }
+        }
+    }
+
+    //Drops the deadlines of the processes that are no longer waiting, until the earliest one is valid
+    fn discard_deadlines(&mut self) {
+        while self.deadlines.peek().is_some_and(|(_, k)| !self.waiting.contains_key(k)) {
+            self.deadlines.pop();
+        }
+    }
+
+    //Servers serving at 'time', which can be ahead of 'time' until the next exit: those whose service has started,
+    //and that are up
+    fn busy_at(&self, time: f64) -> usize {
+        (0..self.servers.len()).filter(|&s| self.servers[s].is_some() && self.starts[s] <= time
+                                             && (self.breakdowns.is_none() || !self.timelines[s].is_down(time))).count()
+    }
+
+    //Whether a waiting process abandons before the next service completion
+    fn abandons_first(&self) -> bool {
+        match (self.deadlines.peek(), self.next_exits.peek()) {
+            (Some((a, _)), Some((s, _))) => a < s,
+            (abandonment, _) => abandonment.is_some(),
+        }
     }
 }
 
 
-impl<T> Queue for MGKFIFO<T> where T: MutDistribution<f64> {
+impl<T> Queue for MGKFIFO<T> where T: ServiceDistribution + Send {
     fn arrival (&mut self, req: Request) {
 
+        let deadline = abandonment_time(&req, self.time, &mut self.patience, &mut self.rng);
+        let work = self.distribution.sample_work(&req, &mut self.rng);
         let process = Process {
             req,
-            work: self.distribution.mut_sample(&mut rand::thread_rng())
+            work
         };
 
-        if let Some(server) = self.free_servers.pop() {
-            self.enqueue(server, process)
+        let ticket = self.next_ticket;
+        self.next_ticket += 1;
+        if self.waiting.is_empty() {
+            self.waiting_since = self.time;
         }
-        else {
-            self.queue.push_back(process)
+        if let Some(d) = deadline {
+            self.deadlines.push(d.max(self.time), ticket);
         }
+        self.waiting.insert(ticket, process);
+        self.queue.push_back(ticket);
+        self.dispatch();
     }
 
     fn update_time    (&mut self, time: f64) {
         self.time = time;
+        if self.breakdowns.is_some() {
+            for timeline in &mut self.timelines {
+                timeline.forget(time);
+            }
+        }
     }
 
     fn read_next_exit (&self) -> Option<(f64,&Request)>  {
+        if self.abandons_first() {
+            return self.deadlines.peek().map(|(t, k)| (t, &self.waiting[k].req));
+        }
         match self.next_exits.peek() {
             Some((t, &s)) => {
                 Some((t, &(self.servers[s].as_ref().unwrap().req)))
@@ -82,16 +314,77 @@ impl<T> Queue for MGKFIFO<T> where T: MutDistribution<f64> {
     }
 
     fn pop_next_exit  (&mut self) -> Option<(f64,Request)> {
-    
+        if self.abandons_first() {
+            let (t, ticket) = self.deadlines.pop().unwrap();
+            self.time = t;
+            let process = self.waiting.remove(&ticket).unwrap();
+            self.dispatch();
+            return Some((t, process.req));
+        }
         match self.next_exits.pop() {
             Some((t, s)) => {
-                Some((t, self.servers[s].take().unwrap().req))
+                self.time = t;
+                Some((t, self.exit(s).unwrap()))
             },
             None => None
         }
     }
 
     fn read_load (&self) -> usize {
-        self.queue.len()
-    }    
+        self.waiting.len() + self.servers.iter().filter(|s| s.is_some()).count()
+    }
+
+    fn next_exit_abandons (&self) -> bool {
+        self.abandons_first() || self.next_exits.peek().is_some_and(|(_, &s)| self.interrupted[s])
+    }
+
+    fn read_busy_servers (&self) -> usize {
+        self.busy_at(self.time)
+    }
+
+    fn read_busy_changes (&self, until: f64) -> Vec<(f64, usize)> {
+        if self.breakdowns.is_none() && self.vacation.is_none() {
+            return Vec::new();
+        }
+        let times = (0..self.servers.len()).filter(|&s| self.servers[s].is_some())
+            .flat_map(|s| {
+                let mut times = self.timelines[s].changes();
+                times.push(self.starts[s]);
+                times
+            }).collect();
+        busy_changes(times, self.time, until, |t| self.busy_at(t))
+    }
+
+    fn read_servers (&self) -> Option<usize> {
+        Some(self.servers.len())
+    }
+
+    fn read_capacity (&self) -> Option<usize> {
+        self.capacity
+    }
+
+    fn block_next_exit (&mut self, stop_dispatch: bool) -> bool {
+        match self.next_exits.pop() {
+            Some((_, server)) => {
+                self.held.push_back((server, stop_dispatch));
+                if stop_dispatch {
+                    self.stopped_dispatch += 1;
+                }
+                true
+            },
+            None => false
+        }
+    }
+
+    fn unblock_exit (&mut self) -> Option<Request> {
+        let (server, stop_dispatch) = self.held.pop_front()?;
+        if stop_dispatch {
+            self.stopped_dispatch -= 1;
+        }
+        self.exit(server)
+    }
+
+    fn reseed (&mut self, seed: u64) {
+        self.rng = rng_from_seed(seed);
+    }
 }
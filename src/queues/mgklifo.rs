@@ -0,0 +1,24 @@
+use queues::service::ServiceDistribution;
+use queues::request::Request;
+use queues::mgkpreemptive::{MGKPreemptive,ServiceOrder};
+use queues::mgkpriority::Preemption;
+
+// Reversed arrival order
+pub struct LatestFirst;
+
+impl ServiceOrder for LatestFirst {
+    fn key(_req: &Request, arrival: usize) -> (usize, usize) {
+        (0, usize::MAX - arrival)
+    }
+}
+
+// k servers serving the latest arrived requests first. Without preemption, a free server takes the latest waiting
+// request (LIFO). With preemption (LCFS-PR), an arrival takes the server of the earliest arrived request in service,
+// which goes back on top of the waiting ones: the k latest requests are always in service
+pub type MGKLIFO<T> = MGKPreemptive<T, LatestFirst>;
+
+impl<T> MGKPreemptive<T, LatestFirst> where T: ServiceDistribution {
+    pub fn new(k: usize, work_rate: f64, preemption: Preemption, distribution: T) -> Self {
+        MGKPreemptive::with_order(k, work_rate, preemption, distribution)
+    }
+}
@@ -0,0 +1,223 @@
+use helpers::float_binaryheap::FloatBinaryHeap;
+use queues::service::ServiceDistribution;
+use helpers::rng::{SimRng,rng_from_entropy,rng_from_seed};
+use std::collections::{BTreeMap,VecDeque};
+use std::marker::PhantomData;
+use queues::request::Request;
+
+use queues::{Queue,Process};
+use queues::mgkpriority::Preemption;
+
+// Order in which the requests are served, the smallest key first, given their arrival order in the queue.
+// Keys are unique, as the arrival order is
+pub trait ServiceOrder {
+    fn key(req: &Request, arrival: usize) -> (usize, usize);
+}
+
+struct Serving {
+    process: Process,
+    key: (usize, usize),
+    started: f64,
+    ticket: usize,
+    // Served, but cannot leave yet (see block_next_exit)
+    held: bool,
+}
+
+// k servers serving the waiting requests with the smallest keys first. With preemption, a request takes the server
+// of the request in service with the largest key, if larger than its own, which goes back with the waiting ones
+pub struct MGKPreemptive<T, O> where T: ServiceDistribution, O: ServiceOrder {
+    time: f64,
+    work_rate: f64,
+    preemption: Preemption,
+    // Waiting processes, by key
+    waiting: BTreeMap<(usize, usize), Process>,
+    next_arrival: usize,
+    servers: Vec<Option<Serving>>,
+    free_servers: Vec<usize>,
+    // Service completions by (server, ticket). Those of preempted services are stale and skipped
+    next_exits: FloatBinaryHeap<(usize, usize)>,
+    next_ticket: usize,
+    distribution: T,
+    rng: SimRng,
+    capacity: Option<usize>,
+    held: VecDeque<(usize, bool)>,
+    stopped_dispatch: usize,
+    order: PhantomData<O>,
+}
+
+impl<T, O> MGKPreemptive<T, O> where T: ServiceDistribution, O: ServiceOrder {
+    pub fn with_order(k: usize, work_rate: f64, preemption: Preemption, distribution: T) -> Self {
+        MGKPreemptive {
+            time: 0.,
+            work_rate,
+            preemption,
+            waiting: BTreeMap::new(),
+            next_arrival: 0,
+            servers: (0..k).map(|_| None).collect(),
+            free_servers: (0..k).rev().collect(),
+            next_exits: FloatBinaryHeap::new(),
+            next_ticket: 0,
+            distribution,
+            rng: rng_from_entropy(),
+            capacity: None,
+            held: VecDeque::new(),
+            stopped_dispatch: 0,
+            order: PhantomData,
+        }
+    }
+
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = Some(capacity);
+    }
+
+    //Sets 'process' as the active job in 'server'. The waiting time of a request ends with its first service
+    fn enqueue(&mut self, server: usize, key: (usize, usize), mut process: Process) {
+        assert!(self.servers[server].is_none());
+
+        if process.req.get_service_start().is_none() {
+            process.req.set_service_start(Some(self.time));
+        }
+        let ticket = self.next_ticket;
+        self.next_ticket += 1;
+        self.next_exits.push(self.time + process.work / self.work_rate, (server, ticket));
+        self.servers[server] = Some(Serving {
+            process,
+            key,
+            started: self.time,
+            ticket,
+            held: false,
+        });
+    }
+
+    //Returns the active process in 'server'
+    fn exit(&mut self, server: usize) -> Option<Request> {
+        let ret = self.servers[server].take().map(|s| s.process.req);
+        self.free_servers.push(server);
+        self.dispatch();
+        ret
+    }
+
+    //Starts serving the waiting processes with the smallest keys on free servers, or on servers taken
+    //from processes with larger keys when preemptive, unless the dispatch is stopped
+    fn dispatch(&mut self) {
+        while self.stopped_dispatch == 0 {
+            let key = match self.waiting.keys().next() {
+                Some(&k) => k,
+                None => break
+            };
+            let server = match self.free_servers.pop().or_else(|| self.victim(key)) {
+                Some(s) => s,
+                None => break
+            };
+            if self.servers[server].is_some() {
+                self.preempt(server);
+            }
+            let process = self.waiting.remove(&key).unwrap();
+            self.enqueue(server, key, process);
+        }
+        self.discard_exits();
+    }
+
+    //Server whose process can be preempted by one with 'key': the one with the largest key, if larger
+    fn victim(&self, key: (usize, usize)) -> Option<usize> {
+        if self.preemption == Preemption::None {
+            return None;
+        }
+        self.servers.iter().enumerate()
+            .filter_map(|(i, s)| s.as_ref().map(|s| (i, s)))
+            .filter(|&(_, s)| !s.held && s.key > key)
+            .max_by_key(|&(_, s)| s.key)
+            .map(|(i, _)| i)
+    }
+
+    //Puts the process of 'server' back with the waiting ones
+    fn preempt(&mut self, server: usize) {
+        let Serving { mut process, key, started, .. } = self.servers[server].take().unwrap();
+        if self.preemption == Preemption::Resume {
+            process.work = (process.work - (self.time - started) * self.work_rate).max(0.);
+        }
+        self.waiting.insert(key, process);
+    }
+
+    //Drops the completions of preempted services, until the earliest one is valid
+    fn discard_exits(&mut self) {
+        while self.next_exits.peek().is_some_and(|(_, &(s, ticket))| self.servers[s].as_ref().is_none_or(|s| s.ticket != ticket)) {
+            self.next_exits.pop();
+        }
+    }
+}
+
+
+impl<T, O> Queue for MGKPreemptive<T, O> where T: ServiceDistribution + Send, O: ServiceOrder + Send {
+    fn arrival (&mut self, req: Request) {
+        let work = self.distribution.sample_work(&req, &mut self.rng);
+        let key = O::key(&req, self.next_arrival);
+        self.next_arrival += 1;
+        self.waiting.insert(key, Process {
+            req,
+            work
+        });
+        self.dispatch();
+    }
+
+    fn update_time    (&mut self, time: f64) {
+        self.time = time;
+    }
+
+    fn read_next_exit (&self) -> Option<(f64,&Request)>  {
+        self.next_exits.peek().map(|(t, &(s, _))| (t, &self.servers[s].as_ref().unwrap().process.req))
+    }
+
+    fn pop_next_exit  (&mut self) -> Option<(f64,Request)> {
+        match self.next_exits.pop() {
+            Some((t, (s, _))) => {
+                self.time = t;
+                Some((t, self.exit(s).unwrap()))
+            },
+            None => None
+        }
+    }
+
+    fn read_load (&self) -> usize {
+        self.waiting.len() + self.read_busy_servers()
+    }
+
+    fn read_busy_servers (&self) -> usize {
+        self.servers.iter().filter(|s| s.is_some()).count()
+    }
+
+    fn read_servers (&self) -> Option<usize> {
+        Some(self.servers.len())
+    }
+
+    fn read_capacity (&self) -> Option<usize> {
+        self.capacity
+    }
+
+    fn block_next_exit (&mut self, stop_dispatch: bool) -> bool {
+        match self.next_exits.pop() {
+            Some((_, (server, _))) => {
+                self.servers[server].as_mut().unwrap().held = true;
+                self.held.push_back((server, stop_dispatch));
+                if stop_dispatch {
+                    self.stopped_dispatch += 1;
+                }
+                self.discard_exits();
+                true
+            },
+            None => false
+        }
+    }
+
+    fn unblock_exit (&mut self) -> Option<Request> {
+        let (server, stop_dispatch) = self.held.pop_front()?;
+        if stop_dispatch {
+            self.stopped_dispatch -= 1;
+        }
+        self.exit(server)
+    }
+
+    fn reseed (&mut self, seed: u64) {
+        self.rng = rng_from_seed(seed);
+    }
+}
@@ -0,0 +1,34 @@
+use queues::service::ServiceDistribution;
+use queues::request::Request;
+use queues::mgkpreemptive::{MGKPreemptive,ServiceOrder};
+
+// What happens to a request in service when one of a higher priority arrives and every server is busy
+#[derive(Debug,Clone,Copy,PartialEq)]
+pub enum Preemption {
+    // The request keeps its server (non-preemptive priority)
+    None,
+    // The request goes back to the head of its class, and later resumes its remaining work
+    Resume,
+    // The request goes back to the head of its class, and later restarts its whole work (repeat-identical)
+    Repeat,
+}
+
+// Class first, then arrival order
+pub struct ByClass;
+
+impl ServiceOrder for ByClass {
+    fn key(req: &Request, arrival: usize) -> (usize, usize) {
+        (req.get_class(), arrival)
+    }
+}
+
+// k servers serving requests by priority of their class: class 0 first, then class 1, and so on.
+// Requests of the same class are served in arrival order. When preemptive, the request in service of the
+// lowest priority, the latest arrived among them, is preempted
+pub type MGKPriority<T> = MGKPreemptive<T, ByClass>;
+
+impl<T> MGKPreemptive<T, ByClass> where T: ServiceDistribution {
+    pub fn new(k: usize, work_rate: f64, preemption: Preemption, distribution: T) -> Self {
+        MGKPreemptive::with_order(k, work_rate, preemption, distribution)
+    }
+}
@@ -0,0 +1,171 @@
+use std::collections::{HashMap,VecDeque};
+
+use queues::request::Request;
+use queues::{Queue,Process};
+use helpers::float_binaryheap::FloatBinaryHeap;
+
+use queues::service::ServiceDistribution;
+use helpers::rng::{SimRng,rng_from_entropy,rng_from_seed};
+
+// Processor sharing over k servers: with n processes in service, each one gets min(1, k/n) of a server.
+// With a limit m, at most m processes are in service and the others wait in arrival order (limited PS):
+// a limit of k makes it an MGKFIFO, and k = 1 without limit an MG1PS
+pub struct MGKPS<T> where T: ServiceDistribution {
+    time: f64,
+    work_rate: f64,
+    servers: usize,
+    limit: Option<usize>,
+    // Work targets of the processes in service, by ticket
+    processes: FloatBinaryHeap<usize>,
+    active: HashMap<usize, Process>,
+    waiting: VecDeque<Process>,
+    next_ticket: usize,
+    applied_work: f64,
+    distribution: T,
+    rng: SimRng,
+    capacity: Option<usize>,
+    // Blocked requests (see Queue::block_next_exit), and whether they stop the dispatch
+    held: VecDeque<(Request, bool)>,
+    stopped_dispatch: usize,
+}
+
+impl<T> MGKPS<T> where T: ServiceDistribution {
+    pub fn new(k: usize, work_rate: f64, distribution: T) -> Self {
+        MGKPS {
+            time: 0.,
+            work_rate,
+            servers: k,
+            limit: None,
+            processes: FloatBinaryHeap::new(),
+            active: HashMap::new(),
+            waiting: VecDeque::new(),
+            next_ticket: 0,
+            applied_work: 0.,
+            distribution,
+            rng: rng_from_entropy(),
+            capacity: None,
+            held: VecDeque::new(),
+            stopped_dispatch: 0,
+        }
+    }
+
+    // Maximum number of processes in service
+    pub fn set_limit(&mut self, limit: usize) {
+        assert!(limit > 0, "The limit of processes in service must be positive");
+        self.limit = Some(limit);
+    }
+
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = Some(capacity);
+    }
+
+    //Processes in service, held ones included
+    fn in_service(&self) -> usize {
+        self.active.len() + self.held.len()
+    }
+
+    //Work done per unit of time on each process in service
+    fn rate(&self) -> f64 {
+        self.work_rate * (self.servers as f64 / self.in_service() as f64).min(1.)
+    }
+
+    fn next_exit(&self) -> Option<(f64, usize)> {
+        self.processes.peek().map(|(w, &k)| (self.time + (w - self.applied_work) / self.rate(), k))
+    }
+
+    fn pop(&mut self) -> Option<(f64, Request)> {
+        let (t, k) = self.next_exit()?;
+        self.processes.pop();
+        let process = self.active.remove(&k).unwrap();
+        self.dispatch();
+        Some((t, process.req))
+    }
+
+    //Starts serving waiting processes while below the limit, unless the dispatch is stopped
+    fn dispatch(&mut self) {
+        while self.stopped_dispatch == 0 && self.limit.is_none_or(|m| self.in_service() < m) {
+            let mut process = match self.waiting.pop_front() {
+                Some(p) => p,
+                None => break
+            };
+            process.req.set_service_start(Some(self.time));
+            let ticket = self.next_ticket;
+            self.next_ticket += 1;
+            self.processes.push(self.applied_work + process.work, ticket);
+            self.active.insert(ticket, process);
+        }
+    }
+
+    fn advance_time(&mut self, time: f64) {
+        if !self.active.is_empty() {
+            self.applied_work += (time - self.time) * self.rate();
+        }
+        self.time = time;
+    }
+}
+
+impl<T> Queue for MGKPS<T> where T: ServiceDistribution + Send {
+    fn arrival (&mut self, req: Request) {
+        let work = self.distribution.sample_work(&req, &mut self.rng);
+        self.waiting.push_back(Process {
+            req,
+            work
+        });
+        self.dispatch();
+    }
+
+    fn update_time (&mut self, time: f64) {
+        self.advance_time(time);
+    }
+
+    fn read_next_exit (&self) -> Option<(f64, &Request)> {
+        self.next_exit().map(|(t, k)| (t, &self.active[&k].req))
+    }
+
+    fn pop_next_exit (&mut self) -> Option<(f64, Request)> {
+        self.pop()
+    }
+
+    fn read_load (&self) -> usize {
+        self.active.len() + self.waiting.len() + self.held.len()
+    }
+
+    fn read_busy_servers (&self) -> usize {
+        self.in_service().min(self.servers)
+    }
+
+    fn read_servers (&self) -> Option<usize> {
+        Some(self.servers)
+    }
+
+    fn read_capacity (&self) -> Option<usize> {
+        self.capacity
+    }
+
+    fn block_next_exit (&mut self, stop_dispatch: bool) -> bool {
+        let (_, k) = match self.next_exit() {
+            Some(exit) => exit,
+            None => return false
+        };
+        self.processes.pop();
+        let process = self.active.remove(&k).unwrap();
+        self.held.push_back((process.req, stop_dispatch));
+        if stop_dispatch {
+            self.stopped_dispatch += 1;
+        }
+        true
+    }
+
+    fn unblock_exit (&mut self) -> Option<Request> {
+        let (req, stop_dispatch) = self.held.pop_front()?;
+        if stop_dispatch {
+            self.stopped_dispatch -= 1;
+        }
+        self.dispatch();
+        Some(req)
+    }
+
+    fn reseed (&mut self, seed: u64) {
+        self.rng = rng_from_seed(seed);
+    }
+}
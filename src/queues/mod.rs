@@ -2,18 +2,38 @@ pub mod request;
 pub mod mg1ps;
 pub mod mginf;
 pub mod mgkfifo;
+pub mod mgklifo;
+pub mod mgkps;
+pub mod mgkpreemptive;
+pub mod mgkpriority;
+pub mod size_based;
+pub mod round_robin;
+pub mod weighted_ps;
 pub mod poisson_generator;
 pub mod cm_poisson_generator;
 pub mod trace_generator;
 pub mod zipfgen;
 pub mod generator;
+pub mod client_population;
 pub mod queueing_network;
+pub mod simulation;
+pub mod replications;
+pub mod warmup;
 pub mod autoscaling_qnetwork;
 pub mod centralized_autoscaling_qnetwork;
 pub mod file_logger;
 pub mod passthrough;
+pub mod classifier;
+pub mod service;
+pub mod breakdowns;
+pub mod sink;
+pub mod stats_collector;
+pub mod retry;
 
 use self::request::Request;
+use self::stats_collector::QueueReport;
+use helpers::distribution::MutDistribution;
+use helpers::rng::SimRng;
 
 #[derive(Clone)]
 pub struct Process {
@@ -21,10 +41,78 @@ pub struct Process {
     work: f64
 }
 
-pub trait Queue {
-    fn arrival        (&mut self, Request);
+//Distribution boxed for queues that take optional ones, like the patience of their requests
+pub type Sampler = Box<dyn FnMut(&mut SimRng) -> f64 + Send>;
+
+pub fn sampler_from<T>(mut distribution: T) -> Sampler where T: MutDistribution<f64> + Send + 'static {
+    Box::new(move |rng| distribution.mut_sample(rng))
+}
+
+//Time at which a request arriving at 'time' abandons: its own deadline, or the end of its patience if earlier
+fn abandonment_time(req: &Request, time: f64, patience: &mut Option<Sampler>, rng: &mut SimRng) -> Option<f64> {
+    let patience = patience.as_mut().map(|p| time + p(rng));
+    match (req.get_deadline(), patience) {
+        (Some(d), Some(p)) => Some(d.min(p)),
+        (d, p) => d.or(p),
+    }
+}
+
+//Changes of 'busy_at' at the given times after 'from' and until 'until', as (time, busy servers) in order
+fn busy_changes<F>(mut times: Vec<f64>, from: f64, until: f64, busy_at: F) -> Vec<(f64, usize)> where F: Fn(f64) -> usize {
+    times.retain(|&t| t > from && t <= until);
+    times.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    times.dedup();
+    let mut busy = busy_at(from);
+    times.into_iter().filter_map(|t| {
+        let b = busy_at(t);
+        if b == busy { return None; }
+        busy = b;
+        Some((t, b))
+    }).collect()
+}
+
+//Queues are Send so that whole networks can be moved to worker threads
+pub trait Queue: Send {
+    fn arrival        (&mut self, req: Request);
+    //Advances the queue to 'time'. QNet calls it lazily, only on the queues a transition touches (the origin before
+    //it pops the next exit, the destination before the arrival): the queue must account at once for everything since
+    //its previous update (work done, failures, ...). Times never decrease, nor go past the next exit of the queue
     fn update_time    (&mut self, time: f64);
     fn read_next_exit (&self) -> Option<(f64,&Request)>;
     fn pop_next_exit  (&mut self) -> Option<(f64,Request)>;
     fn read_load	  (&self) -> usize;
+
+    //Number of requests in service, and number of servers (None if unbounded), for utilization statistics
+    fn read_busy_servers (&self) -> usize { self.read_load().min(1) }
+    fn read_servers   (&self) -> Option<usize> { Some(1) }
+    //Changes of the number of busy servers after the current time and until 'until' (at most the next exit) that
+    //happen without an arrival or an exit: services starting after a vacation or a repair, servers failing.
+    //As (time, busy servers), in order
+    fn read_busy_changes (&self, _until: f64) -> Vec<(f64, usize)> { Vec::new() }
+
+    //Maximum number of requests in the queue (None if unbounded). QNet handles the requests routed to a full queue
+    fn read_capacity  (&self) -> Option<usize> { None }
+
+    //Blocking: the next exit cannot leave yet and stays in its server, until unblock_exit returns it.
+    //It keeps its server, or in processor sharing its share of the servers (its weight, and its place under a limit
+    //of requests in service), while the other requests in service go on being served: a queue serving one request
+    //at a time stops. With 'stop_dispatch', no new service starts meanwhile, and the requests arriving wait.
+    //Returns false if the queue cannot hold requests
+    fn block_next_exit (&mut self, _stop_dispatch: bool) -> bool { false }
+    fn unblock_exit   (&mut self) -> Option<Request> { None }
+
+    //Whether the next exit is a request giving up (see Request::set_deadline) rather than a served one.
+    //QNet routes these requests to the abandonment sink of the queue instead of following its transition
+    fn next_exit_abandons (&self) -> bool { false }
+
+    //Statistics of the queue at time 'now', if they are collected (see StatisticsCollector)
+    fn read_statistics (&self, _now: f64) -> Option<QueueReport> { None }
+
+    //Replaces the random stream of the queue (called by QNet when the queue is added)
+    fn reseed         (&mut self, _seed: u64) {}
+
+    //Statistics and logs collected between these two calls belong to the warm-up period and must be discarded,
+    //except those collected at or after 'end' (the warm-up end may be detected after the fact)
+    fn begin_warmup   (&mut self) {}
+    fn end_warmup     (&mut self, _end: f64) {}
 }
@@ -3,11 +3,22 @@ use std::collections::VecDeque;
 use queues::Queue;
 use queues::request::Request;
 
+#[derive(Default)]
 pub struct PassthroughQueue {
     time: f64,
     requests: VecDeque<Request>,
 }
 
+impl PassthroughQueue {
+    pub fn new() -> Self
+    {
+        PassthroughQueue {
+            time: 0.,
+            requests: VecDeque::new(),
+        }
+    }
+}
+
 impl Queue for PassthroughQueue {
     fn arrival (&mut self, req: Request)
     {
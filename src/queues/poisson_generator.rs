@@ -4,15 +4,18 @@ use self::rand::distributions::Exp;
 use self::rand::distributions::Distribution;
 use queues::request::Request;
 use queues::Queue;
+use helpers::rng::{SimRng,rng_from_entropy,rng_from_seed};
 
 pub struct PoissonGenerator<T> where T: Distribution<usize> {
+    last_exit: f64,
     next_exit: f64,
     next_request: Option<Request>,
     pop_distribution: T,
-    iat_distribution: Exp
+    iat_distribution: Exp,
+    rng: SimRng,
 }
 
-impl<T> Queue for PoissonGenerator<T> where T: Distribution<usize> {
+impl<T> Queue for PoissonGenerator<T> where T: Distribution<usize> + Send {
     fn arrival (&mut self, _req: Request) {
         panic!("You should not arrive at a generator");
     }
@@ -28,6 +31,7 @@ impl<T> Queue for PoissonGenerator<T> where T: Distribution<usize> {
 
     fn pop_next_exit  (&mut self) -> Option<(f64,Request)> {
         let ret = (self.next_exit, self.next_request.take());
+        self.last_exit = self.next_exit;
         self.generate_next_exit();
         match ret.1 {
             None => None,
@@ -38,22 +42,30 @@ impl<T> Queue for PoissonGenerator<T> where T: Distribution<usize> {
     fn read_load (&self) -> usize {
         1
     }    
+
+    // The pending request is redrawn from the new stream
+    fn reseed (&mut self, seed: u64) {
+        self.rng = rng_from_seed(seed);
+        self.generate_next_exit();
+    }
 }
 
 impl<T> PoissonGenerator<T> where T: Distribution<usize> {
     pub fn new (rate: f64, distribution: T) -> Self{
         let mut ret = PoissonGenerator {
+            last_exit: 0.,
             next_exit: 0.,
             next_request: None,
             pop_distribution: distribution,
-            iat_distribution: Exp::new(rate)
+            iat_distribution: Exp::new(rate),
+            rng: rng_from_entropy(),
         };
         ret.generate_next_exit ();
         ret
     }
 
     fn generate_next_exit(&mut self) {
-        self.next_exit += self.iat_distribution.sample(&mut rand::thread_rng());
-        self.next_request = Some(Request::new(self.pop_distribution.sample(&mut rand::thread_rng())));
+        self.next_exit = self.last_exit + self.iat_distribution.sample(&mut self.rng);
+        self.next_request = Some(Request::new(self.pop_distribution.sample(&mut self.rng)));
     }
 }
@@ -1,17 +1,61 @@
 use queues::Queue;
+use queues::passthrough::PassthroughQueue;
+use queues::stats_collector::{NetworkReport,StatisticsCollector};
 use queues::request::Request;
+use queues::retry::{RetryPolicy,RetryStatistics};
+use queues::warmup::{Warmup,WarmupTracker};
+use helpers::rng::{SimRng,SeedSequence,rng_from_seed};
+use helpers::indexed_heap::IndexedFloatHeap;
+use helpers::alias::AliasTable;
+use helpers::float_binaryheap::FloatBinaryHeap;
 use std::vec::Vec;
-use std::f64::INFINITY;
+use std::cell::RefCell;
+use std::collections::{HashMap,VecDeque};
+use rand::Rng;
 
-type TransitionFunc = Box<Fn(&Request, &QNet)->usize>;
+type TransitionFunc = Box<dyn Fn(&Request, &QNet, &mut SimRng)->usize + Send>;
+// Probability that a request refuses to join a queue, given the current load of the queue
+type BalkingFunc = Box<dyn Fn(usize)->f64 + Send>;
 
 
+#[derive(Debug,Clone,Copy,PartialEq)]
+pub enum TransitionOutcome {
+    // The request arrived at the destination
+    Arrived,
+    // The destination was full: the request was dropped, and arrived at the loss sink if there is one
+    // (the destination is then the loss sink). The field is the full queue
+    Dropped(usize),
+    // The destination was full: the request is held by its origin until it has room
+    Blocked,
+    // The request gave up waiting in the queue given in the field (the origin), and arrived at the
+    // abandonment sink of that queue if there is one (it is discarded otherwise, and the destination is the queue itself)
+    Abandoned(usize),
+    // The request refused to join the queue given in the field, and arrived at its abandonment sink if there is one
+    // (the destination is then the abandonment sink)
+    Balked(usize),
+}
+
 #[derive(Debug)]
 pub struct Transition {
     pub time: f64,
     pub origin: usize,
     pub destination: usize,
     pub request: usize,
+    // Time spent in the network by the request so far
+    pub lifetime: f64,
+    pub outcome: TransitionOutcome,
+}
+
+// What happens to requests routed to a queue that reached its capacity
+#[derive(Debug,Clone,Copy,PartialEq)]
+pub enum Overflow {
+    // Requests are dropped, and routed to the given loss sink if any
+    Drop(Option<usize>),
+    // Requests stay in the server of their origin once served, until the destination has room
+    BlockAfterService,
+    // Same, and the origin does not start any new service meanwhile.
+    // Origins that cannot hold requests (eg, generators) drop them instead
+    BlockBeforeService,
 }
 
 #[derive(Debug)]
@@ -22,29 +66,154 @@ pub enum TransitionError {
     UnknownError
 }
 
+#[derive(Debug)]
+pub enum RoutingError {
+    // (origin, destination)
+    DestinationOutOfBound(usize, usize),
+    // (origin, destination)
+    NegativeProbability(usize, usize),
+    // (origin, sum of the probabilities)
+    NotNormalized(usize, f64),
+    // Origin whose destinations and probabilities do not match
+    LengthMismatch(usize),
+    // Origin (row of a routing matrix) that is not a queue of the network
+    OriginOutOfBound(usize),
+    // Exit of a routing matrix that is not a queue of the network
+    ExitOutOfBound(usize),
+}
+
+// Tolerance on the sum of routing probabilities
+const ROUTING_TOLERANCE: f64 = 1e-9;
+
+// Request of a client with a retry policy, until it succeeds or its last attempt fails
+struct Outstanding {
+    // Queue the request leaves at each attempt
+    source: usize,
+    attempt: usize,
+    deadline: Option<f64>,
+    // Copy of the request as it left the source at its first attempt, from which retries are issued
+    request: Request,
+    // Pending retry, if any (see retry_events)
+    event: Option<usize>,
+    // Attempts still in the network
+    in_flight: usize,
+    // Whether the request succeeded or its last attempt failed
+    done: bool,
+}
+
 pub struct QNet {
     pub number_of_queues: usize,
-    pub queues: Vec<Box<Queue>>,
+    queues: Vec<Box<dyn Queue>>,
     pub transitions: Vec<Option<TransitionFunc>>,
-    pub time: f64
+    pub time: f64,
+    seeds: SeedSequence,
+    transition_rngs: Vec<RefCell<SimRng>>,
+    // Next exit time of every queue that has one
+    calendar: IndexedFloatHeap,
+    // Queues handed out mutably, whose next exit must be re-read before the next event
+    dirty_queues: Vec<usize>,
+    warmup: Option<WarmupTracker>,
+    warmup_end: Option<f64>,
+    next_request_id: usize,
+    overflows: Vec<Overflow>,
+    // Requests dropped because each queue was full
+    losses: Vec<usize>,
+    // Origins holding a request for each queue, in blocking order
+    blocked_on: Vec<VecDeque<usize>>,
+    // Moves of blocked requests, done when their destination got room, not returned yet
+    pending: VecDeque<Transition>,
+    // Where the requests that abandon or balk at each queue go (they are discarded by default)
+    abandonment_sinks: Vec<Option<usize>>,
+    balkings: Vec<Option<BalkingFunc>>,
+    // Requests that abandoned each queue, and that balked at it
+    abandonments: Vec<usize>,
+    balks: Vec<usize>,
+    retry_policies: Vec<Option<RetryPolicy>>,
+    retry_statistics: Vec<RetryStatistics>,
+    // Requests with a retry policy, by ID (all the attempts of a request share its ID)
+    outstanding: HashMap<usize, Outstanding>,
+    // Times of the pending retries: (request ID, event). Events that no longer match their request are skipped
+    retry_events: FloatBinaryHeap<(usize, usize)>,
+    next_retry_event: usize,
+    // Client populations whose requests complete at another queue than the population: (population, completion)
+    population_completions: Vec<(usize, usize)>,
+}
+
+impl Default for QNet {
+    fn default() -> Self {
+        QNet::new()
+    }
 }
 
 impl QNet {
 
     pub fn new () -> QNet {
+        QNet::from_seed_sequence(SeedSequence::from_entropy())
+    }
+
+    // Every queue and transition added to the network gets its own stream derived
+    // from 'seed', so that the same seed and construction order reproduce a run
+    pub fn from_seed (seed: u64) -> QNet {
+        QNet::from_seed_sequence(SeedSequence::new(seed))
+    }
+
+    fn from_seed_sequence (seeds: SeedSequence) -> QNet {
         QNet {
             number_of_queues: 0,
             queues : Vec::new(),
             transitions: Vec::new(),
             time: 0.,
+            seeds,
+            transition_rngs: Vec::new(),
+            calendar: IndexedFloatHeap::new(),
+            dirty_queues: Vec::new(),
+            warmup: None,
+            warmup_end: None,
+            next_request_id: 0,
+            overflows: Vec::new(),
+            losses: Vec::new(),
+            blocked_on: Vec::new(),
+            pending: VecDeque::new(),
+            abandonment_sinks: Vec::new(),
+            balkings: Vec::new(),
+            abandonments: Vec::new(),
+            balks: Vec::new(),
+            retry_policies: Vec::new(),
+            retry_statistics: Vec::new(),
+            outstanding: HashMap::new(),
+            retry_events: FloatBinaryHeap::new(),
+            next_retry_event: 0,
+            population_completions: Vec::new(),
         }
     }
 
-    pub fn add_queue(&mut self, q: Box<Queue>) -> usize
+    // Draws a new seed from the master stream, for components living outside of the network (eg, caches)
+    pub fn derive_seed(&mut self) -> u64
+    {
+        self.seeds.next_seed()
+    }
+
+    pub fn add_queue(&mut self, mut q: Box<dyn Queue>) -> usize
     {
+        q.reseed(self.derive_seed());
+        if self.is_warming_up() {
+            q.begin_warmup();
+        }
+        let transition_rng = rng_from_seed(self.derive_seed());
         self.number_of_queues += 1;
         self.queues.push(q);
         self.transitions.push(None);
+        self.transition_rngs.push(RefCell::new(transition_rng));
+        self.overflows.push(Overflow::Drop(None));
+        self.losses.push(0);
+        self.blocked_on.push(VecDeque::new());
+        self.abandonment_sinks.push(None);
+        self.balkings.push(None);
+        self.abandonments.push(0);
+        self.balks.push(0);
+        self.retry_policies.push(None);
+        self.retry_statistics.push(RetryStatistics::default());
+        self.notify(self.number_of_queues-1);
         self.number_of_queues-1
     }
 
@@ -53,73 +222,625 @@ impl QNet {
         self.transitions[queue] = Some(trans);
     }
 
-    pub fn get_queue_mut(&mut self, queue: usize) -> &mut Queue
+    // Requests leaving 'queue' go to destinations[i] with probability probabilities[i].
+    // Probabilities must sum to one: requests leave the network by being routed to a sink
+    pub fn add_routing(&mut self, queue: usize, destinations: Vec<usize>, probabilities: &[f64]) -> Result<(), RoutingError>
+    {
+        if queue >= self.number_of_queues {
+            return Err(RoutingError::OriginOutOfBound(queue));
+        }
+        if destinations.is_empty() || destinations.len() != probabilities.len() {
+            return Err(RoutingError::LengthMismatch(queue));
+        }
+        for (&dest, &p) in destinations.iter().zip(probabilities) {
+            if dest >= self.number_of_queues {
+                return Err(RoutingError::DestinationOutOfBound(queue, dest));
+            }
+            if p < 0. || p.is_nan() {
+                return Err(RoutingError::NegativeProbability(queue, dest));
+            }
+        }
+        let sum: f64 = probabilities.iter().sum();
+        if (sum - 1.).abs() > ROUTING_TOLERANCE {
+            return Err(RoutingError::NotNormalized(queue, sum));
+        }
+
+        let table = AliasTable::new(probabilities);
+        self.add_transition(queue, Box::new(move |_,_,rng| destinations[table.sample(rng)]));
+        Ok(())
+    }
+
+    // Routing of a whole network: matrix[i][j] is the probability to go from queue i to queue j,
+    // and requests leave queue i for 'exit' (typically a sink) with the remaining probability.
+    // Rows may be empty, for queues that keep their own transition (or have none, like sinks)
+    pub fn add_routing_matrix(&mut self, matrix: &[Vec<f64>], exit: usize) -> Result<(), RoutingError>
+    {
+        if exit >= self.number_of_queues {
+            return Err(RoutingError::ExitOutOfBound(exit));
+        }
+        if matrix.len() > self.number_of_queues {
+            return Err(RoutingError::OriginOutOfBound(self.number_of_queues));
+        }
+        for (queue, row) in matrix.iter().enumerate().filter(|&(_, row)| !row.is_empty()) {
+            if row.len() != self.number_of_queues {
+                return Err(RoutingError::LengthMismatch(queue));
+            }
+            let sum: f64 = row.iter().sum();
+            if sum > 1. + ROUTING_TOLERANCE {
+                return Err(RoutingError::NotNormalized(queue, sum));
+            }
+
+            let mut destinations: Vec<usize> = (0..row.len()).filter(|&j| row[j] != 0.).collect();
+            let mut probabilities: Vec<f64> = destinations.iter().map(|&j| row[j]).collect();
+            if sum < 1. - ROUTING_TOLERANCE {
+                destinations.push(exit);
+                probabilities.push(1. - sum);
+            }
+            // Rounding errors are absorbed by the normalization of the alias table
+            let sum: f64 = probabilities.iter().sum();
+            probabilities.iter_mut().for_each(|p| *p /= sum);
+            self.add_routing(queue, destinations, &probabilities)?;
+        }
+        Ok(())
+    }
+
+    // Requests issued by the client population 'population' (see ClientPopulation) complete when they arrive at
+    // 'completion' (eg, a file logger), rather than when they are routed back to the population: their client
+    // then thinks again. Requests can arrive at 'completion' however they get there (eg, by abandoning a queue)
+    pub fn set_population_completion(&mut self, population: usize, completion: usize)
+    {
+        self.population_completions.retain(|&(p, _)| p != population);
+        self.population_completions.push((population, completion));
+    }
+
+    // Behaviour of 'queue' once it reaches its capacity (requests are dropped by default)
+    pub fn set_overflow(&mut self, queue: usize, overflow: Overflow)
+    {
+        self.overflows[queue] = overflow;
+    }
+
+    pub fn get_losses(&self, queue: usize) -> usize
+    {
+        self.losses[queue]
+    }
+
+    // Requests that give up waiting in 'queue' (see Request::set_deadline), or refuse to join it, go to 'sink'
+    pub fn set_abandonment_sink(&mut self, queue: usize, sink: usize)
     {
+        self.abandonment_sinks[queue] = Some(sink);
+    }
+
+    // Requests routed to 'queue' refuse to join it with the probability given by 'balking' for its current load
+    pub fn set_balking(&mut self, queue: usize, balking: BalkingFunc)
+    {
+        self.balkings[queue] = Some(balking);
+    }
+
+    pub fn get_abandonments(&self, queue: usize) -> usize
+    {
+        self.abandonments[queue]
+    }
+
+    pub fn get_balks(&self, queue: usize) -> usize
+    {
+        self.balks[queue]
+    }
+
+    // Requests leaving 'queue' (typically a generator, or the entry of a server farm) are retried according to 'policy'
+    pub fn set_retry_policy(&mut self, queue: usize, policy: RetryPolicy)
+    {
+        self.retry_policies[queue] = Some(policy);
+    }
+
+    pub fn get_retry_statistics(&self, queue: usize) -> &RetryStatistics
+    {
+        &self.retry_statistics[queue]
+    }
+
+    // Requests with a retry policy still tracked: not done yet, or with attempts still in the network
+    pub fn get_outstanding_requests(&self) -> usize
+    {
+        self.outstanding.len()
+    }
+
+    // The queue is rescheduled before the next event, as its next exit may have changed
+    pub fn get_queue_mut(&mut self, queue: usize) -> &mut dyn Queue
+    {
+        self.dirty_queues.push(queue);
         &mut *(self.queues[queue])
     }
 
-    pub fn get_queue(&self, queue: usize) -> &Queue
+    pub fn get_queue(&self, queue: usize) -> &dyn Queue
     {
-        & (*self.queues[queue])
+        &*self.queues[queue]
     }    
 
-    pub fn change_queue(&mut self, queue: usize, mut q: Box<Queue>) -> Box<Queue>
+    pub fn change_queue(&mut self, queue: usize, mut q: Box<dyn Queue>) -> Box<dyn Queue>
     {
+        q.reseed(self.derive_seed());
+        if self.is_warming_up() {
+            q.begin_warmup();
+        }
         std::mem::swap(&mut self.queues[queue], &mut q);
+        self.notify(queue);
         q
     } 
 
+    // Wraps 'queue' in a StatisticsCollector, whose metrics appear in report()
+    pub fn collect_statistics(&mut self, queue: usize)
+    {
+        let q = std::mem::replace(&mut self.queues[queue], Box::new(PassthroughQueue::new()));
+        self.queues[queue] = Box::new(StatisticsCollector::new(q, self.time));
+    }
+
+    pub fn collect_all_statistics(&mut self)
+    {
+        (0..self.number_of_queues).for_each(|q| self.collect_statistics(q));
+    }
+
+    // Statistics of the queues that collect them, up to the current time
+    pub fn report(&self) -> NetworkReport
+    {
+        let queues = self.queues.iter().enumerate().filter_map(|(i, q)| {
+            q.read_statistics(self.time).map(|mut r| {
+                r.queue = i;
+                r.name = i.to_string();
+                r
+            })
+        }).collect();
+        NetworkReport { time: self.time, queues }
+    }
+
+    // Statistics and logs are suppressed until the end of the warm-up period
+    pub fn set_warmup(&mut self, warmup: Warmup)
+    {
+        self.warmup = Some(WarmupTracker::new(warmup));
+        self.warmup_end = None;
+        self.queues.iter_mut().for_each(|q| q.begin_warmup());
+    }
+
+    pub fn is_warming_up(&self) -> bool
+    {
+        self.warmup.is_some()
+    }
+
+    pub fn get_warmup_end(&self) -> Option<f64>
+    {
+        self.warmup_end
+    }
+
+    fn end_warmup(&mut self, end: f64)
+    {
+        self.warmup = None;
+        self.warmup_end = Some(end);
+        self.queues.iter_mut().for_each(|q| q.end_warmup(end));
+    }
+
+    // Re-reads the next exit of 'queue' and updates its position in the event calendar
+    pub fn notify(&mut self, queue: usize)
+    {
+        let next_exit = self.queues[queue].read_next_exit().map(|(t,_)| t);
+        self.calendar.set(queue, next_exit);
+    }
+
+    // Time of the next transition, if any queue has a pending exit or any request a pending retry
+    pub fn next_event_time(&mut self) -> Option<f64>
+    {
+        if !self.pending.is_empty() {
+            return Some(self.time);
+        }
+        while let Some(queue) = self.dirty_queues.pop() {
+            self.notify(queue);
+        }
+        while self.retry_events.peek().is_some_and(|(_, &(id, event))| self.outstanding.get(&id).is_none_or(|o| o.event != Some(event))) {
+            self.retry_events.pop();
+        }
+        match (self.calendar.peek(), self.retry_events.peek()) {
+            (Some((t, _)), Some((r, _))) => Some(t.min(r)),
+            (exit, retry) => exit.map(|(t,_)| t).or(retry.map(|(r,_)| r)),
+        }
+    }
+
+    fn is_full(&self, queue: usize) -> bool
+    {
+        self.queues[queue].read_capacity().is_some_and(|c| self.queues[queue].read_load() >= c)
+    }
+
+    // Requests get their ID when they first leave a queue (see Request::get_origin)
+    fn assign_id(&mut self, r: &mut Request)
+    {
+        if !r.has_log() {
+            r.set_id(self.next_request_id);
+            self.next_request_id += 1;
+        }
+    }
+
+    // Moves 'r', which left 'orig' at time 't', into 'dest'
+    fn deliver(&mut self, t: f64, orig: usize, dest: usize, mut r: Request, outcome: TransitionOutcome) -> Transition
+    {
+        if outcome == TransitionOutcome::Arrived {
+            self.complete(t, dest, &r);
+        }
+        r.add_log_entry(t, (orig, dest));
+        self.complete_population_request(t, dest, &r);
+        let lifetime = r.get_current_lifetime();
+        let request = r.get_content();
+        self.queues[dest].update_time(t);
+        self.queues[dest].arrival(r);
+        self.notify(dest);
+        if let Some(end) = self.warmup.as_mut().and_then(|w| w.observe(t, dest, lifetime)) {
+            self.end_warmup(end);
+        }
+        Transition {
+            time: t,
+            origin: orig,
+            destination: dest,
+            request,
+            lifetime,
+            outcome,
+        }
+    }
+
+    // Transition of a request that left 'orig' at time 't' without arriving anywhere, because of 'queue'
+    fn discard(&self, t: f64, orig: usize, queue: usize, mut r: Request, outcome: TransitionOutcome) -> Transition
+    {
+        r.add_log_entry(t, (orig, queue));
+        Transition {
+            time: t,
+            origin: orig,
+            destination: queue,
+            request: r.get_content(),
+            lifetime: r.get_current_lifetime(),
+            outcome,
+        }
+    }
+
+    // Starts tracking a request leaving 'orig' for 'dest' at time 't', if 'orig' has a retry policy
+    fn issue(&mut self, t: f64, orig: usize, dest: usize, r: &Request)
+    {
+        if self.retry_policies[orig].is_none() || self.outstanding.contains_key(&r.get_id()) {
+            return;
+        }
+        let mut request = r.clone();
+        request.add_log_entry(t, (orig, dest));
+        self.outstanding.insert(r.get_id(), Outstanding {
+            source: orig,
+            attempt: r.get_attempt(),
+            deadline: None,
+            request,
+            event: None,
+            in_flight: 1,
+            done: false,
+        });
+        self.retry_statistics[orig].requests += 1;
+        self.arm(t, r.get_id());
+    }
+
+    // Sets the deadline of the current attempt of request 'id', issued at time 't', and schedules its retry
+    fn arm(&mut self, t: f64, id: usize)
+    {
+        let source = self.outstanding[&id].source;
+        let policy = self.retry_policies[source].unwrap();
+        let deadline = policy.timeout.map(|timeout| t + timeout);
+        self.outstanding.get_mut(&id).unwrap().deadline = deadline;
+        if let Some(deadline) = deadline {
+            self.schedule_retry(deadline, id);
+        }
+    }
+
+    // The current attempt of request 'id' failed at time 't': it is retried after a backoff, unless it was the last one
+    fn schedule_retry(&mut self, t: f64, id: usize)
+    {
+        let (source, attempt) = {
+            let o = &self.outstanding[&id];
+            (o.source, o.attempt)
+        };
+        let policy = self.retry_policies[source].unwrap();
+        if attempt >= policy.max_attempts {
+            // The last attempt is only given up once it fails or completes, as it may still succeed in time
+            self.outstanding.get_mut(&id).unwrap().event = None;
+            return;
+        }
+        let delay = policy.backoff.delay(attempt, &mut *self.transition_rngs[source].borrow_mut());
+        let event = self.next_retry_event;
+        self.next_retry_event += 1;
+        self.retry_events.push(t + delay, (id, event));
+        self.outstanding.get_mut(&id).unwrap().event = Some(event);
+    }
+
+    // Request 'r' was dropped, abandoned or balked at time 't'
+    fn fail(&mut self, t: f64, r: &Request)
+    {
+        let id = r.get_id();
+        let (source, current, last, pending) = match self.outstanding.get_mut(&id) {
+            Some(o) => {
+                o.in_flight -= 1;
+                // Failures of older attempts do not matter: the request was already retried
+                (o.source, !o.done && o.attempt == r.get_attempt(), o.attempt >= self.retry_policies[o.source].unwrap().max_attempts,
+                 o.event.is_some())
+            },
+            None => return
+        };
+        if current && last {
+            self.give_up(id);
+            self.retry_statistics[source].exhausted += 1;
+        }
+        // A retry already scheduled by the timeout of the attempt is kept
+        else if current && !pending {
+            self.schedule_retry(t, id);
+        }
+        self.forget(id);
+    }
+
+    // Request 'r' arrives at 'dest' at time 't', which completes it if 'dest' is the completion queue of its retry policy.
+    // An attempt reaching a sink (a queue without transition) without going through the completion queue failed
+    fn complete(&mut self, t: f64, dest: usize, r: &Request)
+    {
+        let id = r.get_id();
+        let completion = match self.outstanding.get(&id) {
+            Some(o) => self.retry_policies[o.source].unwrap().completion,
+            None => return
+        };
+        if dest != completion {
+            if self.transitions[dest].is_none() && !r.log().iter().any(|&(_, (_, d))| d == completion) {
+                self.fail(t, r);
+            }
+            return;
+        }
+        let (source, current, in_time, last) = match self.outstanding.get_mut(&id) {
+            Some(o) => {
+                o.in_flight -= 1;
+                let current = !o.done && o.attempt == r.get_attempt();
+                (o.source, current, current && o.deadline.is_none_or(|d| t <= d),
+                 o.attempt >= self.retry_policies[o.source].unwrap().max_attempts)
+            },
+            _ => return
+        };
+        if in_time {
+            self.give_up(id);
+            self.retry_statistics[source].successes += 1;
+        }
+        else {
+            self.retry_statistics[source].late_responses += 1;
+            if current && last {
+                self.give_up(id);
+                self.retry_statistics[source].exhausted += 1;
+            }
+        }
+        self.forget(id);
+    }
+
+    // 'r' arrived at 'dest': if it is the completion queue of the population that issued it, its client thinks again
+    fn complete_population_request(&mut self, t: f64, dest: usize, r: &Request)
+    {
+        if !self.population_completions.iter().any(|&(_, c)| c == dest) {
+            return;
+        }
+        if let Some(population) = r.get_origin() {
+            if self.population_completions.contains(&(population, dest)) {
+                self.queues[population].update_time(t);
+                self.queues[population].arrival(r.clone());
+                self.notify(population);
+            }
+        }
+    }
+
+    // Request 'id' succeeded or failed for good: it is no longer retried
+    fn give_up(&mut self, id: usize)
+    {
+        let o = self.outstanding.get_mut(&id).unwrap();
+        o.done = true;
+        o.event = None;
+    }
+
+    // Requests are tracked until their last attempt in the network completes or fails, to count late responses
+    fn forget(&mut self, id: usize)
+    {
+        if self.outstanding.get(&id).is_some_and(|o| o.done && o.in_flight == 0) {
+            self.outstanding.remove(&id);
+        }
+    }
+
+    // Issues the next attempt of request 'id' from its source, at time 't'
+    fn retry(&mut self, t: f64, id: usize) -> Result<Transition,TransitionError>
+    {
+        let (source, mut r) = {
+            let o = self.outstanding.get_mut(&id).unwrap();
+            o.attempt += 1;
+            o.event = None;
+            o.in_flight += 1;
+            let mut r = o.request.clone();
+            r.set_attempt(o.attempt);
+            (o.source, r)
+        };
+        self.retry_statistics[source].retries += 1;
+        self.arm(t, id);
+
+        let dest = match self.transitions[source] {
+            None => return Err(TransitionError::NoTransitionFound(source)),
+            Some(ref f) => f(&r, self, &mut self.transition_rngs[source].borrow_mut()),
+        };
+        if dest >= self.queues.len() {
+            r.add_log_entry(t, (source, dest));
+            return Err(TransitionError::DestinationOutOfBound(Transition {
+                time: t,
+                origin: source,
+                destination: dest,
+                request: r.get_content(),
+                lifetime: r.get_current_lifetime(),
+                outcome: TransitionOutcome::Arrived,
+            }));
+        }
+        Ok(self.route(t, source, dest, r))
+    }
+
+    // Whether a request routed by 'orig' refuses to join 'dest', given its current load
+    fn balks_at(&self, orig: usize, dest: usize) -> bool
+    {
+        match self.balkings[dest] {
+            Some(ref balking) => {
+                let p = balking(self.queues[dest].read_load());
+                p > 0. && self.transition_rngs[orig].borrow_mut().gen::<f64>() < p
+            },
+            None => false
+        }
+    }
+
+    // The next exit of 'queue' is a request giving up: it leaves for the abandonment sink, whatever the routing
+    fn abandon(&mut self, queue: usize) -> Result<Transition,TransitionError>
+    {
+        let (t, mut r) = match self.queues[queue].pop_next_exit() {
+            Some(exit) => exit,
+            None => return Err(TransitionError::UnknownError)
+        };
+        self.notify(queue);
+        self.assign_id(&mut r);
+        self.abandonments[queue] += 1;
+        self.fail(t, &r);
+
+        let outcome = TransitionOutcome::Abandoned(queue);
+        let ret = match self.abandonment_sinks[queue] {
+            Some(sink) => self.deliver(t, queue, sink, r, outcome),
+            None => self.discard(t, queue, queue, r, outcome),
+        };
+        self.release_blocked(queue);
+        Ok(ret)
+    }
+
+    // Once a request left 'queue', the requests blocked on it move in while it has room,
+    // which in turn may unblock the requests blocked on their origins
+    fn release_blocked(&mut self, queue: usize)
+    {
+        let mut freed = vec![queue];
+        while let Some(q) = freed.pop() {
+            while !self.blocked_on[q].is_empty() && !self.is_full(q) {
+                let orig = self.blocked_on[q].pop_front().unwrap();
+                self.queues[orig].update_time(self.time);
+                let mut r = self.queues[orig].unblock_exit().expect("Blocked queue without a held request");
+                self.notify(orig);
+                self.assign_id(&mut r);
+                let trans = self.deliver(self.time, orig, q, r, TransitionOutcome::Arrived);
+                self.pending.push_back(trans);
+                freed.push(orig);
+            }
+        }
+    }
+
     pub fn make_transition (&mut self) -> Result<Transition,TransitionError>
     {
-        let mut orig_q = self.number_of_queues;
-        let mut next_exit = INFINITY;
-        for queue in 0..self.number_of_queues {
-            if let Some((t,_)) = self.queues[queue].read_next_exit() {
-                if t <= next_exit {
-                    next_exit = t;
-                    orig_q = queue;
-                }
+        if let Some(trans) = self.pending.pop_front() {
+            return Ok(trans);
+        }
+        let next_event = self.next_event_time();
+
+        let (next_exit, orig_q) = match self.calendar.peek() {
+            Some(event) if next_event == Some(event.0) => event,
+            _ => match self.retry_events.pop() {
+                Some((t, (id, _))) => {
+                    self.time = t;
+                    return self.retry(t, id);
+                },
+                None => return Err(TransitionError::NoExitFound(self.number_of_queues))
             }
+        };
+        // Only the origin and destination queues are brought up to date: the others are
+        // advanced lazily, the next time they are involved in a transition
+        self.queues[orig_q].update_time(next_exit);
+        self.time = next_exit;
+
+        if self.queues[orig_q].next_exit_abandons() {
+            return self.abandon(orig_q);
         }
 
+        // The destination is chosen before the request leaves, as it may not be able to
+        let dest_q = match (&self.transitions[orig_q], self.queues[orig_q].read_next_exit()) {
+            (None, _) => return Err(TransitionError::NoTransitionFound(orig_q)),
+            (_, None) => return Err(TransitionError::UnknownError),
+            (Some(f), Some((_, r))) => f(r, self, &mut self.transition_rngs[orig_q].borrow_mut()),
+        };
 
-        if orig_q < self.number_of_queues {
-            self.queues.iter_mut().for_each(|x| x.update_time(next_exit));
-
-            if let Some((t,mut r)) = self.queues[orig_q].pop_next_exit() {
-                self.time = t;
-                match self.transitions[orig_q] {
-                    None => Err(TransitionError::NoTransitionFound(orig_q)),
-                    Some(ref f) => { 
-                        let dest_q = f(&r, &self);
-                        let ret = Transition {
-                            time: t,
-                            origin: orig_q,
-                            destination: dest_q,
-                            request: r.get_content(),
-                        };
-                        if dest_q >= self.queues.len() {
-                            Err(TransitionError::DestinationOutOfBound(ret))
-                        }
-                        else {
-                            r.add_log_entry(t, (orig_q, dest_q));
-                            //println!("Transition: {}->{}", orig_q, dest_q);
-                            self.queues[dest_q].arrival(r);
-                            Ok(ret)
-                        }
-                    }
+        // A request leaving a full queue for the same queue frees its own room
+        if dest_q < self.queues.len() && dest_q != orig_q && self.is_full(dest_q) {
+            let overflow = self.overflows[dest_q];
+            if !matches!(overflow, Overflow::Drop(_)) {
+                let (request, lifetime) = {
+                    let r = self.queues[orig_q].read_next_exit().unwrap().1;
+                    (r.get_content(), r.get_current_lifetime())
+                };
+                if self.queues[orig_q].block_next_exit(overflow == Overflow::BlockBeforeService) {
+                    self.notify(orig_q);
+                    self.blocked_on[dest_q].push_back(orig_q);
+                    return Ok(Transition {
+                        time: next_exit,
+                        origin: orig_q,
+                        destination: dest_q,
+                        request,
+                        lifetime,
+                        outcome: TransitionOutcome::Blocked,
+                    });
                 }
             }
-            else {
-                Err(TransitionError::UnknownError)
+        }
+
+        let (t, mut r) = match self.queues[orig_q].pop_next_exit() {
+            Some(exit) => exit,
+            None => return Err(TransitionError::UnknownError)
+        };
+        self.notify(orig_q);
+        self.assign_id(&mut r);
+
+        if dest_q >= self.queues.len() {
+            r.add_log_entry(t, (orig_q, dest_q));
+            return Err(TransitionError::DestinationOutOfBound(Transition {
+                time: t,
+                origin: orig_q,
+                destination: dest_q,
+                request: r.get_content(),
+                lifetime: r.get_current_lifetime(),
+                outcome: TransitionOutcome::Arrived,
+            }));
+        }
+
+        self.issue(t, orig_q, dest_q, &r);
+        let ret = self.route(t, orig_q, dest_q, r);
+
+        self.release_blocked(orig_q);
+        Ok(ret)
+    }
+
+    // Moves 'r', which left 'orig' at time 't', into 'dest' unless it is full or the request balks
+    fn route(&mut self, t: f64, orig: usize, dest: usize, r: Request) -> Transition
+    {
+        if self.is_full(dest) {
+            self.losses[dest] += 1;
+            self.fail(t, &r);
+            match self.overflows[dest] {
+                Overflow::Drop(Some(sink)) => self.deliver(t, orig, sink, r, TransitionOutcome::Dropped(dest)),
+                // Without a loss sink (or an origin able to hold it), the request is discarded
+                _ => self.discard(t, orig, dest, r, TransitionOutcome::Dropped(dest)),
+            }
+        }
+        else if self.balks_at(orig, dest) {
+            self.balks[dest] += 1;
+            self.fail(t, &r);
+            match self.abandonment_sinks[dest] {
+                Some(sink) => self.deliver(t, orig, sink, r, TransitionOutcome::Balked(dest)),
+                None => self.discard(t, orig, dest, r, TransitionOutcome::Balked(dest)),
             }
         }
         else {
-            Err(TransitionError::NoExitFound(orig_q))
+            self.deliver(t, orig, dest, r, TransitionOutcome::Arrived)
         }
     }
 
     pub fn get_time(&self) -> f64 {
         self.time
     }
+
+    // Moves the clock to 'time', when nothing happens until then (eg, at the horizon of a run). Queues are
+    // advanced lazily, as usual
+    pub fn advance_time(&mut self, time: f64) {
+        assert!(self.next_event_time().is_none_or(|t| t >= time), "Cannot advance the time past the next event");
+        self.time = self.time.max(time);
+    }
 }
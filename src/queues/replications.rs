@@ -0,0 +1,180 @@
+use std::vec::Vec;
+
+use helpers::parallel::parallel_map;
+use helpers::rng::SeedSequence;
+use helpers::statistics::Estimate;
+use queues::simulation::{RunReport,Simulation,StopCondition,StopReason};
+
+pub enum Precision {
+    // Fixed number of replications (or batches)
+    Fixed(usize),
+    // Replications (or batches) are added until the relative half-width of the confidence
+    // interval of the response time is below 'target', within [min, max] replications
+    RelativeHalfWidth { target: f64, min: usize, max: usize },
+}
+
+impl Precision {
+    fn is_reached(&self, estimate: &ReplicationReport) -> bool {
+        // Replications without departures count, but do not help the precision of the response time
+        let n = estimate.throughput.samples;
+        match *self {
+            Precision::Fixed(nb) => n >= nb,
+            Precision::RelativeHalfWidth { target, min, max } =>
+                n >= max || (estimate.response_time.samples >= min.max(2) && estimate.response_time.relative_half_width() <= target),
+        }
+    }
+}
+
+pub struct ReplicationParameters {
+    // Queue at which the response time and throughput are measured (typically the file logger)
+    pub sink: usize,
+    pub confidence: f64,
+    pub precision: Precision,
+    // Master seed from which the seed of each independent replication is derived
+    pub seed: u64,
+}
+
+#[derive(Debug)]
+pub struct ReplicationReport {
+    // Response time over the replications (or batches) with at least one departure at the sink
+    pub response_time: Estimate,
+    pub throughput: Estimate,
+    // Time-averaged load of each queue
+    pub loads: Vec<Estimate>,
+    // Number of replications (or batches) without any departure at the sink, which give no response time
+    pub empty: usize,
+}
+
+// One observation of each metric per replication (or batch)
+struct Observations {
+    response_times: Vec<f64>,
+    throughputs: Vec<f64>,
+    loads: Vec<Vec<f64>>,
+    empty: usize,
+}
+
+impl Observations {
+    fn new() -> Self {
+        Observations {
+            response_times: Vec::new(),
+            throughputs: Vec::new(),
+            loads: Vec::new(),
+            empty: 0,
+        }
+    }
+
+    fn add(&mut self, report: &RunReport, sink: usize) {
+        if report.arrivals.get(sink).is_some_and(|&a| a > 0) {
+            self.response_times.push(report.mean_response_time(sink));
+        }
+        else {
+            self.empty += 1;
+        }
+        self.throughputs.push(report.throughput(sink));
+
+        // Queues may be added during a run (eg, autoscaling): those missing from previous runs had no load
+        let nb_runs = self.throughputs.len();
+        if self.loads.len() < report.load_areas.len() {
+            self.loads.resize(report.load_areas.len(), vec![0.; nb_runs - 1]);
+        }
+        for (q, loads) in self.loads.iter_mut().enumerate() {
+            loads.push(report.mean_load(q));
+        }
+    }
+
+    fn estimate(&self, confidence: f64) -> ReplicationReport {
+        ReplicationReport {
+            response_time: Estimate::from_samples(&self.response_times, confidence),
+            throughput: Estimate::from_samples(&self.throughputs, confidence),
+            loads: self.loads.iter().map(|l| Estimate::from_samples(l, confidence)).collect(),
+            empty: self.empty,
+        }
+    }
+}
+
+// Runs the simulation until the end of its warm-up period (if any), or until 'stop' is met
+fn skip_warmup<S: Simulation>(sim: &mut S, stop: StopCondition) {
+    if sim.get_qnet().is_warming_up() {
+        let warmup_done = StopCondition::Predicate(Box::new(|qn| !qn.is_warming_up()));
+        let report = sim.run_until(StopCondition::Any(vec![warmup_done, stop]));
+        assert!(!report.is_error(), "Simulation failed during warm-up: {:?}", report.stop_reason);
+    }
+}
+
+fn run_replication<S,F,G> (factory: &F, stop: &G, seed: u64) -> RunReport
+    where S: Simulation, F: Fn(u64) -> S, G: Fn() -> StopCondition
+{
+    let mut sim = factory(seed);
+    skip_warmup(&mut sim, stop());
+
+    let report = sim.run_until(stop());
+    assert!(!report.is_error(), "Replication failed: {:?}", report.stop_reason);
+    report
+}
+
+// Runs independent replications of the network built by 'factory' from a seed, each of them until 'stop'
+pub fn independent_replications<S,F,G> (factory: F, stop: G, params: &ReplicationParameters) -> ReplicationReport
+    where S: Simulation, F: Fn(u64) -> S, G: Fn() -> StopCondition
+{
+    let mut seeds = SeedSequence::new(params.seed);
+    let mut observations = Observations::new();
+
+    loop {
+        let report = run_replication(&factory, &stop, seeds.next_seed());
+        observations.add(&report, params.sink);
+
+        let estimate = observations.estimate(params.confidence);
+        if params.precision.is_reached(&estimate) {
+            return estimate;
+        }
+    }
+}
+
+// Same as independent_replications, with the replications spread over 'threads' threads.
+// Each network is built and run within its worker thread, so it does not need to be Send
+pub fn parallel_independent_replications<S,F,G> (factory: F, stop: G, params: &ReplicationParameters, threads: usize) -> ReplicationReport
+    where S: Simulation, F: Fn(u64) -> S + Sync, G: Fn() -> StopCondition + Sync
+{
+    let mut seeds = SeedSequence::new(params.seed);
+    let mut observations = Observations::new();
+
+    loop {
+        // Replications are run in rounds, but added in seed order and only until the precision is reached:
+        // the result is the same as the sequential one, whatever the number of threads
+        let round: Vec<u64> = (0..threads.max(1)).map(|_| seeds.next_seed()).collect();
+        let reports = parallel_map(round, threads, |seed| run_replication(&factory, &stop, seed));
+
+        for report in reports {
+            observations.add(&report, params.sink);
+
+            let estimate = observations.estimate(params.confidence);
+            if params.precision.is_reached(&estimate) {
+                return estimate;
+            }
+        }
+    }
+}
+
+// Splits a single run into consecutive batches of 'batch_duration' simulated seconds, each batch
+// giving one observation. Batches must be long enough for their means to be nearly independent
+pub fn batch_means<S: Simulation> (sim: &mut S, batch_duration: f64, params: &ReplicationParameters) -> ReplicationReport
+{
+    let mut observations = Observations::new();
+    skip_warmup(sim, StopCondition::Exhaustion);
+
+    loop {
+        let end = sim.get_qnet().get_time() + batch_duration;
+        let report = sim.run_until(StopCondition::Time(end));
+        match report.stop_reason {
+            StopReason::Error(e) => panic!("Batch failed: {:?}", e),
+            // An incomplete last batch would bias the estimation
+            StopReason::NoMoreEvents => return observations.estimate(params.confidence),
+            StopReason::ConditionMet => observations.add(&report, params.sink),
+        }
+
+        let estimate = observations.estimate(params.confidence);
+        if params.precision.is_reached(&estimate) {
+            return estimate;
+        }
+    }
+}
@@ -1,8 +1,80 @@
-static mut REQUEST_COUNTER: usize = 0;
+use std::collections::BTreeMap;
 
 type LogKey   = f64;
 type LogEntry = (usize, usize);
 
+// Conventional attribute names (the deadline of a request is a field of its own, see Request::set_deadline)
+pub mod attributes {
+    // Amount of work of the request, eg an object size in bytes
+    pub const SIZE: &str = "size";
+    // Client the request belongs to, set by a classifier
+    pub const TENANT: &str = "tenant";
+}
+
+#[derive(PartialEq,Clone,Debug)]
+pub enum Attribute {
+    Int(i64),
+    Float(f64),
+    Bool(bool),
+    Text(String),
+}
+
+impl Attribute {
+    pub fn as_int(&self) -> Option<i64> {
+        match *self {
+            Attribute::Int(i) => Some(i),
+            _ => None
+        }
+    }
+
+    // Integers are converted
+    pub fn as_float(&self) -> Option<f64> {
+        match *self {
+            Attribute::Int(i) => Some(i as f64),
+            Attribute::Float(f) => Some(f),
+            _ => None
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match *self {
+            Attribute::Bool(b) => Some(b),
+            _ => None
+        }
+    }
+
+    pub fn as_text(&self) -> Option<&str> {
+        match *self {
+            Attribute::Text(ref t) => Some(t),
+            _ => None
+        }
+    }
+}
+
+impl From<i64> for Attribute {
+    fn from(i: i64) -> Self { Attribute::Int(i) }
+}
+
+impl From<usize> for Attribute {
+    fn from(i: usize) -> Self { Attribute::Int(i as i64) }
+}
+
+impl From<f64> for Attribute {
+    fn from(f: f64) -> Self { Attribute::Float(f) }
+}
+
+impl From<bool> for Attribute {
+    fn from(b: bool) -> Self { Attribute::Bool(b) }
+}
+
+impl From<String> for Attribute {
+    fn from(t: String) -> Self { Attribute::Text(t) }
+}
+
+impl<'a> From<&'a str> for Attribute {
+    fn from(t: &'a str) -> Self { Attribute::Text(t.to_owned()) }
+}
+
 #[derive(PartialEq,Clone,Debug)]
 pub struct Request(Box<_Request>);
 
@@ -11,21 +83,82 @@ struct _Request {
     id: usize,
     content: usize,
     log: Vec<(LogKey, LogEntry)>,
+    // Time at which the request entered service in its current queue, for queues where it may wait
+    service_start: Option<f64>,
+    // Time after which the request gives up, if it is still waiting or in service in a queue that lets requests abandon
+    deadline: Option<f64>,
+    // Attempt of the client that issued the request, starting at 1 (see RetryPolicy)
+    attempt: usize,
+    class: usize,
+    attributes: BTreeMap<String, Attribute>,
 }
 
 impl Request {
     pub fn new (content: usize) -> Self {
-        Request { 0: Box::new(_Request::new(content)) }
+        Request(Box::new(_Request::new(content)))
     }
 
     pub fn get_content (&self) -> usize {
         self.0.get_content()
     }
 
+    // Requests of class 0 unless set otherwise, eg by a Classifier
+    pub fn set_class(&mut self, class: usize) {
+        self.0.class = class;
+    }
+
+    pub fn get_class(&self) -> usize {
+        self.0.class
+    }
+
+    pub fn set_attribute<V: Into<Attribute>>(&mut self, key: &str, value: V) {
+        self.0.attributes.insert(key.to_owned(), value.into());
+    }
+
+    pub fn get_attribute(&self, key: &str) -> Option<&Attribute> {
+        self.0.attributes.get(key)
+    }
+
+    pub fn remove_attribute(&mut self, key: &str) -> Option<Attribute> {
+        self.0.attributes.remove(key)
+    }
+
+    pub fn get_int(&self, key: &str) -> Option<i64> {
+        self.get_attribute(key).and_then(|a| a.as_int())
+    }
+
+    pub fn get_float(&self, key: &str) -> Option<f64> {
+        self.get_attribute(key).and_then(|a| a.as_float())
+    }
+
+    pub fn get_bool(&self, key: &str) -> Option<bool> {
+        self.get_attribute(key).and_then(|a| a.as_bool())
+    }
+
+    pub fn get_text(&self, key: &str) -> Option<&str> {
+        self.get_attribute(key).and_then(|a| a.as_text())
+    }
+
+    // Attributes in the order of their names
+    pub fn get_attributes(&self) -> &BTreeMap<String, Attribute> {
+        &self.0.attributes
+    }
+
     pub fn get_id(&self) -> usize {
         self.0.get_id()
     }
 
+    // IDs are allocated by the network when the request leaves its source,
+    // so that they only depend on the network they belong to
+    pub fn set_id(&mut self, id: usize) {
+        self.0.id = id;
+    }
+
+    pub fn has_log(&self) -> bool
+    {
+        !self.0.log.is_empty()
+    }
+
     pub fn add_log_entry(&mut self, key: LogKey, entry: LogEntry)
     {
         self.0.log.push((key, entry));
@@ -36,6 +169,54 @@ impl Request {
         self.0.log.clone()
     }
 
+    // Same, without copying it
+    pub fn log(&self) -> &[(LogKey, LogEntry)]
+    {
+        &self.0.log
+    }
+
+    // Queue the request first left
+    pub fn get_origin(&self) -> Option<usize>
+    {
+        self.0.log.first().map(|&(_, (orig, _))| orig)
+    }
+
+    // Time at which the request arrived in its current queue
+    pub fn get_last_log_time(&self) -> Option<f64>
+    {
+        self.0.log.last().map(|&(t,_)| t)
+    }
+
+    pub fn set_service_start(&mut self, time: Option<f64>)
+    {
+        self.0.service_start = time;
+    }
+
+    pub fn get_service_start(&self) -> Option<f64>
+    {
+        self.0.service_start
+    }
+
+    pub fn set_deadline(&mut self, deadline: Option<f64>)
+    {
+        self.0.deadline = deadline;
+    }
+
+    pub fn get_deadline(&self) -> Option<f64>
+    {
+        self.0.deadline
+    }
+
+    pub fn set_attempt(&mut self, attempt: usize)
+    {
+        self.0.attempt = attempt;
+    }
+
+    pub fn get_attempt(&self) -> usize
+    {
+        self.0.attempt
+    }
+
     pub fn get_current_lifetime(&self) -> f64
     {
         if self.0.log.len() <= 1 {
@@ -49,16 +230,16 @@ impl Request {
 
 impl _Request {
     pub fn new (content: usize) -> Self {
-        let mut ret = _Request {
+        _Request {
             id: 0,
             content,
-            log : Vec::new()
-        };
-        unsafe {
-            ret.id = REQUEST_COUNTER;
-            REQUEST_COUNTER += 1;
+            log : Vec::new(),
+            service_start: None,
+            deadline: None,
+            attempt: 1,
+            class: 0,
+            attributes: BTreeMap::new(),
         }
-        ret
     }
 
     pub fn get_content (&self) -> usize {
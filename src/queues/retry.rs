@@ -0,0 +1,75 @@
+use rand::Rng;
+
+// Randomization of the backoff delays, so that clients that failed together do not retry together
+#[derive(Debug,Clone,Copy,PartialEq)]
+pub enum Jitter {
+    None,
+    // Uniform in [0, delay]
+    Full,
+    // Uniform in [delay/2, delay]
+    Equal,
+}
+
+// Exponential backoff: the n-th retry waits initial * multiplier^(n-1), up to 'max', before the jitter
+#[derive(Debug,Clone,Copy)]
+pub struct Backoff {
+    pub initial: f64,
+    pub multiplier: f64,
+    pub max: f64,
+    pub jitter: Jitter,
+}
+
+impl Backoff {
+    pub fn constant(delay: f64) -> Self {
+        Backoff {
+            initial: delay,
+            multiplier: 1.,
+            max: delay,
+            jitter: Jitter::None,
+        }
+    }
+
+    pub fn exponential(initial: f64, multiplier: f64, max: f64, jitter: Jitter) -> Self {
+        Backoff {
+            initial,
+            multiplier,
+            max,
+            jitter,
+        }
+    }
+
+    // Delay before retry number 'retry' (1 for the first retry)
+    pub fn delay<R: Rng + ?Sized>(&self, retry: usize, rng: &mut R) -> f64 {
+        let delay = (self.initial * self.multiplier.powi(retry as i32 - 1)).min(self.max);
+        match self.jitter {
+            Jitter::None => delay,
+            Jitter::Full => delay * rng.gen::<f64>(),
+            Jitter::Equal => delay / 2. * (1. + rng.gen::<f64>()),
+        }
+    }
+}
+
+// Client-side retries of the requests leaving a queue (see QNet::set_retry_policy). An attempt fails when it
+// does not reach 'completion' (typically the FileLogger) within 'timeout', or when it is dropped, abandons,
+// balks or ends in another sink on its way. The request is then issued again from the same queue after a backoff,
+// up to 'max_attempts' attempts in total. Attempts that timed out are not cancelled: they still load the network
+#[derive(Debug,Clone,Copy)]
+pub struct RetryPolicy {
+    pub completion: usize,
+    pub timeout: Option<f64>,
+    pub max_attempts: usize,
+    pub backoff: Backoff,
+}
+
+#[derive(Debug,Clone,Default)]
+pub struct RetryStatistics {
+    // Requests issued for the first time
+    pub requests: usize,
+    pub retries: usize,
+    // Requests whose current attempt reached the completion queue in time
+    pub successes: usize,
+    // Attempts that reached the completion queue after their timeout, or after a newer attempt was issued
+    pub late_responses: usize,
+    // Requests whose last attempt failed
+    pub exhausted: usize,
+}
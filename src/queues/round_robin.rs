@@ -0,0 +1,196 @@
+use std::collections::{HashMap,VecDeque};
+
+use queues::request::Request;
+use queues::{Queue,Process,Sampler};
+
+use queues::service::ServiceDistribution;
+use helpers::rng::{SimRng,rng_from_entropy,rng_from_seed};
+
+// Single server serving its processes in turn, for at most a quantum of time each, the unfinished ones going back
+// to the end of the cycle. Each switch to another process can cost an overhead, during which no work is done.
+// MG1PS is the limit of a quantum going to zero without overhead
+pub struct RoundRobin<T> where T: ServiceDistribution {
+    time: f64,
+    work_rate: f64,
+    quantum: f64,
+    overhead: Option<Sampler>,
+    // Overheads drawn for the next switches, in order, so that the next exit is known in advance
+    overheads: VecDeque<f64>,
+    // Tickets of the processes, the one in service first. The work of a process is what remains at the start of its quantum
+    cycle: VecDeque<usize>,
+    processes: HashMap<usize, Process>,
+    next_ticket: usize,
+    // Time at which the current quantum starts, once the overhead of the switch elapsed (may be ahead of 'time')
+    quantum_start: f64,
+    next_exit: Option<(f64, usize)>,
+    distribution: T,
+    rng: SimRng,
+    capacity: Option<usize>,
+    // Blocked requests (see Queue::block_next_exit)
+    held: VecDeque<Request>,
+}
+
+impl<T> RoundRobin<T> where T: ServiceDistribution {
+    pub fn new(quantum: f64, work_rate: f64, distribution: T) -> Self {
+        assert!(quantum > 0., "The quantum must be positive");
+        RoundRobin {
+            time: 0.,
+            work_rate,
+            quantum,
+            overhead: None,
+            overheads: VecDeque::new(),
+            cycle: VecDeque::new(),
+            processes: HashMap::new(),
+            next_ticket: 0,
+            quantum_start: 0.,
+            next_exit: None,
+            distribution,
+            rng: rng_from_entropy(),
+            capacity: None,
+            held: VecDeque::new(),
+        }
+    }
+
+    // Time lost at every switch from a process to another
+    pub fn set_switch_overhead(&mut self, overhead: Sampler) {
+        self.overhead = Some(overhead);
+    }
+
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = Some(capacity);
+    }
+
+    //Overhead of the i-th next switch
+    fn switch_overhead(&mut self, i: usize) -> f64 {
+        match self.overhead {
+            Some(ref mut sampler) => {
+                while self.overheads.len() <= i {
+                    let o = sampler(&mut self.rng);
+                    self.overheads.push_back(o);
+                }
+                self.overheads[i]
+            },
+            None => 0.
+        }
+    }
+
+    //Computes the next exit, going through the cycle until a process completes within its quantum
+    fn plan(&mut self) {
+        self.next_exit = None;
+        if !self.held.is_empty() || self.cycle.is_empty() {
+            return;
+        }
+        let quantum_work = self.quantum * self.work_rate;
+        let mut works: VecDeque<(usize, f64)> = self.cycle.iter().map(|k| (*k, self.processes[k].work)).collect();
+        let mut start = self.quantum_start;
+        let mut switches = 0;
+        loop {
+            let (k, w) = works.pop_front().unwrap();
+            if w <= quantum_work {
+                self.next_exit = Some((start + w / self.work_rate, k));
+                return;
+            }
+            works.push_back((k, w - quantum_work));
+            start += self.quantum;
+            if works.len() > 1 {
+                start += self.switch_overhead(switches);
+                switches += 1;
+            }
+        }
+    }
+
+    //Moves the processes whose quantum ended by 'time' to the end of the cycle
+    fn advance_time(&mut self, time: f64) {
+        let quantum_work = self.quantum * self.work_rate;
+        while self.held.is_empty() && self.quantum_start + self.quantum <= time {
+            let k = match self.cycle.front() {
+                Some(&k) if self.processes[&k].work > quantum_work => k,
+                _ => break
+            };
+            self.processes.get_mut(&k).unwrap().work -= quantum_work;
+            self.cycle.rotate_left(1);
+            self.quantum_start += self.quantum;
+            if self.cycle.len() > 1 {
+                self.quantum_start += self.switch_overhead(0);
+                self.overheads.pop_front();
+            }
+        }
+        self.time = time;
+    }
+
+    fn pop(&mut self) -> Option<(f64, Request)> {
+        let (t, k) = self.next_exit?;
+        self.cycle.pop_front();
+        let process = self.processes.remove(&k).unwrap();
+        self.time = t;
+        self.quantum_start = t;
+        if !self.cycle.is_empty() {
+            self.quantum_start += self.switch_overhead(0);
+            self.overheads.pop_front();
+        }
+        self.plan();
+        Some((t, process.req))
+    }
+}
+
+impl<T> Queue for RoundRobin<T> where T: ServiceDistribution + Send {
+    fn arrival (&mut self, req: Request) {
+        let work = self.distribution.sample_work(&req, &mut self.rng);
+        let ticket = self.next_ticket;
+        self.next_ticket += 1;
+        if self.cycle.is_empty() {
+            self.quantum_start = self.time;
+        }
+        self.cycle.push_back(ticket);
+        self.processes.insert(ticket, Process {
+            req,
+            work
+        });
+        self.plan();
+    }
+
+    fn update_time (&mut self, time: f64) {
+        self.advance_time(time);
+    }
+
+    fn read_next_exit (&self) -> Option<(f64, &Request)> {
+        self.next_exit.map(|(t, k)| (t, &self.processes[&k].req))
+    }
+
+    fn pop_next_exit (&mut self) -> Option<(f64, Request)> {
+        self.pop()
+    }
+
+    fn read_load (&self) -> usize {
+        self.cycle.len() + self.held.len()
+    }
+
+    fn read_capacity (&self) -> Option<usize> {
+        self.capacity
+    }
+
+    fn block_next_exit (&mut self, _stop_dispatch: bool) -> bool {
+        match self.pop() {
+            Some((_, req)) => {
+                self.held.push_back(req);
+                self.next_exit = None;
+                true
+            },
+            None => false
+        }
+    }
+
+    // The switch to the next process overlaps with the blocking
+    fn unblock_exit (&mut self) -> Option<Request> {
+        let ret = self.held.pop_front();
+        if self.held.is_empty() {
+            self.quantum_start = self.quantum_start.max(self.time);
+            self.plan();
+        }
+        ret
+    }
+
+    fn reseed (&mut self, seed: u64) {
+        self.rng = rng_from_seed(seed);
+    }
+}
@@ -0,0 +1,96 @@
+use std::collections::HashMap;
+
+use rand::Rng;
+
+use helpers::distribution::MutDistribution;
+use queues::request::Request;
+
+// Work brought by a request to a queue (divided by the work rate of the queue to get its service time).
+// Every distribution gives i.i.d. work whatever the request
+pub trait ServiceDistribution {
+    fn sample_work<R: Rng + ?Sized> (&mut self, req: &Request, rng: &mut R) -> f64;
+}
+
+impl<T> ServiceDistribution for T where T: MutDistribution<f64> {
+    fn sample_work<R: Rng + ?Sized> (&mut self, _req: &Request, rng: &mut R) -> f64 {
+        self.mut_sample(rng)
+    }
+}
+
+// Requests of class i get their work from distributions[i], other classes from 'default', or from the last
+// distribution without default
+#[derive(Clone)]
+pub struct PerClass<T> where T: MutDistribution<f64> {
+    distributions: Vec<T>,
+    default: Option<T>,
+}
+
+impl<T> PerClass<T> where T: MutDistribution<f64> {
+    pub fn new(distributions: Vec<T>, default: Option<T>) -> Self {
+        assert!(!distributions.is_empty() || default.is_some(), "A per-class service needs a distribution");
+        PerClass {
+            distributions,
+            default,
+        }
+    }
+}
+
+impl<T> ServiceDistribution for PerClass<T> where T: MutDistribution<f64> {
+    fn sample_work<R: Rng + ?Sized> (&mut self, req: &Request, rng: &mut R) -> f64 {
+        let last = self.distributions.len().saturating_sub(1);
+        match self.distributions.get_mut(req.get_class()) {
+            Some(d) => d.mut_sample(rng),
+            None => self.default.as_mut().unwrap_or(&mut self.distributions[last]).mut_sample(rng)
+        }
+    }
+}
+
+// Same, by content: as contents are not known in advance, the default is required
+#[derive(Clone)]
+pub struct PerContent<T> where T: MutDistribution<f64> {
+    distributions: HashMap<usize, T>,
+    default: T,
+}
+
+impl<T> PerContent<T> where T: MutDistribution<f64> {
+    pub fn new(distributions: HashMap<usize, T>, default: T) -> Self {
+        PerContent {
+            distributions,
+            default,
+        }
+    }
+}
+
+impl<T> ServiceDistribution for PerContent<T> where T: MutDistribution<f64> {
+    fn sample_work<R: Rng + ?Sized> (&mut self, req: &Request, rng: &mut R) -> f64 {
+        match self.distributions.get_mut(&req.get_content()) {
+            Some(d) => d.mut_sample(rng),
+            None => self.default.mut_sample(rng)
+        }
+    }
+}
+
+// The work is the value of a numeric attribute of the request times 'scale', eg an object size in bytes on a link
+// whose work rate is in bytes per unit of time. Requests without the attribute take the value 'default'
+#[derive(Clone)]
+pub struct FromAttribute {
+    key: String,
+    scale: f64,
+    default: f64,
+}
+
+impl FromAttribute {
+    pub fn new(key: &str, scale: f64, default: f64) -> Self {
+        FromAttribute {
+            key: key.to_owned(),
+            scale,
+            default,
+        }
+    }
+}
+
+impl ServiceDistribution for FromAttribute {
+    fn sample_work<R: Rng + ?Sized> (&mut self, req: &Request, _rng: &mut R) -> f64 {
+        req.get_float(&self.key).unwrap_or(self.default) * self.scale
+    }
+}
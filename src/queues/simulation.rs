@@ -0,0 +1,262 @@
+use std::time::{Duration,Instant};
+use std::vec::Vec;
+
+use queues::queueing_network::{QNet,Transition,TransitionError,TransitionOutcome};
+
+pub enum StopCondition {
+    // Stops before processing the first event happening after the given simulated time
+    Time(f64),
+    // Stops after the given number of events
+    Events(usize),
+    // Stops once the given number of requests have arrived at the given queue (typically a sink)
+    Departures(usize, usize),
+    // Stops once the run has lasted for the given wall-clock time
+    WallClock(Duration),
+    // Stops as soon as the predicate holds, evaluated before each event
+    Predicate(Box<dyn Fn(&QNet) -> bool>),
+    // Stops as soon as any of the conditions is met
+    Any(Vec<StopCondition>),
+    // Only stops when no queue has a pending exit
+    Exhaustion,
+}
+
+#[derive(Debug)]
+pub enum StopReason {
+    ConditionMet,
+    NoMoreEvents,
+    Error(TransitionError),
+}
+
+#[derive(Debug)]
+pub struct RunReport {
+    pub stop_reason: StopReason,
+    pub start_time: f64,
+    pub end_time: f64,
+    pub events: usize,
+    // Number of requests that arrived at each queue during the run
+    pub arrivals: Vec<usize>,
+    // Sum of the lifetimes of the requests when they arrived at each queue
+    pub response_times: Vec<f64>,
+    // Integral of the load of each queue over the run
+    pub load_areas: Vec<f64>,
+    // Number of requests dropped because each queue was full
+    pub losses: Vec<usize>,
+    // Number of requests that gave up waiting in each queue, and that refused to join it
+    pub abandonments: Vec<usize>,
+    pub balks: Vec<usize>,
+    pub wall_time: Duration,
+    // The warm-up period (see QNet::set_warmup) was not over when the run stopped: the counts of the run include the
+    // transient, and the logs held by the queues are discarded when the queues are dropped
+    pub warmup_pending: bool,
+}
+
+impl RunReport {
+    pub fn is_error(&self) -> bool {
+        matches!(self.stop_reason, StopReason::Error(_)) || self.warmup_pending
+    }
+
+    pub fn duration(&self) -> f64 {
+        self.end_time - self.start_time
+    }
+
+    pub fn throughput(&self, queue: usize) -> f64 {
+        self.arrivals.get(queue).map_or(0., |&a| a as f64 / self.duration())
+    }
+
+    pub fn mean_response_time(&self, queue: usize) -> f64 {
+        match self.arrivals.get(queue) {
+            Some(&a) if a > 0 => self.response_times[queue] / a as f64,
+            _ => 0.
+        }
+    }
+
+    pub fn mean_load(&self, queue: usize) -> f64 {
+        self.load_areas.get(queue).map_or(0., |&area| area / self.duration())
+    }
+
+    // Fraction of the requests routed to 'queue' that were dropped
+    pub fn loss_probability(&self, queue: usize) -> f64 {
+        match (self.arrivals.get(queue), self.losses.get(queue)) {
+            (Some(&a), Some(&l)) if a + l > 0 => l as f64 / (a + l) as f64,
+            _ => 0.
+        }
+    }
+
+    // Restarts the counts from 'time', when the warm-up period ends during the run
+    fn restart(&mut self, time: f64) {
+        self.start_time = time;
+        for q in 0..self.arrivals.len() {
+            self.arrivals[q] = 0;
+            self.response_times[q] = 0.;
+            self.load_areas[q] = 0.;
+            self.losses[q] = 0;
+            self.abandonments[q] = 0;
+            self.balks[q] = 0;
+        }
+    }
+
+    fn resize(&mut self, number_of_queues: usize) {
+        if number_of_queues > self.arrivals.len() {
+            self.arrivals.resize(number_of_queues, 0);
+            self.response_times.resize(number_of_queues, 0.);
+            self.load_areas.resize(number_of_queues, 0.);
+            self.losses.resize(number_of_queues, 0);
+            self.abandonments.resize(number_of_queues, 0);
+            self.balks.resize(number_of_queues, 0);
+        }
+    }
+}
+
+impl StopCondition {
+    fn is_met(&self, qn: &QNet, next_time: f64, report: &RunReport, start: &Instant) -> bool {
+        match *self {
+            StopCondition::Time(horizon) => next_time > horizon,
+            StopCondition::Events(n) => report.events >= n,
+            StopCondition::Departures(sink, n) => report.arrivals.get(sink).is_some_and(|&a| a >= n),
+            StopCondition::WallClock(budget) => start.elapsed() >= budget,
+            StopCondition::Predicate(ref f) => f(qn),
+            StopCondition::Any(ref conditions) => conditions.iter().any(|c| c.is_met(qn, next_time, report, start)),
+            StopCondition::Exhaustion => false,
+        }
+    }
+
+    // Earliest simulated time at which the condition stops the run, if it has one
+    fn horizon(&self) -> Option<f64> {
+        match *self {
+            StopCondition::Time(horizon) => Some(horizon),
+            StopCondition::Any(ref conditions) => conditions.iter().filter_map(|c| c.horizon()).fold(None, |h, c| Some(h.map_or(c, |h: f64| h.min(c)))),
+            _ => None,
+        }
+    }
+}
+
+// Anything that is driven one transition at a time on top of a QNet
+pub trait Simulation {
+    fn make_transition (&mut self) -> Result<Transition,TransitionError>;
+    fn get_qnet (&self) -> &QNet;
+    fn get_qnet_mut (&mut self) -> &mut QNet;
+
+    fn run_until (&mut self, stop: StopCondition) -> RunReport {
+        let start = Instant::now();
+        let mut report = RunReport {
+            stop_reason: StopReason::ConditionMet,
+            start_time: self.get_qnet().get_time(),
+            end_time: self.get_qnet().get_time(),
+            events: 0,
+            arrivals: Vec::new(),
+            response_times: Vec::new(),
+            load_areas: Vec::new(),
+            losses: Vec::new(),
+            abandonments: Vec::new(),
+            balks: Vec::new(),
+            wall_time: Duration::from_secs(0),
+            warmup_pending: false,
+        };
+        report.resize(self.get_qnet().number_of_queues);
+
+        // Loads only change at the origin and destination of a transition, so the areas
+        // are only brought up to date for those queues
+        let mut loads: Vec<f64> = (0..self.get_qnet().number_of_queues).map(|q| self.get_qnet().get_queue(q).read_load() as f64).collect();
+        let mut last_changes = vec![report.start_time; loads.len()];
+        let mut warming_up = self.get_qnet().is_warming_up();
+
+        loop {
+            let next_time = match self.get_qnet_mut().next_event_time() {
+                Some(t) => t,
+                None => {
+                    report.stop_reason = StopReason::NoMoreEvents;
+                    break;
+                }
+            };
+            if stop.is_met(self.get_qnet(), next_time, &report, &start) {
+                // The run covers the time until the horizon, even though nothing happens after the last event
+                if let Some(horizon) = stop.horizon().filter(|&h| next_time > h && h > report.end_time) {
+                    self.get_qnet_mut().advance_time(horizon);
+                    report.end_time = horizon;
+                }
+                break;
+            }
+
+            match self.make_transition() {
+                Ok(trans) => {
+                    // The counts start over at the end of the warm-up, or when it is detected if it ended before
+                    // the previous event. The transition that ended it is counted
+                    if warming_up && !self.get_qnet().is_warming_up() {
+                        warming_up = false;
+                        let restart = self.get_qnet().get_warmup_end().map_or(trans.time, |end| end.max(report.end_time));
+                        report.restart(restart);
+                        last_changes.iter_mut().for_each(|t| *t = restart);
+                    }
+                    report.events += 1;
+                    report.end_time = trans.time;
+
+                    let number_of_queues = self.get_qnet().number_of_queues;
+                    report.resize(number_of_queues);
+                    if loads.len() < number_of_queues {
+                        loads.resize(number_of_queues, 0.);
+                        last_changes.resize(number_of_queues, trans.time);
+                    }
+
+                    for &q in [trans.origin, trans.destination].iter() {
+                        if q < number_of_queues {
+                            report.load_areas[q] += loads[q] * (trans.time - last_changes[q]);
+                            loads[q] = self.get_qnet().get_queue(q).read_load() as f64;
+                            last_changes[q] = trans.time;
+                        }
+                    }
+                    let arrived = match trans.outcome {
+                        TransitionOutcome::Arrived => true,
+                        TransitionOutcome::Dropped(full) => {
+                            report.losses[full] += 1;
+                            // Dropped requests only arrive somewhere if there is a loss sink
+                            trans.destination != full
+                        },
+                        TransitionOutcome::Blocked => false,
+                        // Same for the requests that give up, with the abandonment sink
+                        TransitionOutcome::Abandoned(queue) => {
+                            report.abandonments[queue] += 1;
+                            trans.destination != queue
+                        },
+                        TransitionOutcome::Balked(queue) => {
+                            report.balks[queue] += 1;
+                            trans.destination != queue
+                        },
+                    };
+                    if arrived && trans.destination < number_of_queues {
+                        report.arrivals[trans.destination] += 1;
+                        report.response_times[trans.destination] += trans.lifetime;
+                    }
+                },
+                Err(TransitionError::NoExitFound(_)) => {
+                    report.stop_reason = StopReason::NoMoreEvents;
+                    break;
+                },
+                Err(e) => {
+                    report.stop_reason = StopReason::Error(e);
+                    break;
+                }
+            }
+        }
+
+        for q in 0..loads.len() {
+            report.load_areas[q] += loads[q] * (report.end_time - last_changes[q]);
+        }
+        report.wall_time = start.elapsed();
+        report.warmup_pending = self.get_qnet().is_warming_up();
+        report
+    }
+}
+
+impl Simulation for QNet {
+    fn make_transition (&mut self) -> Result<Transition,TransitionError> {
+        QNet::make_transition(self)
+    }
+
+    fn get_qnet (&self) -> &QNet {
+        self
+    }
+
+    fn get_qnet_mut (&mut self) -> &mut QNet {
+        self
+    }
+}
@@ -0,0 +1,35 @@
+use queues::Queue;
+use queues::request::Request;
+
+// Requests routed to a sink leave the network: they are only counted
+#[derive(Default)]
+pub struct Sink {
+    departures: usize,
+}
+
+impl Sink {
+    pub fn new() -> Self
+    {
+        Sink { departures: 0 }
+    }
+
+    pub fn get_departures(&self) -> usize
+    {
+        self.departures
+    }
+}
+
+impl Queue for Sink {
+    fn arrival (&mut self, _req: Request)
+    {
+        self.departures += 1;
+    }
+
+    fn update_time (&mut self, _time: f64) {}
+
+    fn read_next_exit (&self) -> Option<(f64, &Request)> { None }
+
+    fn pop_next_exit (&mut self) -> Option<(f64, Request)> { None }
+
+    fn read_load (&self) -> usize { 0 }
+}
@@ -0,0 +1,344 @@
+use std::collections::{HashMap,VecDeque};
+
+use queues::request::Request;
+use queues::{Queue,Process};
+use helpers::float_binaryheap::FloatBinaryHeap;
+
+use queues::service::ServiceDistribution;
+use helpers::rng::{SimRng,rng_from_entropy,rng_from_seed};
+
+#[derive(Debug,Clone,Copy,PartialEq)]
+pub enum SizePolicy {
+    // Shortest Remaining Processing Time: preemptive, by remaining work
+    Srpt,
+    // Shortest Job First: non-preemptive, by work
+    Sjf,
+    // Fair Sojourn Protocol: preemptive, by order of completion in a virtual PS server of the same speed
+    Fsp,
+}
+
+// Single server serving one process at a time, the one with the smallest key given the policy
+pub struct MG1SizeBased<T> where T: ServiceDistribution {
+    time: f64,
+    work_rate: f64,
+    policy: SizePolicy,
+    // Keys of the waiting processes, by ticket. The work of a process is what remains of it
+    waiting: FloatBinaryHeap<usize>,
+    processes: HashMap<usize, Process>,
+    next_ticket: usize,
+    // Ticket and key of the process in service, and time at which its service (re)started
+    serving: Option<(usize, f64, f64)>,
+    // Virtual PS server of FSP: work targets of its processes, work applied to each of them, and time of the last update
+    virtual_targets: FloatBinaryHeap<()>,
+    virtual_work: f64,
+    virtual_time: f64,
+    distribution: T,
+    rng: SimRng,
+    capacity: Option<usize>,
+    // Blocked requests (see Queue::block_next_exit)
+    held: VecDeque<Request>,
+}
+
+impl<T> MG1SizeBased<T> where T: ServiceDistribution {
+    pub fn new(work_rate: f64, policy: SizePolicy, distribution: T) -> Self {
+        MG1SizeBased {
+            time: 0.,
+            work_rate,
+            policy,
+            waiting: FloatBinaryHeap::new(),
+            processes: HashMap::new(),
+            next_ticket: 0,
+            serving: None,
+            virtual_targets: FloatBinaryHeap::new(),
+            virtual_work: 0.,
+            virtual_time: 0.,
+            distribution,
+            rng: rng_from_entropy(),
+            capacity: None,
+            held: VecDeque::new(),
+        }
+    }
+
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = Some(capacity);
+    }
+
+    //Work left to the process in service
+    fn remaining(&self, ticket: usize, started: f64) -> f64 {
+        (self.processes[&ticket].work - (self.time - started) * self.work_rate).max(0.)
+    }
+
+    //Completes the virtual processes until 'time'
+    fn advance_virtual(&mut self, time: f64) {
+        while let Some((target, _)) = self.virtual_targets.peek() {
+            let n = self.virtual_targets.len() as f64;
+            let completion = self.virtual_time + (target - self.virtual_work) * n / self.work_rate;
+            if completion > time {
+                self.virtual_work += (time - self.virtual_time) * self.work_rate / n;
+                break;
+            }
+            self.virtual_work = target;
+            self.virtual_time = completion;
+            self.virtual_targets.pop();
+        }
+        self.virtual_time = time;
+    }
+
+    //Puts the process in service back with the waiting ones
+    fn preempt(&mut self) {
+        let (ticket, key, started) = self.serving.take().unwrap();
+        let remaining = self.remaining(ticket, started);
+        self.processes.get_mut(&ticket).unwrap().work = remaining;
+        self.waiting.push(if self.policy == SizePolicy::Srpt { remaining } else { key }, ticket);
+    }
+
+    //Starts serving the waiting process with the smallest key, if the server is idle. The waiting time
+    //of a request ends with its first service
+    fn dispatch(&mut self) {
+        if self.serving.is_none() && self.held.is_empty() {
+            self.serving = self.waiting.pop().map(|(key, ticket)| (ticket, key, self.time));
+            if let Some((ticket, _, _)) = self.serving {
+                let req = &mut self.processes.get_mut(&ticket).unwrap().req;
+                if req.get_service_start().is_none() {
+                    req.set_service_start(Some(self.time));
+                }
+            }
+        }
+    }
+
+    fn next_exit(&self) -> Option<(f64, usize)> {
+        self.serving.map(|(ticket, _, started)| (started + self.processes[&ticket].work / self.work_rate, ticket))
+    }
+
+    fn pop(&mut self) -> Option<(f64, Request)> {
+        let (t, ticket) = self.next_exit()?;
+        self.serving = None;
+        self.time = t;
+        Some((t, self.processes.remove(&ticket).unwrap().req))
+    }
+}
+
+impl<T> Queue for MG1SizeBased<T> where T: ServiceDistribution + Send {
+    fn arrival (&mut self, req: Request) {
+        let work = self.distribution.sample_work(&req, &mut self.rng);
+        let key = match self.policy {
+            SizePolicy::Srpt | SizePolicy::Sjf => work,
+            SizePolicy::Fsp => {
+                self.advance_virtual(self.time);
+                self.virtual_targets.push(self.virtual_work + work, ());
+                self.virtual_work + work
+            },
+        };
+        let ticket = self.next_ticket;
+        self.next_ticket += 1;
+        self.processes.insert(ticket, Process {
+            req,
+            work
+        });
+
+        if let Some((serving, serving_key, started)) = self.serving {
+            let current = match self.policy {
+                SizePolicy::Srpt => self.remaining(serving, started),
+                SizePolicy::Sjf => f64::NEG_INFINITY,
+                SizePolicy::Fsp => serving_key,
+            };
+            if key < current {
+                self.preempt();
+            }
+        }
+        self.waiting.push(key, ticket);
+        self.dispatch();
+    }
+
+    fn update_time (&mut self, time: f64) {
+        self.time = time;
+    }
+
+    fn read_next_exit (&self) -> Option<(f64, &Request)> {
+        self.next_exit().map(|(t, ticket)| (t, &self.processes[&ticket].req))
+    }
+
+    fn pop_next_exit (&mut self) -> Option<(f64, Request)> {
+        let ret = self.pop();
+        self.dispatch();
+        ret
+    }
+
+    fn read_load (&self) -> usize {
+        self.processes.len() + self.held.len()
+    }
+
+    fn read_capacity (&self) -> Option<usize> {
+        self.capacity
+    }
+
+    fn block_next_exit (&mut self, _stop_dispatch: bool) -> bool {
+        match self.pop() {
+            Some((_, req)) => {
+                self.held.push_back(req);
+                true
+            },
+            None => false
+        }
+    }
+
+    fn unblock_exit (&mut self) -> Option<Request> {
+        let ret = self.held.pop_front();
+        self.dispatch();
+        ret
+    }
+
+    fn reseed (&mut self, seed: u64) {
+        self.rng = rng_from_seed(seed);
+    }
+}
+
+// Least Attained Service (or foreground-background): the processes that received the least work share the server
+pub struct MG1LAS<T> where T: ServiceDistribution {
+    time: f64,
+    work_rate: f64,
+    // Processes with the same attained work, with their total work by ticket. Groups are sorted by decreasing
+    // attained work: the last one is in service, and merges with the previous one once it attained as much
+    groups: Vec<(f64, FloatBinaryHeap<usize>)>,
+    processes: HashMap<usize, Process>,
+    next_ticket: usize,
+    distribution: T,
+    rng: SimRng,
+    capacity: Option<usize>,
+    // Blocked requests (see Queue::block_next_exit)
+    held: VecDeque<Request>,
+}
+
+impl<T> MG1LAS<T> where T: ServiceDistribution {
+    pub fn new(work_rate: f64, distribution: T) -> Self {
+        MG1LAS {
+            time: 0.,
+            work_rate,
+            groups: Vec::new(),
+            processes: HashMap::new(),
+            next_ticket: 0,
+            distribution,
+            rng: rng_from_entropy(),
+            capacity: None,
+            held: VecDeque::new(),
+        }
+    }
+
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = Some(capacity);
+    }
+
+    //Time and ticket of the next completion, merging the groups that catch up with the next ones before it
+    fn next_exit(&self) -> Option<(f64, usize)> {
+        if !self.held.is_empty() {
+            return None;
+        }
+        let (mut attained, ref served) = *self.groups.last()?;
+        let (mut work, mut ticket) = served.peek().map(|(w, &k)| (w, k)).unwrap();
+        let mut n = served.len() as f64;
+        let mut time = self.time;
+        for &(next_attained, ref next) in self.groups.iter().rev().skip(1) {
+            let merge = time + (next_attained - attained) * n / self.work_rate;
+            if time + (work - attained) * n / self.work_rate <= merge {
+                break;
+            }
+            time = merge;
+            attained = next_attained;
+            n += next.len() as f64;
+            if let Some((w, &k)) = next.peek() {
+                if w < work {
+                    work = w;
+                    ticket = k;
+                }
+            }
+        }
+        Some((time + (work - attained) * n / self.work_rate, ticket))
+    }
+
+    //Serves the last group until 'time'
+    fn advance_time(&mut self, time: f64) {
+        while self.held.is_empty() && !self.groups.is_empty() {
+            let len = self.groups.len();
+            let n = self.groups[len - 1].1.len() as f64;
+            if len > 1 {
+                let merge = self.time + (self.groups[len - 2].0 - self.groups[len - 1].0) * n / self.work_rate;
+                if merge <= time {
+                    let (_, mut served) = self.groups.pop().unwrap();
+                    while let Some((w, k)) = served.pop() {
+                        self.groups[len - 2].1.push(w, k);
+                    }
+                    self.time = merge;
+                    continue;
+                }
+            }
+            self.groups[len - 1].0 += (time - self.time) * self.work_rate / n;
+            break;
+        }
+        self.time = time;
+    }
+
+    fn pop(&mut self) -> Option<(f64, Request)> {
+        let (t, _) = self.next_exit()?;
+        self.advance_time(t);
+        let (_, ticket) = self.groups.last_mut().unwrap().1.pop().unwrap();
+        if self.groups.last().unwrap().1.is_empty() {
+            self.groups.pop();
+        }
+        Some((t, self.processes.remove(&ticket).unwrap().req))
+    }
+}
+
+impl<T> Queue for MG1LAS<T> where T: ServiceDistribution + Send {
+    fn arrival (&mut self, req: Request) {
+        let work = self.distribution.sample_work(&req, &mut self.rng);
+        let ticket = self.next_ticket;
+        self.next_ticket += 1;
+        self.processes.insert(ticket, Process {
+            req,
+            work
+        });
+        // A new process has attained nothing: it is served alone, or with those that have not either
+        if self.groups.last().is_none_or(|&(attained, _)| attained > 0.) {
+            self.groups.push((0., FloatBinaryHeap::new()));
+        }
+        self.groups.last_mut().unwrap().1.push(work, ticket);
+    }
+
+    fn update_time (&mut self, time: f64) {
+        self.advance_time(time);
+    }
+
+    fn read_next_exit (&self) -> Option<(f64, &Request)> {
+        self.next_exit().map(|(t, ticket)| (t, &self.processes[&ticket].req))
+    }
+
+    fn pop_next_exit (&mut self) -> Option<(f64, Request)> {
+        self.pop()
+    }
+
+    fn read_load (&self) -> usize {
+        self.processes.len() + self.held.len()
+    }
+
+    fn read_capacity (&self) -> Option<usize> {
+        self.capacity
+    }
+
+    fn block_next_exit (&mut self, _stop_dispatch: bool) -> bool {
+        match self.pop() {
+            Some((_, req)) => {
+                self.held.push_back(req);
+                true
+            },
+            None => false
+        }
+    }
+
+    fn unblock_exit (&mut self) -> Option<Request> {
+        self.held.pop_front()
+    }
+
+    fn reseed (&mut self, seed: u64) {
+        self.rng = rng_from_seed(seed);
+    }
+}
@@ -0,0 +1,305 @@
+use std::fmt;
+use std::vec::Vec;
+
+use helpers::histogram::LogHistogram;
+use queues::Queue;
+use queues::request::Request;
+
+// Sojourn and waiting times are binned from 1us, with a 1% relative precision on their quantiles
+const HISTOGRAM_MIN: f64 = 1e-6;
+const HISTOGRAM_GROWTH: f64 = 1.01;
+
+// Time-integrated metrics of a queue since 'start'
+struct QueueStatistics {
+    start: f64,
+    last_change: f64,
+    load: usize,
+    busy: usize,
+    load_area: f64,
+    busy_area: f64,
+    arrivals: usize,
+    departures: usize,
+    abandonments: usize,
+    max_load: usize,
+    idle_since: Option<f64>,
+    idle_periods: usize,
+    idle_time: f64,
+    sojourn_times: LogHistogram,
+    waiting_times: LogHistogram,
+}
+
+impl QueueStatistics {
+    fn new(start: f64, load: usize, busy: usize) -> Self {
+        QueueStatistics {
+            start,
+            last_change: start,
+            load,
+            busy,
+            load_area: 0.,
+            busy_area: 0.,
+            arrivals: 0,
+            departures: 0,
+            abandonments: 0,
+            max_load: load,
+            idle_since: if load == 0 { Some(start) } else { None },
+            idle_periods: 0,
+            idle_time: 0.,
+            sojourn_times: LogHistogram::new(HISTOGRAM_MIN, HISTOGRAM_GROWTH),
+            waiting_times: LogHistogram::new(HISTOGRAM_MIN, HISTOGRAM_GROWTH),
+        }
+    }
+
+    // Integrates the previous state until 'time', then switches to the new one
+    fn update(&mut self, time: f64, load: usize, busy: usize) {
+        self.load_area += self.load as f64 * (time - self.last_change);
+        self.busy_area += self.busy as f64 * (time - self.last_change);
+        self.last_change = time;
+
+        if load == 0 && self.idle_since.is_none() {
+            self.idle_since = Some(time);
+        }
+        else if load > 0 {
+            if let Some(since) = self.idle_since.take() {
+                self.idle_periods += 1;
+                self.idle_time += time - since;
+            }
+        }
+        self.load = load;
+        self.busy = busy;
+        self.max_load = self.max_load.max(load);
+    }
+
+    // 'changes' are those of the busy servers since the last change, on their own (see Queue::read_busy_changes)
+    fn report(&self, now: f64, servers: Option<usize>, changes: &[(f64, usize)]) -> QueueReport {
+        let duration = now - self.start;
+        let elapsed = now - self.last_change;
+        let (mut busy_area, mut busy, mut last_change) = (self.busy_area, self.busy, self.last_change);
+        for &(t, b) in changes {
+            busy_area += busy as f64 * (t - last_change);
+            busy = b;
+            last_change = t;
+        }
+        let mean_busy = (busy_area + busy as f64 * (now - last_change)) / duration;
+        let current_idle = self.idle_since.map_or(0., |since| now - since);
+
+        QueueReport {
+            queue: 0,
+            name: String::new(),
+            duration,
+            arrivals: self.arrivals,
+            departures: self.departures,
+            abandonments: self.abandonments,
+            throughput: self.departures as f64 / duration,
+            mean_load: (self.load_area + self.load as f64 * elapsed) / duration,
+            max_load: self.max_load,
+            utilization: servers.map_or(mean_busy, |k| mean_busy / k as f64),
+            idle_periods: self.idle_periods,
+            idle_fraction: (self.idle_time + current_idle) / duration,
+            mean_idle_period: if self.idle_periods > 0 { self.idle_time / self.idle_periods as f64 } else { f64::NAN },
+            sojourn_time: TimeDistribution::from_histogram(&self.sojourn_times),
+            waiting_time: TimeDistribution::from_histogram(&self.waiting_times),
+        }
+    }
+}
+
+#[derive(Debug,Clone,Serialize)]
+pub struct TimeDistribution {
+    pub samples: usize,
+    pub mean: f64,
+    pub std_dev: f64,
+    pub p50: f64,
+    pub p90: f64,
+    pub p99: f64,
+    pub max: f64,
+}
+
+impl TimeDistribution {
+    fn from_histogram(h: &LogHistogram) -> Self {
+        TimeDistribution {
+            samples: h.count(),
+            mean: h.mean(),
+            std_dev: h.variance().sqrt(),
+            p50: h.quantile(0.5),
+            p90: h.quantile(0.9),
+            p99: h.quantile(0.99),
+            max: h.max(),
+        }
+    }
+}
+
+#[derive(Debug,Clone,Serialize)]
+pub struct QueueReport {
+    pub queue: usize,
+    pub name: String,
+    // Observation period (since the start of the collection, or the end of the warm-up)
+    pub duration: f64,
+    pub arrivals: usize,
+    // Served requests: those that gave up are only counted in 'abandonments'
+    pub departures: usize,
+    pub abandonments: usize,
+    pub throughput: f64,
+    // Time-averaged number of requests in the queue
+    pub mean_load: f64,
+    pub max_load: usize,
+    // Time-averaged fraction of busy servers (average number of busy servers for unbounded queues)
+    pub utilization: f64,
+    // Completed periods during which the queue was empty
+    pub idle_periods: usize,
+    pub idle_fraction: f64,
+    pub mean_idle_period: f64,
+    pub sojourn_time: TimeDistribution,
+    pub waiting_time: TimeDistribution,
+}
+
+#[derive(Debug,Clone,Serialize)]
+pub struct NetworkReport {
+    pub time: f64,
+    pub queues: Vec<QueueReport>,
+}
+
+impl fmt::Display for NetworkReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{:<12} {:>10} {:>10} {:>10} {:>10} {:>10} {:>8} {:>6} {:>10} {:>10} {:>10} {:>10} {:>10} {:>10}",
+                 "queue", "arrivals", "departures", "abandons", "throughput", "mean_load", "max_load", "util", "idle_frac",
+                 "sojourn", "sojourn_p99", "waiting", "waiting_p99", "idle_mean")?;
+        for q in &self.queues {
+            writeln!(f, "{:<12} {:>10} {:>10} {:>10} {:>10.4} {:>10.4} {:>8} {:>6.3} {:>10.4} {:>10.4} {:>10.4} {:>10.4} {:>10.4} {:>10.4}",
+                     q.name, q.arrivals, q.departures, q.abandonments, q.throughput, q.mean_load, q.max_load, q.utilization, q.idle_fraction,
+                     q.sojourn_time.mean, q.sojourn_time.p99, q.waiting_time.mean, q.waiting_time.p99, q.mean_idle_period)?;
+        }
+        Ok(())
+    }
+}
+
+// Wraps a queue to collect its statistics, without changing its behaviour.
+// Sojourn times are measured from the arrival of the request in the queue (its last log entry),
+// and waiting times until the service start set by the queue (zero for queues that serve immediately).
+// Requests that abandon the queue are only counted
+pub struct StatisticsCollector {
+    queue: Box<dyn Queue>,
+    time: f64,
+    stats: QueueStatistics,
+}
+
+impl StatisticsCollector {
+    pub fn new(queue: Box<dyn Queue>, time: f64) -> Self {
+        let stats = QueueStatistics::new(time, queue.read_load(), queue.read_busy_servers());
+        StatisticsCollector {
+            queue,
+            time,
+            stats,
+        }
+    }
+
+    fn update_statistics(&mut self) {
+        self.stats.update(self.time, self.queue.read_load(), self.queue.read_busy_servers());
+    }
+
+    fn record_departure(&mut self, time: f64, req: &Request) {
+        self.time = time;
+        self.stats.departures += 1;
+        // Requests that arrived before the start of the collection would bias the distributions
+        if let Some(arrival) = req.get_last_log_time().filter(|&a| a >= self.stats.start) {
+            self.stats.sojourn_times.add(time - arrival);
+            self.stats.waiting_times.add(req.get_service_start().map_or(0., |s| s - arrival));
+        }
+        self.update_statistics();
+    }
+}
+
+impl Queue for StatisticsCollector {
+    fn arrival (&mut self, mut req: Request) {
+        req.set_service_start(None);
+        self.queue.arrival(req);
+        self.stats.arrivals += 1;
+        self.update_statistics();
+    }
+
+    // The busy servers may have changed on their own since the previous update
+    fn update_time (&mut self, time: f64) {
+        let load = self.queue.read_load();
+        for (t, busy) in self.queue.read_busy_changes(time) {
+            self.stats.update(t, load, busy);
+        }
+        self.time = time;
+        self.queue.update_time(time);
+    }
+
+    fn read_next_exit (&self) -> Option<(f64,&Request)> {
+        self.queue.read_next_exit()
+    }
+
+    fn pop_next_exit (&mut self) -> Option<(f64,Request)> {
+        let abandons = self.queue.next_exit_abandons();
+        let exit = self.queue.pop_next_exit();
+        match exit {
+            Some((t, _)) if abandons => {
+                self.time = t;
+                self.stats.abandonments += 1;
+                self.update_statistics();
+            },
+            Some((t, ref req)) => self.record_departure(t, req),
+            None => ()
+        }
+        exit
+    }
+
+    fn next_exit_abandons (&self) -> bool {
+        self.queue.next_exit_abandons()
+    }
+
+    fn read_capacity (&self) -> Option<usize> {
+        self.queue.read_capacity()
+    }
+
+    // Blocked requests are still in the queue: they only depart once unblocked
+    fn block_next_exit (&mut self, stop_dispatch: bool) -> bool {
+        let blocked = self.queue.block_next_exit(stop_dispatch);
+        self.update_statistics();
+        blocked
+    }
+
+    fn unblock_exit (&mut self) -> Option<Request> {
+        let req = self.queue.unblock_exit();
+        if let Some(ref r) = req {
+            let time = self.time;
+            self.record_departure(time, r);
+        }
+        req
+    }
+
+    fn read_load (&self) -> usize {
+        self.queue.read_load()
+    }
+
+    fn read_busy_servers (&self) -> usize {
+        self.queue.read_busy_servers()
+    }
+
+    fn read_busy_changes (&self, until: f64) -> Vec<(f64, usize)> {
+        self.queue.read_busy_changes(until)
+    }
+
+    fn read_servers (&self) -> Option<usize> {
+        self.queue.read_servers()
+    }
+
+    fn read_statistics (&self, now: f64) -> Option<QueueReport> {
+        Some(self.stats.report(now, self.queue.read_servers(), &self.queue.read_busy_changes(now)))
+    }
+
+    fn reseed (&mut self, seed: u64) {
+        self.queue.reseed(seed);
+    }
+
+    fn begin_warmup (&mut self) {
+        self.queue.begin_warmup();
+    }
+
+    // The warm-up end may be detected after the fact: statistics restart from 'end' if the queue
+    // has not changed since then, from its last change otherwise
+    fn end_warmup (&mut self, end: f64) {
+        self.queue.end_warmup(end);
+        self.stats = QueueStatistics::new(self.time.max(end), self.queue.read_load(), self.queue.read_busy_servers());
+    }
+}
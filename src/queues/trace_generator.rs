@@ -3,17 +3,18 @@ use std::io::BufRead;
 use std::fs::File;
 
 use helpers::float_binaryheap::FloatBinaryHeap;
-use queues::request::Request;
+use queues::request::{attributes,Request};
 use queues::Queue;
 
+// Replays a trace of "time content [size]" lines; the optional size is set as the SIZE attribute of the request
 pub struct TraceGenerator {
-    requests: FloatBinaryHeap<usize>,
+    requests: FloatBinaryHeap<(usize, Option<f64>)>,
     next_exit: f64,
     next_request: Option<Request>,
 }
 
 impl TraceGenerator {
-    pub fn new(csv_filename: &'static str, csv_delimiter: char) -> Self {
+    pub fn new(csv_filename: &str, csv_delimiter: char) -> Self {
         let mut ret = TraceGenerator {
             requests: FloatBinaryHeap::new(),
             next_exit: 0.,
@@ -28,15 +29,9 @@ impl TraceGenerator {
             let mut s = l.split(csv_delimiter);
             if let Some(s1) = s.next() {
                 if let Ok(t) = s1.parse() {
-                    if let Some(s2) = s.next() {
-                        if let Ok(work) = s2.parse() {
-                            ret.requests.push(t, work);
-                        } else {
-                            ret.requests.push(t, 0);
-                        }
-                    } else {
-                        ret.requests.push(t, 0);
-                    }
+                    let content = s.next().and_then(|s2| s2.parse().ok()).unwrap_or(0);
+                    let size = s.next().and_then(|s3| s3.parse().ok());
+                    ret.requests.push(t, (content, size));
                 }
             }
         }
@@ -46,9 +41,13 @@ impl TraceGenerator {
     }
 
     fn generate_next_exit(&mut self) {
-        if let Some((t, work)) = self.requests.pop() {
+        if let Some((t, (content, size))) = self.requests.pop() {
             self.next_exit = t;
-            self.next_request = Some(Request::new(work));
+            let mut req = Request::new(content);
+            if let Some(size) = size {
+                req.set_attribute(attributes::SIZE, size);
+            }
+            self.next_request = Some(req);
         } else {
             self.next_request = None;
         }
@@ -0,0 +1,71 @@
+use std::vec::Vec;
+
+use helpers::steady_state::{mser5_truncation,welch_truncation};
+
+pub enum Warmup {
+    // Warm-up ends at the given simulated time
+    Time(f64),
+    // Warm-up ends after the given number of arrivals at the given sink
+    Departures(usize, usize),
+    // Warm-up end is detected with MSER-5 on the response times observed at 'sink'.
+    // Detection is attempted once 'min_observations' have been collected, then every time their number doubles
+    Mser5 { sink: usize, min_observations: usize },
+    // Warm-up end is detected with Welch's moving-average procedure on the response times observed at 'sink'
+    Welch { sink: usize, window: usize, tolerance: f64, min_observations: usize },
+}
+
+pub struct WarmupTracker {
+    warmup: Warmup,
+    count: usize,
+    // (time, response time) of the arrivals at the sink, for automatic detection
+    observations: Vec<(f64, f64)>,
+    next_check: usize,
+}
+
+impl WarmupTracker {
+    pub fn new(warmup: Warmup) -> Self {
+        let next_check = match warmup {
+            Warmup::Mser5 { min_observations, .. } |
+            Warmup::Welch { min_observations, .. } => min_observations.max(1),
+            _ => 0
+        };
+        WarmupTracker {
+            warmup,
+            count: 0,
+            observations: Vec::new(),
+            next_check,
+        }
+    }
+
+    // Records the arrival of a request at 'queue' with the given lifetime.
+    // Returns the time at which the warm-up ended, once it is over
+    pub fn observe(&mut self, time: f64, queue: usize, lifetime: f64) -> Option<f64> {
+        match self.warmup {
+            Warmup::Time(end) => if time >= end { Some(end) } else { None },
+            Warmup::Departures(sink, n) => {
+                if queue == sink {
+                    self.count += 1;
+                }
+                // The warm-up covers the first n arrivals: it ends with the next one
+                if self.count > n { Some(time) } else { None }
+            },
+            Warmup::Mser5 { sink, .. } | Warmup::Welch { sink, .. } => {
+                if queue != sink {
+                    return None;
+                }
+                self.observations.push((time, lifetime));
+                if self.observations.len() < self.next_check {
+                    return None;
+                }
+                self.next_check *= 2;
+
+                let series: Vec<f64> = self.observations.iter().map(|&(_,x)| x).collect();
+                let truncation = match self.warmup {
+                    Warmup::Welch { window, tolerance, .. } => welch_truncation(&series, window, tolerance),
+                    _ => mser5_truncation(&series),
+                };
+                truncation.map(|d| self.observations[d].0)
+            }
+        }
+    }
+}
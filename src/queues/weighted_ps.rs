@@ -0,0 +1,335 @@
+use std::collections::{HashMap,VecDeque};
+
+use queues::request::Request;
+use queues::{Queue,Process};
+use helpers::float_binaryheap::FloatBinaryHeap;
+
+use queues::service::ServiceDistribution;
+use helpers::rng::{SimRng,rng_from_entropy,rng_from_seed};
+
+// Classes above the last weight are served as the last class (see NetworkConfig::build, which rejects them)
+fn class_index(weights: &[f64], class: usize) -> usize {
+    class.min(weights.len() - 1)
+}
+
+fn weight(weights: &[f64], class: usize) -> f64 {
+    weights[class_index(weights, class)]
+}
+
+// Discriminatory processor sharing: every process of class c gets a share weights[c] / (sum of the weights of
+// all processes) of the server. Each unit of virtual time gives weights[c] work to every process of class c,
+// so that processes leave in the order of their virtual finish times, as in MG1PS
+pub struct MG1DPS<T> where T: ServiceDistribution {
+    time: f64,
+    work_rate: f64,
+    weights: Vec<f64>,
+    // Virtual finish times of the processes, by ticket
+    processes: FloatBinaryHeap<usize>,
+    active: HashMap<usize, Process>,
+    next_ticket: usize,
+    total_weight: f64,
+    virtual_time: f64,
+    distribution: T,
+    rng: SimRng,
+    capacity: Option<usize>,
+    // Blocked processes (see Queue::block_next_exit), and whether they stop the dispatch
+    held: VecDeque<(Request, bool)>,
+    stopped_dispatch: usize,
+    // Processes arrived while the dispatch is stopped
+    waiting: VecDeque<Process>,
+}
+
+impl<T> MG1DPS<T> where T: ServiceDistribution {
+    pub fn new(work_rate: f64, weights: Vec<f64>, distribution: T) -> Self {
+        assert!(!weights.is_empty(), "There must be at least one weight");
+        MG1DPS {
+            time: 0.,
+            work_rate,
+            weights,
+            processes: FloatBinaryHeap::new(),
+            active: HashMap::new(),
+            next_ticket: 0,
+            total_weight: 0.,
+            virtual_time: 0.,
+            distribution,
+            rng: rng_from_entropy(),
+            capacity: None,
+            held: VecDeque::new(),
+            stopped_dispatch: 0,
+            waiting: VecDeque::new(),
+        }
+    }
+
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = Some(capacity);
+    }
+
+    fn next_exit(&self) -> Option<(f64, usize)> {
+        self.processes.peek().map(|(v, &k)| (self.time + (v - self.virtual_time) * self.total_weight / self.work_rate, k))
+    }
+
+    fn pop(&mut self) -> Option<(f64, Request)> {
+        let (t, k) = self.next_exit()?;
+        self.processes.pop();
+        let process = self.active.remove(&k).unwrap();
+        self.total_weight -= weight(&self.weights, process.req.get_class());
+        self.reset_weight();
+        Some((t, process.req))
+    }
+
+    //Clears the rounding errors once the server is empty
+    fn reset_weight(&mut self) {
+        if self.active.is_empty() && self.held.is_empty() {
+            self.total_weight = 0.;
+        }
+    }
+
+    fn admit(&mut self, process: Process) {
+        let w = weight(&self.weights, process.req.get_class());
+        let ticket = self.next_ticket;
+        self.next_ticket += 1;
+        self.processes.push(self.virtual_time + process.work / w, ticket);
+        self.total_weight += w;
+        self.active.insert(ticket, process);
+    }
+
+    fn advance_time(&mut self, time: f64) {
+        if !self.active.is_empty() {
+            self.virtual_time += (time - self.time) * self.work_rate / self.total_weight;
+        }
+        self.time = time;
+    }
+}
+
+impl<T> Queue for MG1DPS<T> where T: ServiceDistribution + Send {
+    fn arrival (&mut self, req: Request) {
+        let work = self.distribution.sample_work(&req, &mut self.rng);
+        let process = Process {
+            req,
+            work
+        };
+        if self.stopped_dispatch > 0 {
+            self.waiting.push_back(process);
+        }
+        else {
+            self.admit(process);
+        }
+    }
+
+    fn update_time (&mut self, time: f64) {
+        self.advance_time(time);
+    }
+
+    fn read_next_exit (&self) -> Option<(f64, &Request)> {
+        self.next_exit().map(|(t, k)| (t, &self.active[&k].req))
+    }
+
+    fn pop_next_exit (&mut self) -> Option<(f64, Request)> {
+        self.pop()
+    }
+
+    fn read_load (&self) -> usize {
+        self.active.len() + self.held.len() + self.waiting.len()
+    }
+
+    fn read_capacity (&self) -> Option<usize> {
+        self.capacity
+    }
+
+    fn block_next_exit (&mut self, stop_dispatch: bool) -> bool {
+        match self.pop() {
+            Some((_, req)) => {
+                self.total_weight += weight(&self.weights, req.get_class());
+                self.held.push_back((req, stop_dispatch));
+                if stop_dispatch {
+                    self.stopped_dispatch += 1;
+                }
+                true
+            },
+            None => false
+        }
+    }
+
+    fn unblock_exit (&mut self) -> Option<Request> {
+        let (req, stop_dispatch) = self.held.pop_front()?;
+        self.total_weight -= weight(&self.weights, req.get_class());
+        self.reset_weight();
+        if stop_dispatch {
+            self.stopped_dispatch -= 1;
+        }
+        if self.stopped_dispatch == 0 {
+            while let Some(process) = self.waiting.pop_front() {
+                self.admit(process);
+            }
+        }
+        Some(req)
+    }
+
+    fn reseed (&mut self, seed: u64) {
+        self.rng = rng_from_seed(seed);
+    }
+}
+
+// Generalized processor sharing: every class c with waiting processes gets a share weights[c] / (sum of the
+// weights of these classes) of the server, whatever its number of processes, and serves them in arrival order.
+// The process at the head of class c gets weights[c] work per unit of virtual time
+pub struct MG1GPS<T> where T: ServiceDistribution {
+    time: f64,
+    work_rate: f64,
+    weights: Vec<f64>,
+    // Processes of each class, in arrival order
+    classes: Vec<VecDeque<Process>>,
+    // Virtual finish times of the processes at the head of their class, by class
+    heads: FloatBinaryHeap<usize>,
+    load: usize,
+    backlogged_weight: f64,
+    virtual_time: f64,
+    distribution: T,
+    rng: SimRng,
+    capacity: Option<usize>,
+    // Blocked processes (see Queue::block_next_exit), and whether they stop the dispatch.
+    // The next processes of their class are served meanwhile
+    held: VecDeque<(Request, bool)>,
+    stopped_dispatch: usize,
+    // Processes arrived while the dispatch is stopped
+    waiting: VecDeque<Process>,
+}
+
+impl<T> MG1GPS<T> where T: ServiceDistribution {
+    pub fn new(work_rate: f64, weights: Vec<f64>, distribution: T) -> Self {
+        assert!(!weights.is_empty(), "There must be at least one weight");
+        MG1GPS {
+            time: 0.,
+            work_rate,
+            classes: weights.iter().map(|_| VecDeque::new()).collect(),
+            weights,
+            heads: FloatBinaryHeap::new(),
+            load: 0,
+            backlogged_weight: 0.,
+            virtual_time: 0.,
+            distribution,
+            rng: rng_from_entropy(),
+            capacity: None,
+            held: VecDeque::new(),
+            stopped_dispatch: 0,
+            waiting: VecDeque::new(),
+        }
+    }
+
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = Some(capacity);
+    }
+
+    fn next_exit(&self) -> Option<(f64, usize)> {
+        self.heads.peek().map(|(v, &c)| (self.time + (v - self.virtual_time) * self.backlogged_weight / self.work_rate, c))
+    }
+
+    fn pop(&mut self) -> Option<(f64, Request)> {
+        let (t, _) = self.next_exit()?;
+        let (finish, class) = self.heads.pop().unwrap();
+        let process = self.classes[class].pop_front().unwrap();
+        self.load -= 1;
+        let w = self.weights[class];
+        // The next process of the class starts its service when this one ends, at virtual time 'finish'
+        match self.classes[class].front() {
+            Some(next) => self.heads.push(finish + next.work / w, class),
+            None => self.backlogged_weight -= w,
+        }
+        self.reset_weight();
+        Some((t, process.req))
+    }
+
+    //Clears the rounding errors once the server is empty
+    fn reset_weight(&mut self) {
+        if self.load == 0 && self.held.is_empty() {
+            self.backlogged_weight = 0.;
+        }
+    }
+
+    fn admit(&mut self, process: Process) {
+        let class = class_index(&self.weights, process.req.get_class());
+        let w = weight(&self.weights, class);
+        if self.classes[class].is_empty() {
+            self.heads.push(self.virtual_time + process.work / w, class);
+            self.backlogged_weight += w;
+        }
+        self.classes[class].push_back(process);
+        self.load += 1;
+    }
+
+    fn advance_time(&mut self, time: f64) {
+        if self.load > 0 {
+            self.virtual_time += (time - self.time) * self.work_rate / self.backlogged_weight;
+        }
+        self.time = time;
+    }
+}
+
+impl<T> Queue for MG1GPS<T> where T: ServiceDistribution + Send {
+    fn arrival (&mut self, req: Request) {
+        let work = self.distribution.sample_work(&req, &mut self.rng);
+        let process = Process {
+            req,
+            work
+        };
+        if self.stopped_dispatch > 0 {
+            self.waiting.push_back(process);
+        }
+        else {
+            self.admit(process);
+        }
+    }
+
+    fn update_time (&mut self, time: f64) {
+        self.advance_time(time);
+    }
+
+    fn read_next_exit (&self) -> Option<(f64, &Request)> {
+        self.next_exit().map(|(t, c)| (t, &self.classes[c].front().unwrap().req))
+    }
+
+    fn pop_next_exit (&mut self) -> Option<(f64, Request)> {
+        self.pop()
+    }
+
+    fn read_load (&self) -> usize {
+        self.load + self.held.len() + self.waiting.len()
+    }
+
+    fn read_capacity (&self) -> Option<usize> {
+        self.capacity
+    }
+
+    fn block_next_exit (&mut self, stop_dispatch: bool) -> bool {
+        match self.pop() {
+            Some((_, req)) => {
+                self.backlogged_weight += weight(&self.weights, req.get_class());
+                self.held.push_back((req, stop_dispatch));
+                if stop_dispatch {
+                    self.stopped_dispatch += 1;
+                }
+                true
+            },
+            None => false
+        }
+    }
+
+    fn unblock_exit (&mut self) -> Option<Request> {
+        let (req, stop_dispatch) = self.held.pop_front()?;
+        self.backlogged_weight -= weight(&self.weights, req.get_class());
+        self.reset_weight();
+        if stop_dispatch {
+            self.stopped_dispatch -= 1;
+        }
+        if self.stopped_dispatch == 0 {
+            while let Some(process) = self.waiting.pop_front() {
+                self.admit(process);
+            }
+        }
+        Some(req)
+    }
+
+    fn reseed (&mut self, seed: u64) {
+        self.rng = rng_from_seed(seed);
+    }
+}
@@ -10,14 +10,17 @@ use std::mem;
 use queues::Queue;
 use queues::request::Request;
 use helpers::float_binaryheap::FloatBinaryHeap;
+use helpers::rng::{SimRng,rng_from_entropy,rng_from_seed};
 
 pub struct ZipfGenerator<T> where T: MutDistribution<f64> {
     pop_distribution: ZipfDistribution,
     ita_distribution: T,
     next_req: Request,
+    last_arrival: f64,
     next_arrival: f64,
     total_nb_arrivals: usize,
     cur_nb_arrivals: usize,
+    rng: SimRng,
 }
 
 impl<T> ZipfGenerator<T> where T: MutDistribution<f64> {
@@ -26,9 +29,11 @@ impl<T> ZipfGenerator<T> where T: MutDistribution<f64> {
             pop_distribution: ZipfDistribution::new(catalogue_size, alpha).unwrap(),
             ita_distribution: distribution,
             next_req: Request::new(0),
+            last_arrival: 0.,
             next_arrival: 0.,
             total_nb_arrivals,
             cur_nb_arrivals: 0,
+            rng: rng_from_entropy(),
         };
         ret.draw_req();
         ret.draw_arrival();
@@ -37,19 +42,20 @@ impl<T> ZipfGenerator<T> where T: MutDistribution<f64> {
     }
 
     fn draw_req (&mut self) -> Request {
-        let mut new_req = Request::new(self.pop_distribution.mut_sample(&mut rand::thread_rng()));
+        let mut new_req = Request::new(self.pop_distribution.mut_sample(&mut self.rng));
         mem::swap(&mut self.next_req, &mut new_req);
         new_req
     }
 
     fn draw_arrival (&mut self) -> f64 {
         let ret = self.next_arrival;
-        self.next_arrival += self.ita_distribution.mut_sample(&mut rand::thread_rng());
+        self.last_arrival = ret;
+        self.next_arrival += self.ita_distribution.mut_sample(&mut self.rng);
         ret
     }
 }
 
-impl<T> Queue for ZipfGenerator<T> where T: MutDistribution<f64> {
+impl<T> Queue for ZipfGenerator<T> where T: MutDistribution<f64> + Send {
     fn arrival (&mut self, _req: Request) {
         panic!("You should not arrive at a generator");
     }
@@ -80,6 +86,14 @@ impl<T> Queue for ZipfGenerator<T> where T: MutDistribution<f64> {
     fn read_load (&self) -> usize {
         1
     }        
+
+    // The pending request is redrawn from the new stream
+    fn reseed (&mut self, seed: u64) {
+        self.rng = rng_from_seed(seed);
+        self.draw_req();
+        self.next_arrival = self.last_arrival;
+        self.draw_arrival();
+    }
 }
 
 pub struct ZipfGeneratorOld<T> where T: MutDistribution<f64> {
@@ -88,6 +102,8 @@ pub struct ZipfGeneratorOld<T> where T: MutDistribution<f64> {
     popularity: Vec<f64>,
     next_arrivals: FloatBinaryHeap<Request>,
     iat_distribution: Vec<T>,
+    started: bool,
+    rng: SimRng,
 } 
 
 impl<T> ZipfGeneratorOld<T> where T: MutDistribution<f64> {
@@ -99,7 +115,9 @@ impl<T> ZipfGeneratorOld<T> where T: MutDistribution<f64> {
             catalogue_size,
             popularity: Vec::with_capacity(catalogue_size),
             next_arrivals: FloatBinaryHeap::new(),
-            iat_distribution: Vec::with_capacity(catalogue_size)
+            iat_distribution: Vec::with_capacity(catalogue_size),
+            started: false,
+            rng: rng_from_entropy(),
         };
         let mut count = 0.;
         for i in 0..catalogue_size {
@@ -108,15 +126,22 @@ impl<T> ZipfGeneratorOld<T> where T: MutDistribution<f64> {
             count += pop;
         }
         for i in 0..catalogue_size {
-            ret.popularity[i as usize] /= count;
-            ret.iat_distribution.push(iat_func(ret.popularity[i as usize]));
-            ret.insert_exit(i+1, 0.);
+            ret.popularity[i] /= count;
+            ret.iat_distribution.push(iat_func(ret.popularity[i]));
         }
+        ret.draw_first_exits();
         ret
     }
 
+    fn draw_first_exits(&mut self) {
+        self.next_arrivals = FloatBinaryHeap::new();
+        for i in 0..self.catalogue_size {
+            self.insert_exit(i+1, 0.);
+        }
+    }
+
     fn insert_exit(&mut self, content: usize, time: f64) {
-        let ntime = time + self.iat_distribution[content-1].mut_sample(&mut rand::thread_rng());
+        let ntime = time + self.iat_distribution[content-1].mut_sample(&mut self.rng);
         self.next_arrivals.push(ntime, Request::new(content));
     }
 
@@ -125,7 +150,7 @@ impl<T> ZipfGeneratorOld<T> where T: MutDistribution<f64> {
     }
 }
 
-impl<T> Queue for ZipfGeneratorOld<T> where T: MutDistribution<f64> {
+impl<T> Queue for ZipfGeneratorOld<T> where T: MutDistribution<f64> + Send {
     fn arrival (&mut self, _req: Request) {
         panic!("You should not arrive at a generator");
     }
@@ -138,14 +163,22 @@ impl<T> Queue for ZipfGeneratorOld<T> where T: MutDistribution<f64> {
 
     fn pop_next_exit (&mut self) -> Option<(f64,Request)> {
         let ret = self.next_arrivals.pop();
-        match ret {
-            None => (),
-            Some((t,ref r)) => self.insert_exit(r.get_content() as usize, t)
-        };
+        self.started = true;
+        if let Some((t,ref r)) = ret {
+            self.insert_exit(r.get_content(), t)
+        }
         ret
     }
 
     fn read_load (&self) -> usize {
         1
     }    
+
+    // The first arrival of each content is only redrawn if none has left yet
+    fn reseed (&mut self, seed: u64) {
+        self.rng = rng_from_seed(seed);
+        if !self.started {
+            self.draw_first_exits();
+        }
+    }
 }
@@ -0,0 +1,622 @@
+// Compares the mean response times of simulated queues with their closed-form values.
+// Every check uses fixed seeds, so the outcome of the suite is deterministic
+extern crate queue_sim;
+extern crate rand;
+
+use rand::distributions::Exp;
+
+use queue_sim::analytic::mva::{exact_mva,Station};
+use queue_sim::helpers::distribution::ConstantDistribution;
+use queue_sim::queues::{Queue,sampler_from};
+use queue_sim::queues::breakdowns::{Breakdowns,Interruption};
+use queue_sim::queues::classifier::Classifier;
+use queue_sim::queues::client_population::ClientPopulation;
+use queue_sim::queues::mg1ps::MG1PS;
+use queue_sim::queues::mginf::MGINF;
+use queue_sim::queues::mgkfifo::{MGKFIFO,ServerSelection};
+use queue_sim::queues::mgklifo::MGKLIFO;
+use queue_sim::queues::mgkps::MGKPS;
+use queue_sim::queues::mgkpriority::{MGKPriority,Preemption};
+use queue_sim::queues::poisson_generator::PoissonGenerator;
+use queue_sim::queues::queueing_network::{Overflow,QNet};
+use queue_sim::queues::round_robin::RoundRobin;
+use queue_sim::queues::size_based::{MG1LAS,MG1SizeBased,SizePolicy};
+use queue_sim::queues::weighted_ps::{MG1DPS,MG1GPS};
+use queue_sim::queues::replications::{independent_replications,Precision,ReplicationParameters,ReplicationReport};
+use queue_sim::queues::simulation::{Simulation,StopCondition};
+use queue_sim::queues::sink::Sink;
+use queue_sim::queues::warmup::Warmup;
+
+const CONFIDENCE: f64 = 0.99;
+const REPLICATIONS: usize = 20;
+const WARMUP: f64 = 1000.;
+const HORIZON: f64 = 20000.;
+
+// Builds source -> stations[0] -> ... -> stations[n-1] -> sink, with Poisson arrivals of rate 'lambda'.
+// The sink is queue n+1
+fn tandem(seed: u64, lambda: f64, stations: Vec<Box<dyn Queue>>) -> QNet {
+    let mut qn = QNet::from_seed(seed);
+    let source = qn.add_queue(Box::new(PoissonGenerator::new(lambda, ConstantDistribution::new(0))));
+    for station in stations {
+        qn.add_queue(station);
+    }
+    let sink = qn.add_queue(Box::new(Sink::new()));
+
+    for q in source..sink {
+        qn.add_transition(q, Box::new(move |_,_,_| q + 1));
+    }
+    qn.set_warmup(Warmup::Time(WARMUP));
+    qn
+}
+
+fn replicate<F> (sink: usize, seed: u64, factory: F) -> ReplicationReport
+    where F: Fn(u64) -> QNet
+{
+    let params = ReplicationParameters {
+        sink,
+        confidence: CONFIDENCE,
+        precision: Precision::Fixed(REPLICATIONS),
+        seed,
+    };
+    independent_replications(factory, || StopCondition::Time(HORIZON), &params)
+}
+
+fn assert_response_time(report: &ReplicationReport, expected: f64) {
+    let (low, high) = report.response_time.interval();
+    assert!(low <= expected && expected <= high,
+            "Expected a mean response time of {}, got {} (confidence interval [{}, {}])",
+            expected, report.response_time.mean, low, high);
+}
+
+// Probability of waiting in an M/M/k queue with offered load a = lambda/mu
+fn erlang_c(k: usize, a: f64) -> f64 {
+    let mut term = 1.;
+    let mut sum = 1.;
+    for i in 1..k {
+        term *= a / i as f64;
+        sum += term;
+    }
+    let last = term * a / k as f64 * k as f64 / (k as f64 - a);
+    last / (sum + last)
+}
+
+#[test]
+fn mm1_ps_response_time() {
+    let (lambda, mu) = (0.5, 1.);
+    let report = replicate(2, 1, |seed| tandem(seed, lambda, vec![Box::new(MG1PS::new(1., Exp::new(mu)))]));
+    assert_response_time(&report, 1. / (mu - lambda));
+}
+
+#[test]
+fn md1_ps_response_time_is_insensitive() {
+    let (lambda, service) = (0.5, 1.);
+    let report = replicate(2, 2, |seed| tandem(seed, lambda, vec![Box::new(MG1PS::new(1., ConstantDistribution::new(service)))]));
+    assert_response_time(&report, service / (1. - lambda * service));
+}
+
+#[test]
+fn mg1_ps_work_rate_scales_service() {
+    let (lambda, mu, work_rate) = (1., 1., 2.);
+    let report = replicate(2, 3, |seed| tandem(seed, lambda, vec![Box::new(MG1PS::new(work_rate, Exp::new(mu)))]));
+    assert_response_time(&report, 1. / (mu * work_rate - lambda));
+}
+
+#[test]
+fn mm_inf_response_time() {
+    let (lambda, mu) = (5., 0.5);
+    let report = replicate(2, 4, |seed| tandem(seed, lambda, vec![Box::new(MGINF::new(1., Exp::new(mu)))]));
+    assert_response_time(&report, 1. / mu);
+    // Little's law: the number of busy servers is Poisson with mean lambda/mu
+    let (low, high) = report.loads[1].interval();
+    assert!(low <= lambda / mu && lambda / mu <= high, "Unexpected mean load {:?}", report.loads[1]);
+}
+
+#[test]
+fn md_inf_response_time() {
+    let (lambda, service) = (5., 1.5);
+    let report = replicate(2, 5, |seed| tandem(seed, lambda, vec![Box::new(MGINF::new(1., ConstantDistribution::new(service)))]));
+    // Deterministic service: every request stays exactly 'service'
+    assert!((report.response_time.mean - service).abs() < 1e-9, "Unexpected response time {:?}", report.response_time);
+}
+
+#[test]
+fn mm1_fifo_response_time() {
+    let (lambda, mu) = (0.5, 1.);
+    let report = replicate(2, 6, |seed| tandem(seed, lambda, vec![Box::new(MGKFIFO::new(1, 1., Exp::new(mu)))]));
+    assert_response_time(&report, 1. / (mu - lambda));
+}
+
+#[test]
+fn mmk_fifo_erlang_c() {
+    let (k, lambda, mu) = (3, 2., 1.);
+    let expected = erlang_c(k, lambda / mu) / (k as f64 * mu - lambda) + 1. / mu;
+    let report = replicate(2, 7, |seed| tandem(seed, lambda, vec![Box::new(MGKFIFO::new(k, 1., Exp::new(mu)))]));
+    assert_response_time(&report, expected);
+}
+
+#[test]
+fn md1_fifo_pollaczek_khinchine() {
+    let (lambda, service) = (0.5, 1.);
+    let rho = lambda * service;
+    let expected = service + rho * service / (2. * (1. - rho));
+    let report = replicate(2, 8, |seed| tandem(seed, lambda, vec![Box::new(MGKFIFO::new(1, 1., ConstantDistribution::new(service)))]));
+    assert_response_time(&report, expected);
+}
+
+#[test]
+fn jackson_tandem() {
+    let (lambda, mu1, mu2) = (0.5, 1., 0.8);
+    let expected = 1. / (mu1 - lambda) + 1. / (mu2 - lambda);
+    let report = replicate(3, 9, |seed| tandem(seed, lambda, vec![
+        Box::new(MG1PS::new(1., Exp::new(mu1))),
+        Box::new(MGKFIFO::new(1, 1., Exp::new(mu2))),
+    ]));
+    assert_response_time(&report, expected);
+}
+
+#[test]
+fn jackson_network_with_feedback() {
+    // source -> a; a -> b w.p. 0.5, exits otherwise; b -> a w.p. 0.5, exits otherwise
+    let (lambda, mu_a, mu_b) = (0.3, 1., 1.);
+    // Traffic equations: lambda_a = lambda + lambda_b / 2, lambda_b = lambda_a / 2
+    let lambda_a = lambda / 0.75;
+    let lambda_b = lambda_a / 2.;
+    let (visits_a, visits_b) = (lambda_a / lambda, lambda_b / lambda);
+    let expected = visits_a / (mu_a - lambda_a) + visits_b / (mu_b - lambda_b);
+
+    let factory = |seed| {
+        let mut qn = QNet::from_seed(seed);
+        qn.add_queue(Box::new(PoissonGenerator::new(lambda, ConstantDistribution::new(0))));
+        qn.add_queue(Box::new(MG1PS::new(1., Exp::new(mu_a))));
+        qn.add_queue(Box::new(MGKFIFO::new(1, 1., Exp::new(mu_b))));
+        let sink = qn.add_queue(Box::new(Sink::new()));
+        let routing = vec![
+            vec![0., 1., 0., 0.],
+            vec![0., 0., 0.5, 0.],
+            vec![0., 0.5, 0., 0.],
+        ];
+        qn.add_routing_matrix(&routing, sink).unwrap();
+        qn.set_warmup(Warmup::Time(WARMUP));
+        qn
+    };
+    let report = replicate(3, 10, factory);
+    assert_response_time(&report, expected);
+}
+
+#[test]
+fn closed_network_matches_exact_mva() {
+    // clients -> cpu (PS) -> disk (FIFO) -> clients
+    let (clients, think_time, cpu, disk) = (5, 2., 0.5, 0.3);
+    let stations = vec![Station::delay("clients", 1., think_time), Station::queueing("cpu", 1., cpu), Station::queueing("disk", 1., disk)];
+    let solution = exact_mva(&stations, clients);
+
+    let factory = |seed| {
+        let mut qn = QNet::from_seed(seed);
+        qn.add_queue(Box::new(ClientPopulation::new(clients, Exp::new(1. / think_time))));
+        qn.add_queue(Box::new(MG1PS::new(1., Exp::new(1. / cpu))));
+        qn.add_queue(Box::new(MGKFIFO::new(1, 1., Exp::new(1. / disk))));
+        for q in 0..3 {
+            qn.add_transition(q, Box::new(move |_,_,_| (q + 1) % 3));
+        }
+        qn.set_warmup(Warmup::Time(WARMUP));
+        qn
+    };
+    let report = replicate(0, 11, factory);
+    // Requests are completed when they come back to the clients: their response time excludes the think time
+    assert_response_time(&report, solution.cycle_time(0) - think_time);
+    let (low, high) = report.throughput.interval();
+    assert!(low <= solution.throughputs[0] && solution.throughputs[0] <= high,
+            "Expected a throughput of {}, got {:?}", solution.throughputs[0], report.throughput);
+}
+
+// Probability that an arrival finds all the servers of an M/M/k/k queue busy, with offered load a = lambda/mu
+fn erlang_b(k: usize, a: f64) -> f64 {
+    (1..=k).fold(1., |b, i| a * b / (i as f64 + a * b))
+}
+
+// source -> station -> sink, where requests dropped by the station go to a loss sink (queue 3)
+fn lossy(seed: u64, lambda: f64, station: Box<dyn Queue>) -> QNet {
+    let mut qn = tandem(seed, lambda, vec![station]);
+    let losses = qn.add_queue(Box::new(Sink::new()));
+    qn.set_overflow(1, Overflow::Drop(Some(losses)));
+    qn
+}
+
+fn assert_loss_rate(report: &ReplicationReport, expected: f64) {
+    let (low, high) = report.throughput.interval();
+    assert!(low <= expected && expected <= high, "Expected a loss rate of {}, got {:?}", expected, report.throughput);
+}
+
+#[test]
+fn mmkk_erlang_b() {
+    let (k, lambda, mu) = (4, 3., 1.);
+    let report = replicate(3, 12, |seed| {
+        let mut station = MGKFIFO::new(k, 1., Exp::new(mu));
+        station.set_capacity(k);
+        lossy(seed, lambda, Box::new(station))
+    });
+    assert_loss_rate(&report, lambda * erlang_b(k, lambda / mu));
+}
+
+#[test]
+fn mm1k_loss_probability() {
+    let (capacity, lambda, mu) = (3, 0.8, 1.);
+    let rho: f64 = lambda / mu;
+    let expected = lambda * (1. - rho) * rho.powi(capacity as i32) / (1. - rho.powi(capacity as i32 + 1));
+    // The number of requests in an M/M/1/K queue does not depend on the service discipline
+    let fifo = replicate(3, 13, |seed| {
+        let mut station = MGKFIFO::new(1, 1., Exp::new(mu));
+        station.set_capacity(capacity);
+        lossy(seed, lambda, Box::new(station))
+    });
+    assert_loss_rate(&fifo, expected);
+    let ps = replicate(3, 14, |seed| {
+        let mut station = MG1PS::new(1., Exp::new(mu));
+        station.set_capacity(capacity);
+        lossy(seed, lambda, Box::new(station))
+    });
+    assert_loss_rate(&ps, expected);
+}
+
+#[test]
+fn blocking_loses_no_request() {
+    // source -> first -> second (capacity 2) -> sink: requests wait in 'first' instead of being dropped
+    let (lambda, mu1, mu2) = (0.5, 1., 0.9);
+    for &overflow in &[Overflow::BlockAfterService, Overflow::BlockBeforeService] {
+        let factory = |seed| {
+            let mut second = MGKFIFO::new(1, 1., Exp::new(mu2));
+            second.set_capacity(2);
+            let mut qn = tandem(seed, lambda, vec![Box::new(MGKFIFO::new(2, 1., Exp::new(mu1))), Box::new(second)]);
+            qn.set_overflow(2, overflow);
+            qn
+        };
+        let report = replicate(3, 15, factory);
+        let (low, high) = report.throughput.interval();
+        assert!(low <= lambda && lambda <= high, "{:?}: expected a throughput of {}, got {:?}", overflow, lambda, report.throughput);
+
+        let mut qn = factory(16);
+        let run = qn.run_until(StopCondition::Time(HORIZON));
+        assert_eq!(run.losses.iter().sum::<usize>(), 0);
+        assert!(run.arrivals[3] > 0);
+    }
+}
+
+// Stationary distribution of a birth-death process on 0..=max with the given rates in state n
+fn birth_death<B, D>(births: B, deaths: D, max: usize) -> Vec<f64>
+    where B: Fn(usize) -> f64, D: Fn(usize) -> f64
+{
+    let mut p = vec![1.];
+    for n in 1..=max {
+        let next = p[n - 1] * births(n - 1) / deaths(n);
+        p.push(next);
+    }
+    let sum: f64 = p.iter().sum();
+    p.iter().map(|x| x / sum).collect()
+}
+
+// Same as lossy, for the requests that abandon or balk at the station
+fn impatient(seed: u64, lambda: f64, station: Box<dyn Queue>) -> QNet {
+    let mut qn = tandem(seed, lambda, vec![station]);
+    let abandonments = qn.add_queue(Box::new(Sink::new()));
+    qn.set_abandonment_sink(1, abandonments);
+    qn
+}
+
+#[test]
+fn mm1_fifo_reneging() {
+    // M/M/1+M: requests waiting in the buffer abandon at rate theta
+    let (lambda, mu, theta) = (1., 1., 0.5);
+    let p = birth_death(|_| lambda, |n| mu + (n - 1) as f64 * theta, 100);
+    let expected: f64 = p.iter().enumerate().skip(1).map(|(n, p)| theta * (n - 1) as f64 * p).sum();
+    let report = replicate(3, 17, |seed| {
+        let mut station = MGKFIFO::new(1, 1., Exp::new(mu));
+        station.set_patience(sampler_from(Exp::new(theta)));
+        impatient(seed, lambda, Box::new(station))
+    });
+    assert_loss_rate(&report, expected);
+}
+
+#[test]
+fn mm1_ps_reneging() {
+    // Every request in service abandons at rate theta
+    let (lambda, mu, theta) = (1., 1., 0.5);
+    let p = birth_death(|_| lambda, |n| mu + n as f64 * theta, 100);
+    let expected: f64 = p.iter().enumerate().map(|(n, p)| theta * n as f64 * p).sum();
+    let report = replicate(3, 18, |seed| {
+        let mut station = MG1PS::new(1., Exp::new(mu));
+        station.set_patience(sampler_from(Exp::new(theta)));
+        impatient(seed, lambda, Box::new(station))
+    });
+    assert_loss_rate(&report, expected);
+}
+
+#[test]
+fn mm1_proportional_balking() {
+    // Arriving requests refuse to join with probability n / max_load
+    let (lambda, mu, max_load) = (1., 1., 5);
+    let join = |n: usize| 1. - (n as f64 / max_load as f64).min(1.);
+    let p = birth_death(|n| lambda * join(n), |_| mu, max_load);
+    let expected: f64 = p.iter().enumerate().map(|(n, p)| lambda * (1. - join(n)) * p).sum();
+    let report = replicate(3, 19, |seed| {
+        let mut qn = impatient(seed, lambda, Box::new(MGKFIFO::new(1, 1., Exp::new(mu))));
+        qn.set_balking(1, Box::new(move |n| (n as f64 / max_load as f64).min(1.)));
+        qn
+    });
+    assert_loss_rate(&report, expected);
+
+    let mut qn = impatient(20, lambda, Box::new(MGKFIFO::new(1, 1., Exp::new(mu))));
+    qn.set_balking(1, Box::new(move |n| if n >= max_load { 1. } else { 0. }));
+    let run = qn.run_until(StopCondition::Time(HORIZON));
+    assert!(run.balks[1] > 0);
+    assert_eq!(run.balks[1], run.arrivals[3]);
+    assert!(qn.get_queue(1).read_load() <= max_load);
+}
+
+// source -> classifier -> station, with Poisson arrivals of rate lambdas[i] for class i. Class i leaves
+// the station to the sink 3 + i, so that replicating with that sink gives its response time
+fn prioritized(seed: u64, lambdas: &[f64], station: Box<dyn Queue>) -> QNet {
+    let mut qn = tandem(seed, lambdas.iter().sum(), vec![Box::new(Classifier::new(lambdas)), station]);
+    for _ in 1..lambdas.len() {
+        qn.add_queue(Box::new(Sink::new()));
+    }
+    qn.add_transition(2, Box::new(|req,_,_| 3 + req.get_class()));
+    qn
+}
+
+// Mean response times of each class in an M/M/1 queue with classes served by priority, all with rate mu:
+// Cobham's formula for the non-preemptive case, and the preemptive-resume one
+fn priority_response_times(lambdas: &[f64], mu: f64, preemptive: bool) -> Vec<f64> {
+    let residual = |k: usize| lambdas[..k].iter().sum::<f64>() / (mu * mu);
+    let mut sigma = 0.;
+    let mut expected = Vec::new();
+    for k in 0..lambdas.len() {
+        let next = sigma + lambdas[k] / mu;
+        expected.push(if preemptive {
+            1. / (mu * (1. - sigma)) + residual(k + 1) / ((1. - sigma) * (1. - next))
+        } else {
+            1. / mu + residual(lambdas.len()) / ((1. - sigma) * (1. - next))
+        });
+        sigma = next;
+    }
+    expected
+}
+
+#[test]
+fn mm1_priority_response_times() {
+    let (lambdas, mu) = ([0.2, 0.3], 1.);
+    for (&preemption, seed) in [Preemption::None, Preemption::Resume].iter().zip(21..) {
+        let expected = priority_response_times(&lambdas, mu, preemption != Preemption::None);
+        for (class, &expected) in expected.iter().enumerate() {
+            let report = replicate(3 + class, seed, |seed| prioritized(seed, &lambdas, Box::new(MGKPriority::new(1, 1., preemption, Exp::new(mu)))));
+            assert_response_time(&report, expected);
+        }
+    }
+}
+
+fn integrate<F>(f: F, a: f64, b: f64) -> f64 where F: Fn(f64) -> f64 {
+    // Simpson's rule
+    let n = 2000;
+    let h = (b - a) / n as f64;
+    let sum: f64 = (1..n).map(|i| f(a + i as f64 * h) * if i % 2 == 1 { 4. } else { 2. }).sum();
+    (f(a) + sum + f(b)) * h / 3.
+}
+
+// Mean response time of an M/M/1 queue with service rate 1, given the mean response time of a request of size x
+fn mean_over_sizes<F>(response_time: F) -> f64 where F: Fn(f64) -> f64 {
+    integrate(|x| response_time(x) * (-x).exp(), 0., 40.)
+}
+
+// Size-based station with exponential service of rate 1: MG1LAS without a policy
+fn size_based(seed: u64, lambda: f64, policy: Option<SizePolicy>) -> QNet {
+    let station: Box<dyn Queue> = match policy {
+        Some(policy) => Box::new(MG1SizeBased::new(1., policy, Exp::new(1.))),
+        None => Box::new(MG1LAS::new(1., Exp::new(1.))),
+    };
+    tandem(seed, lambda, vec![station])
+}
+
+#[test]
+fn mm1_size_based_response_times() {
+    let lambda = 0.5;
+    // Load brought by the requests of size at most x, and their second moment (Schrage and Miller)
+    let rho = |x: f64| lambda * (1. - (-x).exp() * (1. + x));
+    let second = |x: f64| 2. - (-x).exp() * (x * x + 2. * x + 2.);
+    let srpt = mean_over_sizes(|x| {
+        lambda * (second(x) + x * x * (-x).exp()) / (2. * (1. - rho(x)).powi(2)) + integrate(|t| 1. / (1. - rho(t)), 0., x)
+    });
+    let sjf = mean_over_sizes(|x| x + lambda / (1. - rho(x)).powi(2));
+    // Least attained service: requests of size x only see the first x of the work of the others
+    let attained = |x: f64| lambda * (1. - (-x).exp());
+    let truncated = |x: f64| 2. * (1. - (-x).exp() * (1. + x));
+    let las = mean_over_sizes(|x| lambda * truncated(x) / (2. * (1. - attained(x)).powi(2)) + x / (1. - attained(x)));
+
+    for (&(policy, expected), seed) in [(Some(SizePolicy::Srpt), srpt), (Some(SizePolicy::Sjf), sjf), (None, las)].iter().zip(23..) {
+        let report = replicate(2, seed, |seed| size_based(seed, lambda, policy));
+        assert_response_time(&report, expected);
+    }
+}
+
+#[test]
+fn md1_fsp_is_fifo() {
+    // With equal sizes, requests complete in arrival order in PS, hence in FSP
+    let (lambda, service) = (0.5, 1.);
+    let rho = lambda * service;
+    let expected = service + rho * service / (2. * (1. - rho));
+    let report = replicate(2, 26, |seed| tandem(seed, lambda, vec![Box::new(MG1SizeBased::new(1., SizePolicy::Fsp, ConstantDistribution::new(service)))]));
+    assert_response_time(&report, expected);
+}
+
+#[test]
+fn lifo_response_times() {
+    // Non-preemptive LIFO has the mean response time of FIFO
+    let (lambda, mu) = (0.5, 1.);
+    let report = replicate(2, 27, |seed| tandem(seed, lambda, vec![Box::new(MGKLIFO::new(1, 1., Preemption::None, Exp::new(mu)))]));
+    assert_response_time(&report, 1. / (mu - lambda));
+
+    // Single-server LCFS-PR is insensitive: with deterministic service, it behaves like M/M/1 on average
+    let (lambda, service) = (0.5, 1.);
+    let report = replicate(2, 28, |seed| tandem(seed, lambda, vec![Box::new(MGKLIFO::new(1, 1., Preemption::Resume, ConstantDistribution::new(service)))]));
+    assert_response_time(&report, service / (1. - lambda * service));
+    // With k servers and exponential service, the number of requests is that of M/M/k
+    let (k, lambda, mu) = (3, 2., 1.);
+    let expected = erlang_c(k, lambda / mu) / (k as f64 * mu - lambda) + 1. / mu;
+    let report = replicate(2, 29, |seed| tandem(seed, lambda, vec![Box::new(MGKLIFO::new(k, 1., Preemption::Resume, Exp::new(mu)))]));
+    assert_response_time(&report, expected);
+}
+
+#[test]
+fn mm1_dps_response_times() {
+    // Mean response times of the classes of an M/M/1 DPS queue with weights g solve (Fayolle, Mitrani and Iasnogorodski)
+    // T_k (1 - sum_j lambda_j g_j / (g_j mu + g_k mu)) - sum_j lambda_j g_j T_j / (g_j mu + g_k mu) = 1 / mu
+    let (lambdas, weights, mu) = ([0.25, 0.25], [2., 1.], 1.);
+    let coefficient = |k: usize, j: usize| lambdas[j] * weights[j] / (weights[j] * mu + weights[k] * mu);
+    let a = |k: usize, j: usize| if j == k { 1. - coefficient(k, 0) - coefficient(k, 1) - coefficient(k, k) } else { -coefficient(k, j) };
+    let det = a(0, 0) * a(1, 1) - a(0, 1) * a(1, 0);
+    let expected = [(a(1, 1) - a(0, 1)) / (mu * det), (a(0, 0) - a(1, 0)) / (mu * det)];
+    for (class, &expected) in expected.iter().enumerate() {
+        let report = replicate(3 + class, 30, |seed| prioritized(seed, &lambdas, Box::new(MG1DPS::new(1., weights.to_vec(), Exp::new(mu)))));
+        assert_response_time(&report, expected);
+    }
+}
+
+#[test]
+fn mm1_gps_is_work_conserving() {
+    // With exponential service of the same rate for every class, the number of requests is that of M/M/1,
+    // and so is the mean response time over all classes
+    let (lambdas, mu) = ([0.2, 0.3], 1.);
+    let report = replicate(3, 31, |seed| tandem(seed, 0.5, vec![Box::new(Classifier::new(&lambdas)), Box::new(MG1GPS::new(1., vec![3., 1.], Exp::new(mu)))]));
+    assert_response_time(&report, 1. / (mu - 0.5));
+}
+
+#[test]
+fn mgk_ps_response_times() {
+    // M/G/k-PS is insensitive: with deterministic service, it has the mean response time of M/M/k
+    let (k, lambda, service) = (3, 2., 1.);
+    let expected = erlang_c(k, lambda * service) / (k as f64 / service - lambda) + service;
+    let report = replicate(2, 32, |seed| tandem(seed, lambda, vec![Box::new(MGKPS::new(k, 1., ConstantDistribution::new(service)))]));
+    assert_response_time(&report, expected);
+
+    // With at most one request in service, a single server is FIFO
+    let (lambda, service) = (0.5, 1.);
+    let rho = lambda * service;
+    let report = replicate(2, 33, |seed| {
+        let mut station = MGKPS::new(1, 1., ConstantDistribution::new(service));
+        station.set_limit(1);
+        tandem(seed, lambda, vec![Box::new(station)])
+    });
+    assert_response_time(&report, service + rho * service / (2. * (1. - rho)));
+}
+
+#[test]
+fn round_robin_response_times() {
+    // Without overhead, the number of requests is that of M/M/1 whatever the quantum
+    let (lambda, mu) = (0.5, 1.);
+    let report = replicate(2, 34, |seed| tandem(seed, lambda, vec![Box::new(RoundRobin::new(0.1, 1., Exp::new(mu)))]));
+    assert_response_time(&report, 1. / (mu - lambda));
+
+    // A quantum longer than every request makes it FIFO
+    let (lambda, service) = (0.5, 1.);
+    let rho = lambda * service;
+    let report = replicate(2, 35, |seed| tandem(seed, lambda, vec![Box::new(RoundRobin::new(2. * service, 1., ConstantDistribution::new(service)))]));
+    assert_response_time(&report, service + rho * service / (2. * (1. - rho)));
+}
+
+// Mean response time of an M/M/2 queue with a fast and a slow server, where an arrival to an empty queue takes
+// the fast server with probability 'a'. With p(2) = 1, p(n) = rho^(n-2) above 2, and the states with a single
+// busy server follow from their balance equations and the cut between 1 and 2 requests
+fn heterogeneous_mm2(lambda: f64, fast: f64, slow: f64, a: f64) -> f64 {
+    let rho = lambda / (fast + slow);
+    // x = (slow + a lambda z) / (lambda + fast), y = (fast + (1-a) lambda z) / (lambda + slow), lambda (x+y) = fast + slow
+    let (cx, cy) = (1. / (lambda + fast), 1. / (lambda + slow));
+    let z = ((fast + slow) / lambda - slow * cx - fast * cy) / (a * lambda * cx + (1. - a) * lambda * cy);
+    let (x, y) = ((slow + a * lambda * z) * cx, (fast + (1. - a) * lambda * z) * cy);
+    let load = x + y + 2. / (1. - rho) + rho / ((1. - rho) * (1. - rho));
+    load / (z + x + y + 1. / (1. - rho)) / lambda
+}
+
+#[test]
+fn heterogeneous_mm2_response_times() {
+    let (lambda, fast, slow) = (1.5, 2., 1.);
+    for &(seed, selection, a) in &[(36, ServerSelection::FastestFree, 1.), (37, ServerSelection::RandomFree, 0.5)] {
+        let report = replicate(2, seed, |seed| {
+            let mut station = MGKFIFO::new(2, 1., Exp::new(1.));
+            station.set_speeds(&[slow, fast]);
+            station.set_selection(selection);
+            tandem(seed, lambda, vec![Box::new(station)])
+        });
+        assert_response_time(&report, heterogeneous_mm2(lambda, fast, slow, a));
+    }
+}
+
+// Mean response time of an M/M/1 queue whose server fails at rate 'fail', busy or idle, and is repaired at rate
+// 'repair', the service resuming then. Stationary distribution of (requests, server up) by Gauss-Seidel iterations
+fn mm1_with_breakdowns(lambda: f64, mu: f64, fail: f64, repair: f64, max: usize) -> f64 {
+    let mut p = vec![[1., 1.]; max + 1];
+    for _ in 0..2000 {
+        for n in 0..=max {
+            let arrivals = if n < max { lambda } else { 0. };
+            let services = if n > 0 { mu } else { 0. };
+            let from_below = |p: &Vec<[f64; 2]>, s: usize| if n > 0 { p[n - 1][s] * lambda } else { 0. };
+            let from_above = if n < max { p[n + 1][0] * mu } else { 0. };
+            p[n][0] = (from_below(&p, 0) + from_above + p[n][1] * repair) / (arrivals + services + fail);
+            p[n][1] = (from_below(&p, 1) + p[n][0] * fail) / (arrivals + repair);
+        }
+        let sum: f64 = p.iter().map(|x| x[0] + x[1]).sum();
+        for x in p.iter_mut() {
+            *x = [x[0] / sum, x[1] / sum];
+        }
+    }
+    p.iter().enumerate().map(|(n, x)| n as f64 * (x[0] + x[1])).sum::<f64>() / lambda
+}
+
+#[test]
+fn mm1_breakdowns_response_time() {
+    // With exponential service, FIFO and PS have the same number of requests
+    let (lambda, mu, fail, repair) = (0.5, 1., 0.1, 1.);
+    let expected = mm1_with_breakdowns(lambda, mu, fail, repair, 100);
+    let breakdowns = || Breakdowns::new(sampler_from(Exp::new(fail)), sampler_from(Exp::new(repair)), Interruption::Resume);
+    let report = replicate(2, 38, |seed| {
+        let mut station = MGKFIFO::new(1, 1., Exp::new(mu));
+        station.set_breakdowns(breakdowns());
+        tandem(seed, lambda, vec![Box::new(station)])
+    });
+    assert_response_time(&report, expected);
+    let report = replicate(2, 39, |seed| {
+        let mut station = MG1PS::new(1., Exp::new(mu));
+        station.set_breakdowns(breakdowns());
+        tandem(seed, lambda, vec![Box::new(station)])
+    });
+    assert_response_time(&report, expected);
+}
+
+#[test]
+fn mm1_multiple_vacations_response_time() {
+    // Decomposition: the M/M/1 response time, plus the mean residual vacation E[V^2] / 2E[V] = 1/theta
+    let (lambda, mu, theta) = (0.5, 1., 1.);
+    let report = replicate(2, 40, |seed| {
+        let mut station = MGKFIFO::new(1, 1., Exp::new(mu));
+        station.set_vacation(sampler_from(Exp::new(theta)));
+        tandem(seed, lambda, vec![Box::new(station)])
+    });
+    assert_response_time(&report, 1. / (mu - lambda) + 1. / theta);
+}
+
+#[test]
+fn infinite_server_breakdowns() {
+    // Each request has its own server, failing at rate 'fail' during its service of length 'service'
+    let (lambda, service, fail, repair) = (2., 1., 0.5, 1.);
+    let station = |interruption| {
+        let mut station = MGINF::new(1., ConstantDistribution::new(service));
+        station.set_breakdowns(Breakdowns::new(sampler_from(Exp::new(fail)), sampler_from(ConstantDistribution::new(repair)), interruption));
+        station
+    };
+    // A Poisson number of failures, each adding a repair
+    let report = replicate(2, 41, |seed| tandem(seed, lambda, vec![Box::new(station(Interruption::Resume))]));
+    assert_response_time(&report, service * (1. + fail * repair));
+    // Attempts until one lasts 'service' without failure
+    let report = replicate(2, 42, |seed| tandem(seed, lambda, vec![Box::new(station(Interruption::Restart))]));
+    assert_response_time(&report, ((fail * service).exp() - 1.) * (1. / fail + repair));
+    // Requests whose server fails before the end of their service leave
+    let report = replicate(3, 43, |seed| impatient(seed, lambda, Box::new(station(Interruption::Reroute))));
+    assert_loss_rate(&report, lambda * (1. - (-fail * service).exp()));
+}
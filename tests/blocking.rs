@@ -0,0 +1,102 @@
+// Checks that processor sharing servers go on serving while one of their requests is blocked
+extern crate queue_sim;
+
+use queue_sim::queues::Queue;
+use queue_sim::queues::mg1ps::{AggregatingMG1PS,MG1PS};
+use queue_sim::queues::request::Request;
+use queue_sim::queues::service::FromAttribute;
+
+fn request(id: usize, content: usize, work: f64) -> Request {
+    let mut r = Request::new(content);
+    r.set_id(id);
+    r.set_attribute("work", work);
+    r
+}
+
+// The (time, id) of the exits left
+fn exits(q: &mut dyn Queue) -> Vec<(f64, usize)> {
+    let mut exits = Vec::new();
+    while let Some((t, _)) = q.read_next_exit() {
+        q.update_time(t);
+        exits.push(q.pop_next_exit().map(|(t, r)| (t, r.get_id())).unwrap());
+    }
+    exits
+}
+
+// Requests of works 1 and 3 share the server until 2, when the first one is blocked:
+// the second one keeps half of the server meanwhile
+fn ps_blocked_at_2(stop_dispatch: bool) -> MG1PS<FromAttribute, Request> {
+    let mut q = MG1PS::new(1., FromAttribute::new("work", 1., 0.));
+    q.arrival(request(0, 0, 1.));
+    q.arrival(request(1, 0, 3.));
+    q.update_time(2.);
+    assert!(q.block_next_exit(stop_dispatch));
+    assert_eq!(q.read_next_exit().map(|(t, r)| (t, r.get_id())), Some((6., 1)));
+    q
+}
+
+#[test]
+fn ps_keeps_serving_while_blocked() {
+    let mut q = ps_blocked_at_2(false);
+    q.update_time(3.);
+    assert_eq!(q.unblock_exit().map(|r| r.get_id()), Some(0));
+    assert_eq!(exits(&mut q), vec![(4.5, 1)]);
+}
+
+#[test]
+fn ps_can_stop_the_dispatch() {
+    let mut q = ps_blocked_at_2(true);
+    // Request 2 waits for the release of request 0, then shares the server with request 1
+    q.update_time(2.5);
+    q.arrival(request(2, 0, 1.));
+    assert_eq!(q.read_load(), 3);
+    q.update_time(3.);
+    assert_eq!(q.unblock_exit().map(|r| r.get_id()), Some(0));
+    assert_eq!(exits(&mut q), vec![(5., 2), (5.5, 1)]);
+
+    // Without stopping the dispatch, it is served from its arrival
+    let mut q = ps_blocked_at_2(false);
+    q.update_time(2.5);
+    q.arrival(request(2, 0, 1.));
+    q.update_time(3.);
+    assert_eq!(q.unblock_exit().map(|r| r.get_id()), Some(0));
+    assert_eq!(exits(&mut q)[0].1, 2);
+}
+
+#[test]
+fn aggregating_ps_keeps_serving_while_blocked() {
+    // Requests 0 and 1 for content 0 are served together, while content 1 gets the other half of the server
+    let mut q = AggregatingMG1PS::new(1., FromAttribute::new("work", 1., 0.));
+    q.arrival(request(0, 0, 1.));
+    q.arrival(request(1, 0, 1.));
+    q.arrival(request(2, 1, 3.));
+    q.update_time(2.);
+    assert!(q.block_next_exit(false));
+    // Request 1 is not held with request 0, and content 1 keeps half of the server
+    assert_eq!(q.pop_next_exit().map(|(t, r)| (t, r.get_id())), Some((2., 1)));
+    assert_eq!(q.read_next_exit().map(|(t, r)| (t, r.get_id())), Some((6., 2)));
+    assert_eq!(q.read_load(), 2);
+    q.update_time(3.);
+    assert_eq!(q.unblock_exit().map(|r| r.get_id()), Some(0));
+    assert_eq!(exits(&mut q), vec![(4.5, 2)]);
+}
+
+#[test]
+fn aggregating_ps_holds_a_content_once() {
+    // Requests 0 and 1 for content 0 are both blocked, and hold a single share of the server
+    let mut q = AggregatingMG1PS::new(1., FromAttribute::new("work", 1., 0.));
+    q.arrival(request(0, 0, 1.));
+    q.arrival(request(1, 0, 1.));
+    q.arrival(request(2, 1, 3.));
+    q.update_time(2.);
+    assert!(q.block_next_exit(false));
+    assert!(q.block_next_exit(false));
+    assert_eq!(q.read_next_exit().map(|(t, r)| (t, r.get_id())), Some((6., 2)));
+    q.update_time(3.);
+    assert_eq!(q.unblock_exit().map(|r| r.get_id()), Some(0));
+    // Content 0 is still held by request 1
+    assert_eq!(q.read_next_exit().map(|(t, r)| (t, r.get_id())), Some((6., 2)));
+    q.update_time(4.);
+    assert_eq!(q.unblock_exit().map(|r| r.get_id()), Some(1));
+    assert_eq!(exits(&mut q), vec![(5., 2)]);
+}
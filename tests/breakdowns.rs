@@ -0,0 +1,136 @@
+// Checks the failures, repairs and vacations of MGKFIFO, MG1PS and MGINF servers
+extern crate queue_sim;
+extern crate toml;
+
+mod common;
+
+use common::{run,work};
+use queue_sim::helpers::distribution::ConstantDistribution;
+use queue_sim::network_config::{InterruptionConfig,NetworkConfig,QueueKind};
+use queue_sim::queues::{Queue,sampler_from};
+use queue_sim::queues::breakdowns::{Breakdowns,Interruption};
+use queue_sim::queues::mg1ps::MG1PS;
+use queue_sim::queues::mginf::MGINF;
+use queue_sim::queues::mgkfifo::MGKFIFO;
+use queue_sim::queues::request::Request;
+use queue_sim::queues::service::FromAttribute;
+
+// Up for 10, down for 5, from time 0
+fn breakdowns(interruption: Interruption) -> Breakdowns {
+    Breakdowns::new(sampler_from(ConstantDistribution::new(10.)), sampler_from(ConstantDistribution::new(5.)), interruption)
+}
+
+fn fifo(interruption: Interruption) -> MGKFIFO<FromAttribute> {
+    let mut q = MGKFIFO::new(1, 1., work());
+    q.set_breakdowns(breakdowns(interruption));
+    q
+}
+
+fn ps(interruption: Interruption) -> MG1PS<FromAttribute, Request> {
+    let mut q = MG1PS::new(1., work());
+    q.set_breakdowns(breakdowns(interruption));
+    q
+}
+
+#[test]
+fn fifo_server_failures() {
+    // Served from 5 to 10, and from 15 for the 3 left
+    assert_eq!(run(&mut fifo(Interruption::Resume), &[(5., 0, 8.)]), vec![(18., 0, false)]);
+    // Served again from 15
+    assert_eq!(run(&mut fifo(Interruption::Restart), &[(5., 0, 8.)]), vec![(23., 0, false)]);
+    assert_eq!(run(&mut fifo(Interruption::Reroute), &[(5., 0, 8.)]), vec![(10., 0, true)]);
+    // A request arriving while the server is down waits for the repair, and the next one for the first to leave
+    assert_eq!(run(&mut fifo(Interruption::Resume), &[(12., 0, 2.), (12., 0, 2.)]), vec![(17., 0, false), (19., 1, false)]);
+}
+
+#[test]
+fn fifo_requests_wait_for_a_repaired_server() {
+    // Both servers are down from 10 to 15: the requests start once they are repaired, the third one on the first free
+    let mut q = MGKFIFO::new(2, 1., work());
+    q.set_breakdowns(breakdowns(Interruption::Resume));
+    assert_eq!(run(&mut q, &[(12., 0, 1.), (12., 0, 3.), (12., 0, 1.)]), vec![(16., 0, false), (17., 2, false), (18., 1, false)]);
+    assert_eq!(q.read_busy_servers(), 0);
+}
+
+#[test]
+fn ps_server_failures() {
+    // Both requests get 5 before the failure at 10, and share the server again from 15
+    assert_eq!(run(&mut ps(Interruption::Resume), &[(0., 0, 6.), (0., 0, 6.)]), vec![(17., 0, false), (17., 1, false)]);
+    // The first request leaves at 8, the second one starts over at 15
+    assert_eq!(run(&mut ps(Interruption::Restart), &[(0., 0, 4.), (0., 0, 7.)]), vec![(8., 0, false), (22., 1, false)]);
+    assert_eq!(run(&mut ps(Interruption::Reroute), &[(0., 0, 4.), (0., 0, 7.)]), vec![(8., 0, false), (10., 1, true)]);
+}
+
+#[test]
+fn infinite_server_failures() {
+    // Every request has its own server, up when it arrives
+    let mut q = MGINF::new(1., work());
+    q.set_breakdowns(breakdowns(Interruption::Resume));
+    assert_eq!(run(&mut q, &[(3., 0, 12.), (3., 0, 5.)]), vec![(8., 1, false), (20., 0, false)]);
+
+    let mut q = MGINF::new(1., work());
+    q.set_breakdowns(breakdowns(Interruption::Reroute));
+    assert_eq!(run(&mut q, &[(3., 0, 12.), (3., 0, 5.)]), vec![(8., 1, false), (13., 0, true)]);
+}
+
+#[test]
+fn idle_servers_take_vacations() {
+    let mut q = MGKFIFO::new(1, 1., work());
+    q.set_vacation(sampler_from(ConstantDistribution::new(3.)));
+    // Idle since 0, back at 3
+    assert_eq!(run(&mut q, &[(1., 0, 2.)]), vec![(5., 0, false)]);
+    // On vacation from 5 to 8, then from 8 to 11
+    assert_eq!(run(&mut q, &[(9., 0, 1.), (9., 0, 1.)]), vec![(12., 0, false), (13., 1, false)]);
+
+    let mut q = MG1PS::new(1., work());
+    q.set_vacation(sampler_from(ConstantDistribution::new(3.)));
+    assert_eq!(run(&mut q, &[(1., 0, 2.), (1., 0, 2.)]), vec![(7., 0, false), (7., 1, false)]);
+}
+
+#[test]
+fn vacations_of_no_length_end() {
+    let mut q = MGKFIFO::new(1, 1., work());
+    q.set_vacation(sampler_from(ConstantDistribution::new(0.)));
+    assert_eq!(run(&mut q, &[(1., 0, 2.)]), vec![(3., 0, false)]);
+
+    let mut q = MG1PS::new(1., work());
+    q.set_vacation(sampler_from(ConstantDistribution::new(0.)));
+    assert_eq!(run(&mut q, &[(1., 0, 2.)]), vec![(3., 0, false)]);
+
+    let config: NetworkConfig = toml::from_str(r#"
+        [[queue]]
+        name = "server"
+        type = "mg1ps"
+        service = { type = "exp", rate = 1.0 }
+        vacation = { type = "constant", value = 0.0 }
+    "#).unwrap();
+    assert_eq!(config.build().err(), Some("Queue server: vacation: vacations must last".to_string()));
+}
+
+#[test]
+fn breakdowns_from_network_description() {
+    let config: NetworkConfig = toml::from_str(r#"
+        [[queue]]
+        name = "server"
+        type = "mgkfifo"
+        servers = 1
+        service = { type = "constant", value = 2.0 }
+        breakdowns = { up_time = { type = "constant", value = 10.0 }, repair_time = { type = "constant", value = 5.0 }, interruption = "restart" }
+
+        [[queue]]
+        name = "delay"
+        type = "mginf"
+        service = { type = "constant", value = 1.0 }
+        breakdowns = { up_time = { type = "constant", value = 10.0 }, repair_time = { type = "exp", rate = 1.0 } }
+    "#).unwrap();
+
+    match config.queues[0].kind {
+        QueueKind::Mgkfifo { breakdowns: Some(ref b), .. } => assert!(b.interruption == InterruptionConfig::Restart),
+        _ => panic!("Unexpected queue kind"),
+    }
+    match config.queues[1].kind {
+        QueueKind::Mginf { breakdowns: Some(ref b), .. } => assert!(b.interruption == InterruptionConfig::Resume),
+        _ => panic!("Unexpected queue kind"),
+    }
+    assert!(config.build().is_ok());
+}
@@ -0,0 +1,240 @@
+// Checks request classes and attributes, and the service times that depend on them
+extern crate queue_sim;
+extern crate rand;
+extern crate toml;
+
+use std::sync::{Arc,Mutex};
+
+use rand::distributions::Exp;
+
+use queue_sim::helpers::distribution::ConstantDistribution;
+use queue_sim::helpers::rng::rng_from_seed;
+use queue_sim::network_config::{AttributeConfig,NetworkConfig,QueueKind,ServiceConfig};
+use queue_sim::queues::{Queue,sampler_from};
+use queue_sim::queues::classifier::{AttributeValue,Classifier};
+use queue_sim::queues::mginf::MGINF;
+use queue_sim::queues::poisson_generator::PoissonGenerator;
+use queue_sim::queues::queueing_network::QNet;
+use queue_sim::queues::request::{attributes,Attribute,Request};
+use queue_sim::queues::service::{FromAttribute,PerClass,PerContent,ServiceDistribution};
+use queue_sim::queues::simulation::{Simulation,StopCondition};
+use queue_sim::queues::sink::Sink;
+
+#[test]
+fn attributes_are_typed() {
+    let mut r = Request::new(7);
+    assert_eq!(r.get_class(), 0);
+    r.set_class(2);
+    r.set_attribute(attributes::SIZE, 1500usize);
+    r.set_attribute(attributes::TENANT, "web");
+    r.set_attribute("ratio", 0.5);
+    r.set_attribute("cached", true);
+
+    assert_eq!(r.get_class(), 2);
+    assert_eq!(r.get_int(attributes::SIZE), Some(1500));
+    // Integers can be read as floats, not the other way around
+    assert_eq!(r.get_float(attributes::SIZE), Some(1500.));
+    assert_eq!(r.get_int("ratio"), None);
+    assert_eq!(r.get_text(attributes::TENANT), Some("web"));
+    assert_eq!(r.get_bool("cached"), Some(true));
+    assert_eq!(r.get_float("missing"), None);
+    assert_eq!(r.remove_attribute("cached"), Some(Attribute::Bool(true)));
+    assert_eq!(r.get_attributes().keys().collect::<Vec<_>>(), vec!["ratio", "size", "tenant"]);
+}
+
+#[test]
+fn classifier_draws_classes_with_their_attributes() {
+    let mut qn = QNet::from_seed(1);
+    let source = qn.add_queue(Box::new(PoissonGenerator::new(1., ConstantDistribution::new(0))));
+    let mut classifier = Classifier::new(&[1., 3.]);
+    classifier.set_attribute(0, attributes::TENANT, AttributeValue::Constant(Attribute::from("a")));
+    classifier.set_attribute(1, attributes::SIZE, AttributeValue::Random(sampler_from(Exp::new(1.))));
+    let classify = qn.add_queue(Box::new(classifier));
+    let sink = qn.add_queue(Box::new(Sink::new()));
+    qn.add_transition(source, Box::new(move |_,_,_| classify));
+
+    let seen = Arc::new(Mutex::new(vec![0; 2]));
+    let counts = seen.clone();
+    qn.add_transition(classify, Box::new(move |req,_,_| {
+        // QNet records the queue the request came from
+        assert_eq!(req.get_origin(), Some(source));
+        match req.get_class() {
+            0 => assert!(req.get_text(attributes::TENANT) == Some("a") && req.get_attribute(attributes::SIZE).is_none()),
+            _ => assert!(req.get_float(attributes::SIZE).is_some_and(|s| s > 0.) && req.get_attribute(attributes::TENANT).is_none()),
+        }
+        counts.lock().unwrap()[req.get_class()] += 1;
+        sink
+    }));
+    qn.run_until(StopCondition::Time(4000.));
+
+    let seen = seen.lock().unwrap();
+    let share = seen[1] as f64 / (seen[0] + seen[1]) as f64;
+    assert!((share - 0.75).abs() < 0.03, "{:?}", *seen);
+}
+
+#[test]
+fn attributes_from_network_description() {
+    let config: NetworkConfig = toml::from_str(r#"
+        [[queue]]
+        name = "source"
+        type = "generator"
+        interarrival = { type = "constant", value = 1.0 }
+        routing = { type = "fixed", to = "classify" }
+
+        [[queue]]
+        name = "classify"
+        type = "classifier"
+        classes = [{ weight = 1.0, attributes = { priority = 2, share = 0.5, tenant = "web", cached = true, size = { type = "exp", rate = 0.1 } } }]
+        routing = { type = "by_class", to = ["link"] }
+
+        [[queue]]
+        name = "link"
+        type = "mginf"
+        service = { attribute = "share", scale = 2.0, default = 0.0 }
+        routing = { type = "fixed", to = "sink" }
+
+        [[queue]]
+        name = "sink"
+        type = "sink"
+    "#).unwrap();
+
+    match config.queues[1].kind {
+        QueueKind::Classifier { ref classes } => {
+            let a = &classes[0].attributes;
+            assert!(matches!(a["priority"], AttributeConfig::Int(2)));
+            assert!(matches!(a["share"], AttributeConfig::Float(_)));
+            assert!(matches!(a["tenant"], AttributeConfig::Text(_)));
+            assert!(matches!(a["cached"], AttributeConfig::Bool(true)));
+            assert!(matches!(a["size"], AttributeConfig::Random(_)));
+        },
+        _ => panic!("Expected a classifier"),
+    }
+    // Requests arrive every second, and their share sets their service in the link
+    let run = config.build().unwrap().run_until(StopCondition::Time(10.5));
+    assert_eq!(run.arrivals[3], 9);
+    assert!((run.mean_response_time(3) - 1.).abs() < 1e-9, "{}", run.mean_response_time(3));
+}
+
+#[test]
+fn service_depends_on_class_content_and_attributes() {
+    let mut rng = rng_from_seed(1);
+    let mut per_class = PerClass::new(vec![ConstantDistribution::new(1.), ConstantDistribution::new(3.)], None);
+    let mut per_content = PerContent::new(vec![(7, ConstantDistribution::new(2.))].into_iter().collect(), ConstantDistribution::new(5.));
+    let mut from_size = FromAttribute::new(attributes::SIZE, 0.001, 1000.);
+
+    let mut r = Request::new(7);
+    r.set_class(1);
+    r.set_attribute(attributes::SIZE, 1500usize);
+    assert_eq!(per_class.sample_work(&r, &mut rng), 3.);
+    assert_eq!(per_content.sample_work(&r, &mut rng), 2.);
+    assert_eq!(per_content.sample_work(&Request::new(8), &mut rng), 5.);
+    assert_eq!(from_size.sample_work(&r, &mut rng), 1.5);
+    // Without default, classes above the last distribution use it
+    r.set_class(2);
+    assert_eq!(per_class.sample_work(&r, &mut rng), 3.);
+    assert_eq!(from_size.sample_work(&Request::new(0), &mut rng), 1.);
+
+    // Links serve bytes: a 1500 bytes packet takes 1.5 on a link at 1000 bytes per unit of time
+    let mut link = MGINF::new(1000., FromAttribute::new(attributes::SIZE, 1., 0.));
+    let mut classes = MGINF::new(2., per_class);
+    for (class, size) in [(0usize, 500usize), (1, 1500)] {
+        let mut r = Request::new(0);
+        r.set_class(class);
+        r.set_attribute(attributes::SIZE, size);
+        link.arrival(r.clone());
+        classes.arrival(r);
+    }
+    let exits = |q: &mut dyn Queue| (0..2).map(|_| q.pop_next_exit().unwrap().0).collect::<Vec<f64>>();
+    assert_eq!(exits(&mut link), vec![0.5, 1.5]);
+    assert_eq!(exits(&mut classes), vec![0.5, 1.5]);
+}
+
+#[test]
+fn service_from_network_description() {
+    let config: NetworkConfig = toml::from_str(r#"
+        [[queue]]
+        name = "server"
+        type = "mgkfifo"
+        servers = 1
+        service = { per_class = [{ type = "constant", value = 0.5 }, { type = "exp", rate = 1.0 }] }
+
+        [[queue]]
+        name = "cache"
+        type = "mg1ps"
+        service = { per_content = [{ content = 1, service = { type = "constant", value = 0.1 } }], default = { type = "constant", value = 0.2 } }
+
+        [[queue]]
+        name = "link"
+        type = "mginf"
+        work_rate = 1e6
+        service = { attribute = "size", scale = 8.0, default = 1500.0 }
+
+        [[queue]]
+        name = "plain"
+        type = "mginf"
+        service = { type = "exp", rate = 1.0 }
+    "#).unwrap();
+
+    let service = |i: usize| match config.queues[i].kind {
+        QueueKind::Mgkfifo { ref service, .. } | QueueKind::Mg1ps { ref service, .. } | QueueKind::Mginf { ref service, .. } => service.clone(),
+        _ => panic!("Expected a server"),
+    };
+    assert!(matches!(service(0), ServiceConfig::PerClass { ref per_class, default: None } if per_class.len() == 2));
+    assert!(matches!(service(1), ServiceConfig::PerContent { default: Some(_), .. }));
+    assert!(matches!(service(2), ServiceConfig::Attribute { scale, default: Some(_), .. } if scale == 8.));
+    assert_eq!(service(3).mean(), Some(1.));
+    assert_eq!(service(0).mean(), None);
+    assert!(config.build().is_ok());
+}
+
+fn build_error(description: &str) -> String {
+    let config: NetworkConfig = toml::from_str(description).unwrap();
+    match config.build() {
+        Ok(_) => panic!("The network should be rejected"),
+        Err(e) => e,
+    }
+}
+
+#[test]
+fn requests_without_service_or_weight_are_rejected() {
+    // Contents and attributes are not known in advance: a default is needed
+    let error = build_error(r#"
+        [[queue]]
+        name = "cache"
+        type = "mg1ps"
+        service = { per_content = [{ content = 1, service = { type = "constant", value = 0.1 } }] }
+    "#);
+    assert_eq!(error, "Queue cache: service: a per-content service needs a default");
+    let error = build_error(r#"
+        [[queue]]
+        name = "link"
+        type = "mginf"
+        service = { attribute = "size" }
+    "#);
+    assert_eq!(error, "Queue link: service: an attribute service needs a non-negative default");
+
+    // The classifier draws classes 0 to 2
+    let classes = r#"
+        [[queue]]
+        name = "classify"
+        type = "classifier"
+        classes = [{ weight = 1.0 }, { weight = 1.0 }, { weight = 1.0 }]
+        routing = { type = "by_class", to = ["server", "server", "server"] }
+    "#;
+    let error = build_error(&format!("{}{}", classes, r#"
+        [[queue]]
+        name = "server"
+        type = "mgkfifo"
+        servers = 1
+        service = { per_class = [{ type = "exp", rate = 1.0 }, { type = "constant", value = 2.0 }] }
+    "#));
+    assert_eq!(error, "Queue server: service: no distribution for class 2, and no default");
+    let error = build_error(&format!("{}{}", classes, r#"
+        [[queue]]
+        name = "server"
+        type = "dps"
+        weights = [2.0, 1.0]
+        service = { type = "exp", rate = 1.0 }
+    "#));
+    assert_eq!(error, "Queue server: no weight for class 2");
+}
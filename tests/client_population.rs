@@ -0,0 +1,65 @@
+// Checks closed populations of clients whose requests complete away from the population
+extern crate queue_sim;
+extern crate toml;
+
+use queue_sim::helpers::distribution::ConstantDistribution;
+use queue_sim::network_config::NetworkConfig;
+use queue_sim::queues::client_population::ClientPopulation;
+use queue_sim::queues::mginf::MGINF;
+use queue_sim::queues::queueing_network::QNet;
+use queue_sim::queues::simulation::{Simulation,StopCondition};
+use queue_sim::queues::sink::Sink;
+
+// 3 clients thinking 1s, whose requests stay 1s in a delay line before the sink
+fn network(completion: bool) -> QNet {
+    let mut qn = QNet::from_seed(1);
+    let clients = qn.add_queue(Box::new(ClientPopulation::new(3, ConstantDistribution::new(1.))));
+    let server = qn.add_queue(Box::new(MGINF::new(1., ConstantDistribution::new(1.))));
+    let sink = qn.add_queue(Box::new(Sink::new()));
+    qn.add_transition(clients, Box::new(move |_,_,_| server));
+    qn.add_transition(server, Box::new(move |_,_,_| sink));
+    if completion {
+        qn.set_population_completion(clients, sink);
+    }
+    qn
+}
+
+#[test]
+fn clients_think_again_once_their_request_completes() {
+    // Every client completes a request every 2s, from 2s on
+    let run = network(true).run_until(StopCondition::Time(100.));
+    assert_eq!(run.arrivals[2], 150);
+    assert_eq!(run.mean_response_time(2), 1.);
+
+    // Without completion queue, the requests never come back to their clients
+    let run = network(false).run_until(StopCondition::Time(100.));
+    assert_eq!(run.arrivals[2], 3);
+}
+
+#[test]
+fn completion_from_network_description() {
+    let config: NetworkConfig = toml::from_str(r#"
+        [run]
+        seed = 1
+
+        [[queue]]
+        name = "clients"
+        type = "client_population"
+        clients = 3
+        think_time = { type = "constant", value = 1.0 }
+        completion = "done"
+        routing = { type = "fixed", to = "server" }
+
+        [[queue]]
+        name = "server"
+        type = "mginf"
+        service = { type = "constant", value = 1.0 }
+        routing = { type = "fixed", to = "done" }
+
+        [[queue]]
+        name = "done"
+        type = "sink"
+    "#).unwrap();
+    let run = config.build().unwrap().run_until(StopCondition::Time(100.));
+    assert_eq!(run.arrivals[2], 150);
+}
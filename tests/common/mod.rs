@@ -0,0 +1,75 @@
+// Fixtures shared by the tests that drive a single queue by hand
+#![allow(dead_code)]
+
+use queue_sim::queues::Queue;
+use queue_sim::queues::request::Request;
+use queue_sim::queues::service::FromAttribute;
+
+// Service of the requests built below: their "work" attribute
+pub fn work() -> FromAttribute {
+    FromAttribute::new("work", 1., 0.)
+}
+
+pub fn request(id: usize, class: usize, work: f64) -> Request {
+    let mut r = Request::new(0);
+    r.set_id(id);
+    r.set_class(class);
+    r.set_attribute("work", work);
+    r
+}
+
+// Requests (arrival time, class, work), numbered in arrival order, served until the queue is empty.
+// Returns the (time, id) of their exits, and whether they leave unserved (see Queue::next_exit_abandons)
+pub fn run(q: &mut dyn Queue, arrivals: &[(f64, usize, f64)]) -> Vec<(f64, usize, bool)> {
+    let mut exits = Vec::new();
+    let mut arrivals = arrivals.iter().enumerate().peekable();
+    while arrivals.peek().is_some() || q.read_load() > 0 {
+        let next_exit = q.read_next_exit().map(|(t, _)| t);
+        match arrivals.peek() {
+            Some(&(id, &(t, class, work))) if next_exit.is_none_or(|e| t < e) => {
+                q.update_time(t);
+                q.arrival(request(id, class, work));
+                arrivals.next();
+            },
+            _ => {
+                q.update_time(next_exit.unwrap());
+                let abandons = q.next_exit_abandons();
+                let (t, r) = q.pop_next_exit().unwrap();
+                exits.push((t, r.get_id(), abandons));
+            },
+        }
+    }
+    exits
+}
+
+// Requests (arrival time, class, work). Returns the (time, id) of their exits
+pub fn class_exits(q: &mut dyn Queue, arrivals: &[(f64, usize, f64)]) -> Vec<(f64, usize)> {
+    run(q, arrivals).into_iter().map(|(t, id, _)| (t, id)).collect()
+}
+
+// Requests (arrival time, work) of class 0. Returns the (time, id) of their exits
+pub fn exits(q: &mut dyn Queue, arrivals: &[(f64, f64)]) -> Vec<(f64, usize)> {
+    let arrivals: Vec<_> = arrivals.iter().map(|&(t, work)| (t, 0, work)).collect();
+    class_exits(q, &arrivals)
+}
+
+// Network description of a generator sending a request of content 0 every 'interarrival' to the given queue,
+// named "server", that routes them to a sink
+pub fn line(interarrival: f64, server: &str) -> String {
+    format!(r#"
+        [[queue]]
+        name = "source"
+        type = "generator"
+        interarrival = {{ type = "constant", value = {:?} }}
+        routing = {{ type = "fixed", to = "server" }}
+
+        [[queue]]
+        name = "server"
+        {}
+        routing = {{ type = "fixed", to = "sink" }}
+
+        [[queue]]
+        name = "sink"
+        type = "sink"
+    "#, interarrival, server)
+}
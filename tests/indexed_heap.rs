@@ -0,0 +1,67 @@
+// Checks the event calendar of QNet
+extern crate queue_sim;
+
+use queue_sim::helpers::indexed_heap::IndexedFloatHeap;
+
+fn drain(heap: &mut IndexedFloatHeap) -> Vec<(f64, usize)> {
+    let mut ret = Vec::new();
+    while let Some((key, index)) = heap.peek() {
+        ret.push((key, index));
+        heap.set(index, None);
+    }
+    ret
+}
+
+#[test]
+fn pops_in_key_order() {
+    let mut heap = IndexedFloatHeap::new();
+    for (i, &k) in [5., 3., 8., 1., 4., 7.].iter().enumerate() {
+        heap.set(i, Some(k));
+    }
+    assert_eq!(heap.len(), 6);
+    assert_eq!(drain(&mut heap), vec![(1., 3), (3., 1), (4., 4), (5., 0), (7., 5), (8., 2)]);
+    assert!(heap.is_empty());
+}
+
+#[test]
+fn update_moves_keys_both_ways() {
+    let mut heap = IndexedFloatHeap::new();
+    for i in 0..10 {
+        heap.set(i, Some(i as f64));
+    }
+    heap.set(9, Some(-1.));
+    heap.set(0, Some(20.));
+    heap.set(5, Some(5.5));
+    assert_eq!(heap.get(0), Some(20.));
+    assert_eq!(heap.peek(), Some((-1., 9)));
+    let order: Vec<usize> = drain(&mut heap).into_iter().map(|(_, i)| i).collect();
+    assert_eq!(order, vec![9, 1, 2, 3, 4, 5, 6, 7, 8, 0]);
+}
+
+#[test]
+fn remove_from_the_middle() {
+    let mut heap = IndexedFloatHeap::new();
+    for i in 0..8 {
+        heap.set(i, Some((i * 3 % 8) as f64));
+    }
+    heap.set(3, None);
+    heap.set(6, None);
+    // Removing an absent index does nothing
+    heap.set(6, None);
+    heap.set(42, None);
+    assert_eq!(heap.get(3), None);
+    assert_eq!(heap.get(42), None);
+    assert_eq!(heap.len(), 6);
+    assert_eq!(drain(&mut heap), vec![(0., 0), (3., 1), (4., 4), (5., 7), (6., 2), (7., 5)]);
+}
+
+#[test]
+fn ties_go_to_the_highest_index() {
+    let mut heap = IndexedFloatHeap::new();
+    heap.set(2, Some(1.));
+    heap.set(0, Some(1.));
+    heap.set(4, Some(1.));
+    heap.set(1, Some(0.5));
+    heap.set(1, Some(1.));
+    assert_eq!(drain(&mut heap), vec![(1., 4), (1., 2), (1., 1), (1., 0)]);
+}
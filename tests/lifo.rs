@@ -0,0 +1,50 @@
+// Checks the service order of LIFO queues
+extern crate queue_sim;
+extern crate toml;
+
+use queue_sim::network_config::{NetworkConfig,PreemptionConfig,QueueKind};
+use queue_sim::queues::Queue;
+use queue_sim::queues::mgklifo::MGKLIFO;
+use queue_sim::queues::mgkpriority::Preemption;
+use queue_sim::queues::request::Request;
+use queue_sim::queues::service::FromAttribute;
+
+// Requests of work 2, arriving at times 0, 0.5 and 1 on 'servers' servers. Returns the (time, id) of their exits
+fn three_arrivals(servers: usize, preemption: Preemption) -> Vec<(f64, usize)> {
+    let mut q = MGKLIFO::new(servers, 1., preemption, FromAttribute::new("work", 1., 0.));
+    for id in 0..3 {
+        let mut r = Request::new(0);
+        r.set_id(id);
+        r.set_attribute("work", 2.);
+        q.update_time(id as f64 / 2.);
+        q.arrival(r);
+    }
+    assert_eq!(q.read_load(), 3);
+    (0..3).map(|_| q.pop_next_exit().map(|(t, r)| (t, r.get_id())).unwrap()).collect()
+}
+
+#[test]
+fn latest_requests_first() {
+    // Request 2 is served before request 1 once request 0 completes
+    assert_eq!(three_arrivals(1, Preemption::None), vec![(2., 0), (4., 2), (6., 1)]);
+    // Each arrival preempts the request in service, which resumes once the later ones completed
+    assert_eq!(three_arrivals(1, Preemption::Resume), vec![(3., 2), (4.5, 1), (6., 0)]);
+    assert_eq!(three_arrivals(1, Preemption::Repeat), vec![(3., 2), (5., 1), (7., 0)]);
+    // With two servers, request 2 preempts request 0, the earliest arrived in service, which resumes once request 1 completes
+    assert_eq!(three_arrivals(2, Preemption::Resume), vec![(2.5, 1), (3., 2), (3.5, 0)]);
+}
+
+#[test]
+fn lifo_from_network_description() {
+    let config: NetworkConfig = toml::from_str(r#"
+        [[queue]]
+        name = "stack"
+        type = "lifo"
+        servers = 2
+        preemption = "resume"
+        service = { type = "constant", value = 1.0 }
+    "#).unwrap();
+
+    assert!(matches!(config.queues[0].kind, QueueKind::Lifo { servers: 2, preemption: PreemptionConfig::Resume, .. }));
+    assert!(config.build().is_ok());
+}
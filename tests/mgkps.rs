@@ -0,0 +1,95 @@
+// Checks the shares of multi-server and limited processor sharing queues
+extern crate queue_sim;
+extern crate toml;
+
+mod common;
+
+use common::{exits,request,work};
+use queue_sim::network_config::{NetworkConfig,QueueKind};
+use queue_sim::queues::Queue;
+use queue_sim::queues::mgkps::MGKPS;
+use queue_sim::queues::service::FromAttribute;
+
+#[test]
+fn k_servers_share_among_n_requests() {
+    // Three requests on two servers get 2/3 each, until only one is left and gets a full server
+    let mut q = MGKPS::new(2, 1., work());
+    assert_eq!(exits(&mut q, &[(0., 2.), (0., 2.), (0., 4.)]), vec![(3., 0), (3., 1), (5., 2)]);
+    // Fewer requests than servers: each one is served at full rate
+    assert_eq!(exits(&mut q, &[(5., 1.), (5., 3.)]), vec![(6., 0), (8., 1)]);
+}
+
+#[test]
+fn limited_ps_queues_the_others() {
+    let mut q = MGKPS::new(1, 1., work());
+    q.set_limit(2);
+    assert_eq!(exits(&mut q, &[(0., 1.), (0., 1.), (0., 1.)]), vec![(2., 0), (2., 1), (3., 2)]);
+}
+
+#[test]
+fn blocked_requests_keep_their_share() {
+    // Three requests on two servers: request 0 is done at 1.5 and held, the others keep 2/3 of a server
+    let mut q = MGKPS::new(2, 1., work());
+    for (id, &work) in [1., 2., 2.].iter().enumerate() {
+        q.arrival(request(id, 0, work));
+    }
+    q.update_time(1.5);
+    assert!(q.block_next_exit(false));
+    assert_eq!(q.read_next_exit().map(|(t, _)| t), Some(3.));
+    assert_eq!(q.read_busy_servers(), 2);
+    // Released at 2, it leaves each of the others a full server for their last 2/3
+    q.update_time(2.);
+    assert_eq!(q.unblock_exit().map(|r| r.get_id()), Some(0));
+    let rest = exits(&mut q, &[]);
+    assert_eq!(rest.iter().map(|&(_, id)| id).collect::<Vec<_>>(), vec![1, 2]);
+    assert!(rest.iter().all(|&(t, _)| (t - 8. / 3.).abs() < 1e-9));
+}
+
+// One server, at most two requests in service: requests 0 and 1 are done at 2, and request 0 is held
+fn limited_and_blocked(stop_dispatch: bool) -> MGKPS<FromAttribute> {
+    let mut q = MGKPS::new(1, 1., work());
+    q.set_limit(2);
+    for id in 0..3 {
+        q.arrival(request(id, 0, 1.));
+    }
+    q.update_time(2.);
+    assert!(q.block_next_exit(stop_dispatch));
+    assert_eq!(q.pop_next_exit().map(|(t, r)| (t, r.get_id())), Some((2., 1)));
+    q
+}
+
+#[test]
+fn blocked_requests_count_in_the_limit() {
+    // Request 2 starts at 2 with half of the server, and has it all once request 0 is released at 3
+    let mut q = limited_and_blocked(false);
+    assert_eq!(q.read_busy_servers(), 1);
+    q.update_time(3.);
+    assert_eq!(q.unblock_exit().map(|r| r.get_id()), Some(0));
+    assert_eq!(exits(&mut q, &[]), vec![(3.5, 2)]);
+}
+
+#[test]
+fn blocked_requests_can_stop_the_dispatch() {
+    // Request 2 waits for the release of request 0
+    let mut q = limited_and_blocked(true);
+    assert!(q.read_next_exit().is_none());
+    assert_eq!(q.read_load(), 2);
+    q.update_time(3.);
+    assert_eq!(q.unblock_exit().map(|r| r.get_id()), Some(0));
+    assert_eq!(exits(&mut q, &[]), vec![(4., 2)]);
+}
+
+#[test]
+fn mgkps_from_network_description() {
+    let config: NetworkConfig = toml::from_str(r#"
+        [[queue]]
+        name = "server"
+        type = "mgkps"
+        servers = 4
+        limit = 16
+        service = { type = "constant", value = 1.0 }
+    "#).unwrap();
+
+    assert!(matches!(config.queues[0].kind, QueueKind::Mgkps { servers: 4, limit: Some(16), capacity: None, .. }));
+    assert!(config.build().is_ok());
+}
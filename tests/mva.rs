@@ -0,0 +1,156 @@
+// Checks the MVA solvers against closed-form results
+extern crate queue_sim;
+extern crate toml;
+
+use queue_sim::analytic::mva::{exact_mva,schweitzer_mva,stations_from_config,Station,StationKind};
+use queue_sim::network_config::NetworkConfig;
+
+fn assert_close(value: f64, expected: f64, relative_error: f64) {
+    assert!((value - expected).abs() <= relative_error * expected.abs(), "Expected {}, got {}", expected, value);
+}
+
+// Throughput of the finite-source M/M/1//N queue (machine repairman model)
+fn repairman_throughput(population: usize, service_time: f64, think_time: f64) -> f64 {
+    let ratio = service_time / think_time;
+    let mut term = 1.;
+    let mut sum = 1.;
+    for n in 1..=population {
+        term *= (population - n + 1) as f64 * ratio;
+        sum += term;
+    }
+    (1. - 1. / sum) / service_time
+}
+
+#[test]
+fn exact_mva_machine_repairman() {
+    let stations = vec![Station::delay("think", 1., 10.), Station::queueing("cpu", 1., 1.)];
+    for &n in &[1, 5, 10, 20] {
+        let solution = exact_mva(&stations, n);
+        assert_close(solution.throughputs[0], repairman_throughput(n, 1., 10.), 1e-9);
+        // Little's law over the whole cycle
+        assert_close(solution.throughputs[0] * solution.cycle_time(0), n as f64, 1e-9);
+    }
+}
+
+#[test]
+fn exact_mva_visit_ratios() {
+    // The cpu is visited twice per cycle, for half of the service time: same demand as one full visit
+    let once = exact_mva(&[Station::delay("think", 1., 5.), Station::queueing("cpu", 1., 2.)], 8);
+    let stations = vec![Station::delay("think", 1., 5.), Station::queueing("cpu", 2., 1.)];
+    let twice = exact_mva(&stations, 8);
+    assert_close(twice.throughputs[0], once.throughputs[0], 1e-9);
+    assert_close(twice.station_throughput(&stations, 0, 1), 2. * once.throughputs[0], 1e-9);
+}
+
+#[test]
+fn schweitzer_approximates_exact_mva() {
+    let stations = vec![Station::delay("think", 1., 4.), Station::queueing("cpu", 1., 0.5), Station::queueing("disk", 2., 0.3)];
+    let exact = exact_mva(&stations, 30);
+    let approximate = schweitzer_mva(&stations, &[30], 1e-9).unwrap();
+    assert_close(approximate.throughputs[0], exact.throughputs[0], 0.05);
+}
+
+#[test]
+fn schweitzer_multiclass_respects_bounds() {
+    let stations = vec![
+        Station::new("think", StationKind::Delay, vec![1., 1.], vec![2., 8.]),
+        Station::new("cpu", StationKind::Queueing, vec![1., 1.], vec![0.2, 0.1]),
+        Station::new("disk", StationKind::Queueing, vec![3., 1.], vec![0.1, 0.4]),
+    ];
+    let solution = schweitzer_mva(&stations, &[10, 5], 1e-9).unwrap();
+    for (k, u) in solution.utilizations.iter().enumerate().skip(1) {
+        assert!(*u < 1., "Station {} is over-utilized: {}", k, u);
+    }
+    for c in 0..2 {
+        let total_demand: f64 = stations.iter().map(|s| s.demand(c)).sum();
+        assert!(solution.throughputs[c] <= [10., 5.][c] / total_demand + 1e-9);
+        assert_close(solution.throughputs[c] * solution.cycle_time(c), [10., 5.][c], 1e-9);
+    }
+}
+
+#[test]
+fn schweitzer_reports_non_convergence() {
+    let stations = vec![Station::delay("think", 1., 4.), Station::queueing("cpu", 1., 0.5)];
+    // No change can be below a zero tolerance
+    assert!(schweitzer_mva(&stations, &[10], 0.).is_err());
+}
+
+#[test]
+fn visit_ratios_from_network_description() {
+    let config: NetworkConfig = toml::from_str(r#"
+        [[queue]]
+        name = "think"
+        type = "mginf"
+        service = { type = "exp", rate = 0.1 }
+        routing = { type = "fixed", to = "cpu" }
+
+        [[queue]]
+        name = "cpu"
+        type = "mg1ps"
+        service = { type = "exp", rate = 2.0 }
+        routing = { type = "probabilistic", to = ["disk", "think"], probabilities = [0.75, 0.25] }
+
+        [[queue]]
+        name = "disk"
+        type = "mgkfifo"
+        servers = 1
+        service = { type = "exp", rate = 5.0 }
+        routing = { type = "fixed", to = "cpu" }
+    "#).unwrap();
+
+    let stations = stations_from_config(&config, "think").unwrap();
+    assert_eq!(stations.len(), 3);
+    assert_close(stations[1].visits[0], 4., 1e-9);
+    assert_close(stations[2].visits[0], 3., 1e-9);
+    assert_close(stations[1].demand(0), 2., 1e-9);
+    assert_close(stations[2].demand(0), 0.6, 1e-9);
+    assert_eq!(stations[0].kind, StationKind::Delay);
+}
+
+#[test]
+fn fcfs_needs_exponential_service() {
+    let config: NetworkConfig = toml::from_str(r#"
+        [[queue]]
+        name = "think"
+        type = "mginf"
+        service = { type = "exp", rate = 0.1 }
+        routing = { type = "fixed", to = "disk" }
+
+        [[queue]]
+        name = "disk"
+        type = "mgkfifo"
+        servers = 1
+        service = { type = "constant", value = 0.2 }
+        routing = { type = "fixed", to = "think" }
+    "#).unwrap();
+
+    assert_eq!(stations_from_config(&config, "think").unwrap_err(), "Queue disk: a single-server mgkfifo needs an exponential service");
+}
+
+#[test]
+fn population_with_a_completion_queue() {
+    let config: NetworkConfig = toml::from_str(r#"
+        [[queue]]
+        name = "clients"
+        type = "client_population"
+        clients = 10
+        think_time = { type = "exp", rate = 0.5 }
+        completion = "log"
+        routing = { type = "fixed", to = "cpu" }
+
+        [[queue]]
+        name = "cpu"
+        type = "mg1ps"
+        service = { type = "exp", rate = 2.0 }
+        routing = { type = "fixed", to = "log" }
+
+        [[queue]]
+        name = "log"
+        type = "sink"
+    "#).unwrap();
+
+    // The completion queue leads back to the clients, and takes no time
+    let stations = stations_from_config(&config, "clients").unwrap();
+    assert_eq!(stations.len(), 2);
+    assert_close(stations[1].visits[0], 1., 1e-9);
+}
@@ -0,0 +1,262 @@
+// Checks the parsing and validation of network descriptions
+extern crate queue_sim;
+extern crate serde_json;
+extern crate toml;
+
+mod common;
+
+use common::line;
+use queue_sim::network_config::{CacheKind,NetworkConfig,QueueKind,RoutingConfig};
+use queue_sim::queues::simulation::{Simulation,StopCondition};
+
+fn parse(description: &str) -> Result<NetworkConfig, String> {
+    toml::from_str(description).map_err(|e| e.to_string())
+}
+
+fn parse_error(description: &str) -> String {
+    match parse(description) {
+        Ok(_) => panic!("The description should not parse"),
+        Err(e) => e,
+    }
+}
+
+// Error of building a network made of the given queue, feeding a sink
+fn build_error(queue: &str) -> String {
+    let config = parse(&format!(r#"
+        [[queue]]
+        name = "server"
+        {}
+        routing = {{ type = "fixed", to = "sink" }}
+
+        [[queue]]
+        name = "sink"
+        type = "sink"
+    "#, queue)).unwrap();
+    match config.build() {
+        Ok(_) => panic!("The network should not build"),
+        Err(e) => e,
+    }
+}
+
+#[test]
+fn example_networks_parse() {
+    // They are not built, as their file loggers would create files
+    for file in ["networks/tandem.toml", "networks/interactive.toml"].iter() {
+        let config = NetworkConfig::from_file(file).unwrap();
+        assert!(config.queues.len() >= 3, "{}", file);
+    }
+}
+
+#[test]
+fn queue_kind_and_common_keys() {
+    let config = parse(r#"
+        [[cache]]
+        name = "lru"
+        type = "lru"
+        size = 10
+
+        [[queue]]
+        name = "server"
+        type = "mgkfifo"
+        servers = 2
+        service = { type = "exp", rate = 1.0 }
+        capacity = 5
+        routing = { type = "cache", cache = "lru", hit = "sink", miss = "sink" }
+        overflow = { type = "drop" }
+        abandonment_sink = "sink"
+
+        [[queue]]
+        name = "sink"
+        type = "sink"
+    "#).unwrap();
+
+    assert!(matches!(config.caches[0].kind, CacheKind::Lru { size: 10 }));
+    assert_eq!(config.queues[0].name, "server");
+    assert!(matches!(config.queues[0].kind, QueueKind::Mgkfifo { servers: 2, capacity: Some(5), .. }));
+    assert!(matches!(config.queues[0].routing, Some(RoutingConfig::Cache { .. })));
+    assert!(config.queues[0].overflow.is_some());
+    assert_eq!(config.queues[0].abandonment_sink, Some("sink".to_string()));
+    assert!(matches!(config.queues[1].kind, QueueKind::Sink {}));
+}
+
+#[test]
+fn json_descriptions() {
+    let config: NetworkConfig = serde_json::from_str(r#"{
+        "queue": [
+            { "name": "source", "type": "poisson_generator", "rate": 1, "routing": { "type": "fixed", "to": "sink" } },
+            { "name": "sink", "type": "sink" }
+        ]
+    }"#).unwrap();
+    assert!(matches!(config.queues[0].kind, QueueKind::PoissonGenerator { rate, content: 1 } if rate == 1.));
+
+    let error = serde_json::from_str::<NetworkConfig>(r#"{ "queue": [ { "name": "sink", "type": "sink", "capacity": 1 } ] }"#);
+    assert!(error.is_err());
+}
+
+#[test]
+fn unknown_keys_are_rejected() {
+    // Keys of the queue kind
+    let error = parse_error(r#"
+        [[queue]]
+        name = "server"
+        type = "mg1ps"
+        service = { type = "exp", rate = 1.0 }
+        capcity = 5
+    "#);
+    assert!(error.contains("capcity") && error.contains("server"), "{}", error);
+
+    // Keys common to all queues
+    let error = parse_error(r#"
+        [[queue]]
+        name = "sink"
+        type = "sink"
+        abandonment_snk = "sink"
+    "#);
+    assert!(error.contains("abandonment_snk"), "{}", error);
+
+    // Nested keys, and caches
+    assert!(parse(r#"
+        [[queue]]
+        name = "server"
+        type = "mg1ps"
+        service = { type = "exp", rate = 1.0 }
+        breakdowns = { up_time = { type = "exp", rate = 1.0 }, repair_time = { type = "exp", rate = 1.0 }, interuption = "restart" }
+    "#).is_err());
+    assert!(parse(r#"
+        [[queue]]
+        name = "server"
+        type = "mg1ps"
+        service = { per_class = [{ type = "exp", rate = 1.0 }], defalt = { type = "exp", rate = 1.0 } }
+    "#).is_err());
+    assert!(parse_error(r#"
+        [[cache]]
+        name = "lru"
+        type = "lru"
+        sise = 10
+
+        [[queue]]
+        name = "sink"
+        type = "sink"
+    "#).contains("sise"));
+}
+
+#[test]
+fn missing_and_mistyped_keys_are_rejected() {
+    assert!(parse(r#"
+        [[queue]]
+        name = "server"
+        type = "mg1ps"
+    "#).is_err());
+    assert!(parse(r#"
+        [[queue]]
+        name = "server"
+        type = "mgkfifo"
+        servers = "two"
+        service = { type = "exp", rate = 1.0 }
+    "#).is_err());
+    assert!(parse(r#"
+        [[queue]]
+        name = "server"
+        type = "mg2ps"
+    "#).is_err());
+}
+
+#[test]
+fn invalid_parameters_are_rejected_when_building() {
+    let error = build_error(r#"type = "mgkps"
+        servers = 0
+        service = { type = "exp", rate = 1.0 }"#);
+    assert_eq!(error, "Queue server: there must be at least one server");
+
+    let error = build_error(r#"type = "mgkps"
+        servers = 2
+        work_rate = 0.0
+        service = { type = "exp", rate = 1.0 }"#);
+    assert_eq!(error, "Queue server: the rate must be positive");
+
+    let error = build_error(r#"type = "mg1ps"
+        service = { type = "exp", rate = 0.0 }"#);
+    assert_eq!(error, "Queue server: service: invalid distribution parameters");
+
+    let error = build_error(r#"type = "mgkfifo"
+        servers = 1
+        service = { type = "exp", rate = 1.0 }
+        patience = { type = "constant", value = -1.0 }"#);
+    assert_eq!(error, "Queue server: patience: invalid distribution parameters");
+
+    let error = build_error(r#"type = "poisson_generator"
+        rate = -1.0"#);
+    assert_eq!(error, "Queue server: the rate must be positive");
+}
+
+// Each row: the server of a line (see common::line), its interarrival, the end of the run, and the requests served
+// by then with their response time
+#[test]
+fn servers_from_network_descriptions() {
+    let servers = [
+        // Requests of class 0 for content 0, without size
+        (r#"type = "mgkfifo"
+            servers = 1
+            service = { per_class = [{ type = "constant", value = 0.5 }, { type = "exp", rate = 1.0 }] }"#, 1., 10.3, 9, 0.5),
+        (r#"type = "mg1ps"
+            service = { per_content = [{ content = 1, service = { type = "constant", value = 0.1 } }], default = { type = "constant", value = 0.2 } }"#, 1., 10.3, 10, 0.2),
+        (r#"type = "mginf"
+            work_rate = 1e6
+            service = { attribute = "size", scale = 8.0, default = 1500.0 }"#, 1., 10.3, 10, 0.012),
+        (r#"type = "priority"
+            servers = 2
+            service = { per_class = [{ type = "constant", value = 0.1 }, { type = "exp", rate = 1.0 }] }
+            preemption = "resume"
+            capacity = 10"#, 1., 10.3, 10, 0.1),
+        (r#"type = "size_based"
+            policy = "srpt"
+            service = { type = "constant", value = 0.5 }"#, 1., 10.3, 9, 0.5),
+        (r#"type = "size_based"
+            policy = "las"
+            work_rate = 2.0
+            service = { attribute = "size", default = 1.0 }
+            capacity = 5"#, 1., 10.3, 9, 0.5),
+        (r#"type = "lifo"
+            servers = 2
+            preemption = "resume"
+            service = { type = "constant", value = 1.0 }"#, 1., 10.3, 9, 1.),
+        (r#"type = "dps"
+            weights = [2.0, 1.0]
+            service = { type = "constant", value = 0.5 }"#, 1., 10.3, 9, 0.5),
+        (r#"type = "gps"
+            weights = [1.0, 1.0, 2.0]
+            service = { type = "constant", value = 0.5 }
+            capacity = 10"#, 1., 10.3, 9, 0.5),
+        // Two requests at most are in service, each on a full server
+        (r#"type = "mgkps"
+            servers = 4
+            limit = 16
+            service = { type = "constant", value = 1.0 }"#, 0.5, 10.2, 18, 1.),
+        // Every request is served alone, in two quanta without switch
+        (r#"type = "round_robin"
+            quantum = 0.01
+            overhead = { type = "constant", value = 0.001 }
+            service = { type = "constant", value = 0.015 }"#, 0.02, 1.005, 49, 0.015),
+        // Every request finds both servers free, and takes the fastest one
+        (r#"type = "mgkfifo"
+            servers = 2
+            speeds = [2.0, 1.0]
+            service = { type = "constant", value = 1.0 }"#, 2., 20.3, 9, 0.5),
+        // The server is down from 10 to 15, from 25 to 30 and from 40 to 45: the request arriving at 9 starts over
+        // at 15, and the one arriving at 27 waits until 30
+        (r#"type = "mgkfifo"
+            servers = 1
+            service = { type = "constant", value = 2.0 }
+            breakdowns = { up_time = { type = "constant", value = 10.0 }, repair_time = { type = "constant", value = 5.0 }, interruption = "restart" }"#, 9., 47.5, 5, 19. / 5.),
+        // Each request is served before its own server fails
+        (r#"type = "mginf"
+            service = { type = "constant", value = 1.0 }
+            breakdowns = { up_time = { type = "constant", value = 10.0 }, repair_time = { type = "exp", rate = 1.0 } }"#, 9., 47.5, 5, 1.),
+    ];
+    for (i, &(server, interarrival, end, served, response)) in servers.iter().enumerate() {
+        let config = parse(&line(interarrival, server)).unwrap();
+        let run = config.build().unwrap().run_until(StopCondition::Time(end));
+        assert_eq!(run.arrivals[2], served, "server {}", i);
+        assert!((run.mean_response_time(2) - response).abs() < 1e-9, "server {}: {}", i, run.mean_response_time(2));
+    }
+}
@@ -0,0 +1,39 @@
+// Checks that running seeded simulations in parallel does not change their results
+extern crate queue_sim;
+extern crate rand;
+
+use rand::distributions::Exp;
+
+use queue_sim::helpers::distribution::ConstantDistribution;
+use queue_sim::helpers::parallel::parallel_map;
+use queue_sim::helpers::rng::SeedSequence;
+use queue_sim::queues::mgkfifo::MGKFIFO;
+use queue_sim::queues::poisson_generator::PoissonGenerator;
+use queue_sim::queues::queueing_network::QNet;
+use queue_sim::queues::simulation::{Simulation,StopCondition};
+use queue_sim::queues::sink::Sink;
+
+// M/M/2 queue at load 'rho', run for 1000s: (events, departures, mean response time)
+fn sweep_point(rho: f64, seed: u64) -> (usize, usize, f64) {
+    let mut qn = QNet::from_seed(seed);
+    let source = qn.add_queue(Box::new(PoissonGenerator::new(2. * rho, ConstantDistribution::new(0))));
+    let server = qn.add_queue(Box::new(MGKFIFO::new(2, 1., Exp::new(1.))));
+    let sink = qn.add_queue(Box::new(Sink::new()));
+    qn.add_transition(source, Box::new(move |_,_,_| server));
+    qn.add_transition(server, Box::new(move |_,_,_| sink));
+    let run = qn.run_until(StopCondition::Time(1000.));
+    (run.events, run.arrivals[sink], run.mean_response_time(sink))
+}
+
+fn points(seed: u64) -> Vec<(f64, u64)> {
+    let mut seeds = SeedSequence::new(seed);
+    (1..10).map(|i| (i as f64 * 0.1, seeds.next_seed())).collect()
+}
+
+#[test]
+fn parallel_sweep_equals_sequential_one() {
+    let sequential: Vec<(usize, usize, f64)> = points(3).into_iter().map(|(rho, seed)| sweep_point(rho, seed)).collect();
+    for &threads in [1, 2, 4, 16].iter() {
+        assert_eq!(parallel_map(points(3), threads, |(rho, seed)| sweep_point(rho, seed)), sequential);
+    }
+}
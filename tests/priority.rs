@@ -0,0 +1,78 @@
+// Checks the service order of priority queues
+extern crate queue_sim;
+extern crate toml;
+
+use queue_sim::network_config::{NetworkConfig,PreemptionConfig,QueueKind};
+use queue_sim::queues::Queue;
+use queue_sim::queues::mgkpriority::{MGKPriority,Preemption};
+use queue_sim::queues::request::Request;
+use queue_sim::queues::service::FromAttribute;
+
+fn request(id: usize, class: usize, work: f64) -> Request {
+    let mut r = Request::new(0);
+    r.set_id(id);
+    r.set_class(class);
+    r.set_attribute("work", work);
+    r
+}
+
+// A low priority request of work 2 starts at time 0, a high priority one of work 1 arrives at time 1.
+// Returns the (time, id) of their exits
+fn low_then_high(preemption: Preemption) -> Vec<(f64, usize)> {
+    let mut q = MGKPriority::new(1, 1., preemption, FromAttribute::new("work", 1., 0.));
+    q.arrival(request(0, 1, 2.));
+    q.update_time(1.);
+    q.arrival(request(1, 0, 1.));
+    (0..2).map(|_| q.pop_next_exit().map(|(t, r)| (t, r.get_id())).unwrap()).collect()
+}
+
+#[test]
+fn preemption_modes() {
+    assert_eq!(low_then_high(Preemption::None), vec![(2., 0), (3., 1)]);
+    assert_eq!(low_then_high(Preemption::Resume), vec![(2., 1), (3., 0)]);
+    assert_eq!(low_then_high(Preemption::Repeat), vec![(2., 1), (4., 0)]);
+}
+
+#[test]
+fn classes_are_served_by_priority() {
+    let mut q = MGKPriority::new(2, 1., Preemption::Resume, FromAttribute::new("work", 1., 0.));
+    // Both servers are busy with class 2 requests, then a class 1 request preempts the latest started one,
+    // and a class 0 request the other one
+    q.arrival(request(0, 2, 10.));
+    q.update_time(1.);
+    q.arrival(request(1, 2, 10.));
+    q.update_time(2.);
+    q.arrival(request(2, 1, 1.));
+    assert_eq!(q.read_load(), 3);
+    q.update_time(2.5);
+    q.arrival(request(3, 0, 1.));
+    assert_eq!(q.read_busy_servers(), 2);
+
+    let exits: Vec<(f64, usize)> = (0..4).map(|_| q.pop_next_exit().map(|(t, r)| (t, r.get_id())).unwrap()).collect();
+    // Request 0 was served 2.5 before being preempted, and resumes before request 1 (served 1) as it arrived first
+    assert_eq!(exits, vec![(3., 2), (3.5, 3), (10.5, 0), (12.5, 1)]);
+    assert_eq!(q.read_load(), 0);
+}
+
+#[test]
+fn priority_from_network_description() {
+    let config: NetworkConfig = toml::from_str(r#"
+        [[queue]]
+        name = "cpu"
+        type = "priority"
+        servers = 2
+        service = { per_class = [{ type = "constant", value = 0.1 }, { type = "exp", rate = 1.0 }] }
+        preemption = "resume"
+        capacity = 10
+
+        [[queue]]
+        name = "link"
+        type = "priority"
+        servers = 1
+        service = { type = "exp", rate = 1.0 }
+    "#).unwrap();
+
+    assert!(matches!(config.queues[0].kind, QueueKind::Priority { servers: 2, preemption: PreemptionConfig::Resume, capacity: Some(10), .. }));
+    assert!(matches!(config.queues[1].kind, QueueKind::Priority { preemption: PreemptionConfig::None, capacity: None, .. }));
+    assert!(config.build().is_ok());
+}
@@ -0,0 +1,48 @@
+// Checks the batch means method
+extern crate queue_sim;
+
+use queue_sim::helpers::distribution::ConstantDistribution;
+use queue_sim::queues::generator::Generator;
+use queue_sim::queues::mginf::MGINF;
+use queue_sim::queues::queueing_network::QNet;
+use queue_sim::queues::replications::{batch_means,Precision,ReplicationParameters};
+use queue_sim::queues::sink::Sink;
+
+// A request every 'interarrival' seconds, each one staying 0.5s in a delay line before the sink
+fn deterministic_line(interarrival: f64) -> QNet {
+    let mut qn = QNet::from_seed(1);
+    let source = qn.add_queue(Box::new(Generator::new(ConstantDistribution::new(interarrival))));
+    let server = qn.add_queue(Box::new(MGINF::new(1., ConstantDistribution::new(0.5))));
+    let sink = qn.add_queue(Box::new(Sink::new()));
+    qn.add_transition(source, Box::new(move |_,_,_| server));
+    qn.add_transition(server, Box::new(move |_,_,_| sink));
+    qn
+}
+
+fn params(precision: Precision) -> ReplicationParameters {
+    ReplicationParameters { sink: 2, confidence: 0.95, precision, seed: 1 }
+}
+
+#[test]
+fn batches_of_a_deterministic_run() {
+    let mut qn = deterministic_line(1.);
+    let report = batch_means(&mut qn, 10., &params(Precision::Fixed(5)));
+    assert_eq!(report.response_time.samples, 5);
+    assert_eq!(report.empty, 0);
+    assert!((report.response_time.mean - 0.5).abs() < 1e-9);
+    assert!(report.response_time.variance < 1e-18);
+    // Batches are contiguous: each one sees 10 departures in 10s, except the first one (the first departure is at 1.5s)
+    assert!((report.throughput.mean - 0.98).abs() < 1e-9, "{:?}", report.throughput);
+    assert!((report.loads[1].mean - 0.49).abs() < 1e-9, "{:?}", report.loads[1]);
+}
+
+#[test]
+fn empty_batches_give_no_response_time() {
+    // One departure every 25s, from 25.5s on: only 3 batches out of the first 10 see one
+    let mut qn = deterministic_line(25.);
+    let report = batch_means(&mut qn, 10., &params(Precision::Fixed(10)));
+    assert_eq!(report.throughput.samples, 10);
+    assert_eq!(report.empty, 7);
+    assert_eq!(report.response_time.samples, 3);
+    assert!((report.response_time.mean - 0.5).abs() < 1e-9);
+}
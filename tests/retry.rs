@@ -0,0 +1,136 @@
+// Checks the accounting of client retries
+extern crate queue_sim;
+extern crate rand;
+
+use rand::distributions::Exp;
+
+use queue_sim::helpers::distribution::ConstantDistribution;
+use queue_sim::helpers::rng::rng_from_seed;
+use queue_sim::queues::mgkfifo::MGKFIFO;
+use queue_sim::queues::poisson_generator::PoissonGenerator;
+use queue_sim::queues::queueing_network::{QNet,TransitionOutcome};
+use queue_sim::queues::retry::{Backoff,Jitter,RetryPolicy};
+use queue_sim::queues::simulation::{Simulation,StopCondition};
+use queue_sim::queues::sink::Sink;
+
+#[test]
+fn exponential_backoff_is_capped_and_jittered() {
+    let mut rng = rng_from_seed(1);
+    let backoff = Backoff::exponential(1., 2., 5., Jitter::None);
+    let delays: Vec<f64> = (1..=5).map(|n| backoff.delay(n, &mut rng)).collect();
+    assert_eq!(delays, vec![1., 2., 4., 5., 5.]);
+
+    let full = Backoff::exponential(1., 2., 5., Jitter::Full);
+    let equal = Backoff::exponential(1., 2., 5., Jitter::Equal);
+    for _ in 0..1000 {
+        let d = full.delay(3, &mut rng);
+        assert!((0. ..=4.).contains(&d));
+        let d = equal.delay(3, &mut rng);
+        assert!((2. ..=4.).contains(&d));
+    }
+    assert_eq!(Backoff::constant(0.5).delay(10, &mut rng), 0.5);
+}
+
+// source -> server (single slot, drops when busy) -> sink, with retries at the source
+fn lossy_with_retries(seed: u64, timeout: Option<f64>, max_attempts: usize) -> QNet {
+    let mut qn = QNet::from_seed(seed);
+    let source = qn.add_queue(Box::new(PoissonGenerator::new(1., ConstantDistribution::new(0))));
+    let mut station = MGKFIFO::new(1, 1., Exp::new(1.));
+    station.set_capacity(1);
+    let server = qn.add_queue(Box::new(station));
+    let sink = qn.add_queue(Box::new(Sink::new()));
+    qn.add_transition(source, Box::new(move |_,_,_| server));
+    qn.add_transition(server, Box::new(move |_,_,_| sink));
+    qn.set_retry_policy(source, RetryPolicy {
+        completion: sink,
+        timeout,
+        max_attempts,
+        backoff: Backoff::exponential(0.5, 2., 8., Jitter::Full),
+    });
+    qn
+}
+
+#[test]
+fn every_drop_is_retried_or_exhausted() {
+    let mut qn = lossy_with_retries(1, None, 3);
+    let run = qn.run_until(StopCondition::Time(10000.));
+    let stats = qn.get_retry_statistics(0).clone();
+
+    // Without timeout, attempts only fail when dropped, and responses are never late
+    assert_eq!(stats.late_responses, 0);
+    assert_eq!(run.arrivals[2], stats.successes);
+    assert!(stats.retries > 0 && stats.exhausted > 0);
+    // Drops still waiting for their retry at the end of the run
+    let pending = run.losses[1] - stats.retries - stats.exhausted;
+    let in_flight = stats.requests - stats.successes - stats.exhausted;
+    assert!(pending <= in_flight && in_flight <= 10, "{:?}", stats);
+}
+
+#[test]
+fn timeouts_retry_requests_still_in_the_network() {
+    // Service times often exceed the timeout: the late attempts still reach the sink
+    let mut qn = lossy_with_retries(2, Some(0.5), 2);
+    let run = qn.run_until(StopCondition::Time(10000.));
+    let stats = qn.get_retry_statistics(0);
+
+    assert!(stats.late_responses > 0);
+    assert_eq!(run.arrivals[2], stats.successes + stats.late_responses);
+    assert!(stats.requests - stats.successes - stats.exhausted <= 10, "{:?}", stats);
+}
+
+#[test]
+fn drops_wait_for_the_pending_timeout() {
+    // Every attempt is dropped: the second one waits for the timeout of the first, not only for the backoff
+    let mut qn = QNet::from_seed(3);
+    let source = qn.add_queue(Box::new(PoissonGenerator::new(0.01, ConstantDistribution::new(0))));
+    let mut station = MGKFIFO::new(1, 1., Exp::new(1.));
+    station.set_capacity(0);
+    let server = qn.add_queue(Box::new(station));
+    let sink = qn.add_queue(Box::new(Sink::new()));
+    qn.add_transition(source, Box::new(move |_,_,_| server));
+    qn.add_transition(server, Box::new(move |_,_,_| sink));
+    qn.set_retry_policy(source, RetryPolicy {
+        completion: sink,
+        timeout: Some(2.),
+        max_attempts: 2,
+        backoff: Backoff::constant(0.1),
+    });
+    let mut drops = Vec::new();
+    while drops.len() < 20 {
+        let t = qn.make_transition().unwrap();
+        if t.outcome == TransitionOutcome::Dropped(server) {
+            drops.push(t.time);
+        }
+    }
+    // Requests arrive about every 100, and are retried 0.1 after their timeout
+    for pair in drops.chunks(2) {
+        assert!((pair[1] - pair[0] - 2.1).abs() < 1e-9, "{:?}", drops);
+    }
+    let stats = qn.get_retry_statistics(source);
+    assert_eq!((stats.requests, stats.retries, stats.exhausted), (10, 10, 10));
+}
+
+#[test]
+fn attempts_ending_in_another_sink_fail() {
+    // Half of the attempts end in 'other': they are retried, and no request stays tracked
+    let mut qn = QNet::from_seed(4);
+    let source = qn.add_queue(Box::new(PoissonGenerator::new(0.5, ConstantDistribution::new(0))));
+    let server = qn.add_queue(Box::new(MGKFIFO::new(1, 1., Exp::new(1.))));
+    let done = qn.add_queue(Box::new(Sink::new()));
+    let other = qn.add_queue(Box::new(Sink::new()));
+    qn.add_transition(source, Box::new(move |_,_,_| server));
+    qn.add_routing(server, vec![done, other], &[0.5, 0.5]).unwrap();
+    qn.set_retry_policy(source, RetryPolicy {
+        completion: done,
+        timeout: None,
+        max_attempts: 3,
+        backoff: Backoff::constant(0.5),
+    });
+    let run = qn.run_until(StopCondition::Time(10000.));
+    let stats = qn.get_retry_statistics(source).clone();
+
+    assert_eq!(run.arrivals[done], stats.successes);
+    assert_eq!(run.arrivals[other], stats.retries + stats.exhausted);
+    assert!(stats.exhausted > 0);
+    assert!(qn.get_outstanding_requests() <= 30, "{} requests still tracked", qn.get_outstanding_requests());
+}
@@ -0,0 +1,39 @@
+// Checks that a seed reproduces a whole run
+extern crate queue_sim;
+extern crate rand;
+
+use rand::distributions::Exp;
+
+use queue_sim::helpers::distribution::ConstantDistribution;
+use queue_sim::queues::mg1ps::MG1PS;
+use queue_sim::queues::mgkfifo::MGKFIFO;
+use queue_sim::queues::poisson_generator::PoissonGenerator;
+use queue_sim::queues::queueing_network::{QNet,TransitionOutcome};
+use queue_sim::queues::sink::Sink;
+
+// source -> (PS server | 2-server FIFO, drawn at random) -> sink
+fn transitions(seed: u64, n: usize) -> Vec<(f64, usize, usize, usize, TransitionOutcome)> {
+    let mut qn = QNet::from_seed(seed);
+    let source = qn.add_queue(Box::new(PoissonGenerator::new(1., ConstantDistribution::new(0))));
+    let ps = qn.add_queue(Box::new(MG1PS::new(1., Exp::new(1.))));
+    let fifo = qn.add_queue(Box::new(MGKFIFO::new(2, 1., Exp::new(1.))));
+    let sink = qn.add_queue(Box::new(Sink::new()));
+    qn.add_routing(source, vec![ps, fifo], &[0.4, 0.6]).unwrap();
+    qn.add_transition(ps, Box::new(move |_,_,_| sink));
+    qn.add_transition(fifo, Box::new(move |_,_,_| sink));
+
+    (0..n).map(|_| {
+        let t = qn.make_transition().unwrap();
+        (t.time, t.origin, t.destination, t.request, t.outcome)
+    }).collect()
+}
+
+#[test]
+fn same_seed_gives_same_transitions() {
+    assert_eq!(transitions(7, 5000), transitions(7, 5000));
+}
+
+#[test]
+fn different_seeds_give_different_transitions() {
+    assert_ne!(transitions(7, 5000), transitions(8, 5000));
+}
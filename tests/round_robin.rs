@@ -0,0 +1,59 @@
+// Checks the quanta and switch overheads of round-robin queues
+extern crate queue_sim;
+extern crate toml;
+
+mod common;
+
+use common::{exits,work};
+use queue_sim::helpers::distribution::ConstantDistribution;
+use queue_sim::network_config::{NetworkConfig,QueueKind};
+use queue_sim::queues::sampler_from;
+use queue_sim::queues::round_robin::RoundRobin;
+use queue_sim::queues::service::FromAttribute;
+
+fn round_robin(quantum: f64, overhead: Option<f64>) -> RoundRobin<FromAttribute> {
+    let mut q = RoundRobin::new(quantum, 1., work());
+    if let Some(o) = overhead {
+        q.set_switch_overhead(sampler_from(ConstantDistribution::new(o)));
+    }
+    q
+}
+
+fn assert_exits(actual: Vec<(f64, usize)>, expected: &[(f64, usize)]) {
+    assert_eq!(actual.len(), expected.len(), "{:?}", actual);
+    for (a, e) in actual.iter().zip(expected) {
+        assert!(a.1 == e.1 && (a.0 - e.0).abs() < 1e-9, "Expected {:?}, got {:?}", expected, actual);
+    }
+}
+
+#[test]
+fn requests_take_turns() {
+    let arrivals = [(0., 1.), (0., 1.)];
+    assert_exits(exits(&mut round_robin(0.5, None), &arrivals), &[(1.5, 0), (2., 1)]);
+    // Three switches before the first exit, and one before the second
+    assert_exits(exits(&mut round_robin(0.5, Some(0.1)), &arrivals), &[(1.7, 0), (2.3, 1)]);
+    // A quantum longer than every request is FIFO
+    assert_exits(exits(&mut round_robin(10., Some(0.1)), &arrivals), &[(1., 0), (2.1, 1)]);
+}
+
+#[test]
+fn no_overhead_without_switch() {
+    // Request 0 runs alone for two quanta, then request 1 arrives during the third one
+    let arrivals = [(0., 2.), (1.1, 0.2)];
+    assert_exits(exits(&mut round_robin(0.5, Some(0.1)), &arrivals), &[(1.8, 1), (2.4, 0)]);
+}
+
+#[test]
+fn round_robin_from_network_description() {
+    let config: NetworkConfig = toml::from_str(r#"
+        [[queue]]
+        name = "cpu"
+        type = "round_robin"
+        quantum = 0.01
+        overhead = { type = "constant", value = 0.001 }
+        service = { type = "exp", rate = 1.0 }
+    "#).unwrap();
+
+    assert!(matches!(config.queues[0].kind, QueueKind::RoundRobin { overhead: Some(_), .. }));
+    assert!(config.build().is_ok());
+}
@@ -0,0 +1,82 @@
+// Checks the alias tables and the probabilistic routing built on them
+extern crate queue_sim;
+
+use queue_sim::helpers::alias::AliasTable;
+use queue_sim::helpers::rng::rng_from_seed;
+use queue_sim::queues::queueing_network::{QNet,RoutingError};
+use queue_sim::queues::sink::Sink;
+
+// Frequency of every index over 'n' samples
+fn frequencies(table: &AliasTable, n: usize) -> Vec<f64> {
+    let mut rng = rng_from_seed(1);
+    let mut counts = vec![0; table.len()];
+    for _ in 0..n {
+        counts[table.sample(&mut rng)] += 1;
+    }
+    counts.into_iter().map(|c| c as f64 / n as f64).collect()
+}
+
+#[test]
+fn alias_sampling_frequencies() {
+    let weights = [1., 2., 3., 4., 0.5, 9.5];
+    let table = AliasTable::new(&weights);
+    assert_eq!(table.len(), 6);
+    for (f, w) in frequencies(&table, 400_000).into_iter().zip(weights.iter()) {
+        // Standard deviation below 0.0008
+        assert!((f - w / 20.).abs() < 0.004, "{} instead of {}", f, w / 20.);
+    }
+}
+
+#[test]
+fn alias_degenerate_weights() {
+    // Zero weights are never drawn
+    let f = frequencies(&AliasTable::new(&[0., 3., 0., 1.]), 100_000);
+    assert_eq!((f[0], f[2]), (0., 0.));
+    assert!((f[1] - 0.75).abs() < 0.01);
+
+    // A single index, and uniform weights
+    assert_eq!(frequencies(&AliasTable::new(&[2.]), 1000), vec![1.]);
+    for f in frequencies(&AliasTable::new(&[1.; 5]), 100_000) {
+        assert!((f - 0.2).abs() < 0.01);
+    }
+
+    // Weights that do not sum to one are normalized
+    let f = frequencies(&AliasTable::new(&[1e-9, 3e-9]), 100_000);
+    assert!((f[1] - 0.75).abs() < 0.01);
+}
+
+#[test]
+#[should_panic]
+fn alias_needs_a_positive_weight() {
+    AliasTable::new(&[0., 0.]);
+}
+
+fn network(queues: usize) -> QNet {
+    let mut qn = QNet::from_seed(1);
+    for _ in 0..queues {
+        qn.add_queue(Box::new(Sink::new()));
+    }
+    qn
+}
+
+#[test]
+fn invalid_routing_is_an_error() {
+    let mut qn = network(3);
+    assert!(matches!(qn.add_routing(0, vec![1, 3], &[0.5, 0.5]), Err(RoutingError::DestinationOutOfBound(0, 3))));
+    assert!(matches!(qn.add_routing(0, vec![1, 2], &[0.5]), Err(RoutingError::LengthMismatch(0))));
+    assert!(matches!(qn.add_routing(0, vec![1, 2], &[1.5, -0.5]), Err(RoutingError::NegativeProbability(0, 2))));
+    assert!(matches!(qn.add_routing(0, vec![1, 2], &[0.5, 0.4]), Err(RoutingError::NotNormalized(0, _))));
+    assert!(matches!(qn.add_routing(5, vec![1], &[1.]), Err(RoutingError::OriginOutOfBound(5))));
+    assert!(qn.add_routing(0, vec![1, 2], &[0.5, 0.5]).is_ok());
+}
+
+#[test]
+fn invalid_routing_matrix_is_an_error() {
+    let mut qn = network(3);
+    let row = vec![0., 0.5, 0.5];
+    assert!(matches!(qn.add_routing_matrix(std::slice::from_ref(&row), 7), Err(RoutingError::ExitOutOfBound(7))));
+    assert!(matches!(qn.add_routing_matrix(&vec![row.clone(); 4], 2), Err(RoutingError::OriginOutOfBound(3))));
+    assert!(matches!(qn.add_routing_matrix(&[vec![], vec![0.5, 0.5]], 2), Err(RoutingError::LengthMismatch(1))));
+    assert!(matches!(qn.add_routing_matrix(&[vec![], vec![0.5, 0.5, 0.5]], 2), Err(RoutingError::NotNormalized(1, _))));
+    assert!(qn.add_routing_matrix(&[row, vec![], vec![]], 2).is_ok());
+}
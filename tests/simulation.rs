@@ -0,0 +1,108 @@
+// Checks the run report of run_until
+extern crate queue_sim;
+extern crate rand;
+
+use rand::distributions::Exp;
+
+use std::env;
+use std::fs;
+
+use queue_sim::helpers::distribution::ConstantDistribution;
+use queue_sim::queues::file_logger::FileLogger;
+use queue_sim::queues::generator::Generator;
+use queue_sim::queues::mginf::MGINF;
+use queue_sim::queues::poisson_generator::PoissonGenerator;
+use queue_sim::queues::Queue;
+use queue_sim::queues::queueing_network::QNet;
+use queue_sim::queues::simulation::{Simulation,StopCondition};
+use queue_sim::queues::sink::Sink;
+use queue_sim::queues::warmup::Warmup;
+
+// source -> infinite server -> sink
+fn delay_line(seed: u64) -> QNet {
+    let mut qn = QNet::from_seed(seed);
+    let source = qn.add_queue(Box::new(PoissonGenerator::new(1., ConstantDistribution::new(0))));
+    let server = qn.add_queue(Box::new(MGINF::new(1., Exp::new(0.5))));
+    let sink = qn.add_queue(Box::new(Sink::new()));
+    qn.add_transition(source, Box::new(move |_,_,_| server));
+    qn.add_transition(server, Box::new(move |_,_,_| sink));
+    qn
+}
+
+#[test]
+fn time_stop_ends_at_the_horizon() {
+    let mut qn = delay_line(1);
+    let run = qn.run_until(StopCondition::Time(1000.5));
+    assert_eq!(run.end_time, 1000.5);
+    assert_eq!(qn.get_time(), 1000.5);
+    // Little's law over the whole horizon: about 2 requests in the server
+    assert!((run.mean_load(1) - 2.).abs() < 0.2, "{}", run.mean_load(1));
+    // The next run starts where this one ended
+    let run = qn.run_until(StopCondition::Time(2000.));
+    assert_eq!((run.start_time, run.end_time), (1000.5, 2000.));
+
+    let mut qn = delay_line(1);
+    let run = qn.run_until(StopCondition::Any(vec![StopCondition::Time(1000.5), StopCondition::Events(10)]));
+    assert_eq!(run.events, 10);
+    assert_eq!(run.end_time, qn.get_time());
+}
+
+#[test]
+fn unfinished_warmup_is_reported() {
+    let mut qn = delay_line(2);
+    qn.set_warmup(Warmup::Departures(2, 1_000_000));
+    let run = qn.run_until(StopCondition::Time(100.));
+    assert!(run.warmup_pending);
+    assert!(run.is_error());
+
+    let mut qn = delay_line(2);
+    qn.set_warmup(Warmup::Departures(2, 10));
+    let run = qn.run_until(StopCondition::Time(100.));
+    assert!(!run.warmup_pending);
+    assert!(!run.is_error());
+}
+
+// A request every second, staying 0.5s in a delay line before 'sink'
+fn deterministic_line(sink: Box<dyn Queue>) -> QNet {
+    let mut qn = QNet::from_seed(1);
+    let source = qn.add_queue(Box::new(Generator::new(ConstantDistribution::new(1.))));
+    let server = qn.add_queue(Box::new(MGINF::new(1., ConstantDistribution::new(0.5))));
+    let sink = qn.add_queue(sink);
+    qn.add_transition(source, Box::new(move |_,_,_| server));
+    qn.add_transition(server, Box::new(move |_,_,_| sink));
+    qn
+}
+
+#[test]
+fn counts_start_at_the_end_of_the_warmup() {
+    let mut qn = deterministic_line(Box::new(Sink::new()));
+    qn.set_warmup(Warmup::Time(50.));
+    let run = qn.run_until(StopCondition::Time(100.7));
+    assert!(!run.warmup_pending);
+    assert_eq!((run.start_time, run.end_time), (50., 100.7));
+    // The requests arriving from 50 to 100, that leave the delay line from 50.5 to 100.5
+    assert_eq!(run.arrivals[1], 51);
+    assert_eq!(run.arrivals[2], 51);
+    assert!((run.throughput(2) - 51. / 50.7).abs() < 1e-9);
+    assert!((run.mean_response_time(2) - 0.5).abs() < 1e-9);
+    assert!((run.mean_load(1) - 25.5 / 50.7).abs() < 1e-9, "{}", run.mean_load(1));
+}
+
+#[test]
+fn logs_of_an_unfinished_warmup_are_discarded() {
+    let filename = env::temp_dir().join(format!("queue_sim_warmup_{}.csv", std::process::id()));
+    let path = filename.to_str().unwrap();
+    let mut qn = deterministic_line(Box::new(FileLogger::new(1, path)));
+    qn.set_warmup(Warmup::Time(50.));
+    assert!(qn.run_until(StopCondition::Time(20.)).warmup_pending);
+    drop(qn);
+    assert_eq!(fs::read_to_string(path).unwrap(), "");
+
+    let mut qn = deterministic_line(Box::new(FileLogger::new(1, path)));
+    qn.set_warmup(Warmup::Time(10.));
+    assert!(!qn.run_until(StopCondition::Time(20.)).warmup_pending);
+    drop(qn);
+    // The requests that reached the logger from 10.5 to 19.5
+    assert_eq!(fs::read_to_string(path).unwrap().lines().count(), 10);
+    fs::remove_file(path).unwrap();
+}
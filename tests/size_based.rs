@@ -0,0 +1,84 @@
+// Checks the service order of size-based scheduling disciplines
+extern crate queue_sim;
+extern crate toml;
+
+mod common;
+
+use common::{exits,request,work};
+use queue_sim::network_config::{NetworkConfig,QueueKind,SizePolicyConfig};
+use queue_sim::queues::Queue;
+use queue_sim::queues::size_based::{MG1LAS,MG1SizeBased,SizePolicy};
+
+#[test]
+fn srpt_and_sjf() {
+    let arrivals = [(0., 4.), (1., 2.), (2., 0.5)];
+    // SRPT preempts the first request, then the second one, which then has 1 left
+    assert_eq!(exits(&mut MG1SizeBased::new(1., SizePolicy::Srpt, work()), &arrivals), vec![(2.5, 2), (3.5, 1), (6.5, 0)]);
+    // SJF only chooses the shortest waiting request once the server is free
+    assert_eq!(exits(&mut MG1SizeBased::new(1., SizePolicy::Sjf, work()), &arrivals), vec![(4., 0), (4.5, 2), (6.5, 1)]);
+}
+
+#[test]
+fn waiting_ends_with_the_first_service() {
+    // SRPT serves request 0 from 0, preempts it at 1 for request 1, and resumes it at 2
+    let mut q = MG1SizeBased::new(1., SizePolicy::Srpt, work());
+    let mut starts = Vec::new();
+    for (id, &(t, work)) in [(0., 3.), (1., 1.), (1.5, 2.)].iter().enumerate() {
+        q.update_time(t);
+        q.arrival(request(id, 0, work));
+    }
+    while let Some((t, _)) = q.read_next_exit() {
+        q.update_time(t);
+        let (_, r) = q.pop_next_exit().unwrap();
+        starts.push((r.get_id(), r.get_service_start()));
+    }
+    assert_eq!(starts, vec![(1, Some(1.)), (0, Some(0.)), (2, Some(4.))]);
+}
+
+#[test]
+fn fsp_follows_virtual_ps() {
+    // In PS, request 1 (work 1 at time 1) completes at 3, before request 0 (work 3 at time 0) at 4:
+    // FSP serves request 1 as soon as it arrives, then request 0
+    let arrivals = [(0., 3.), (1., 1.)];
+    assert_eq!(exits(&mut MG1SizeBased::new(1., SizePolicy::Fsp, work()), &arrivals), vec![(2., 1), (4., 0)]);
+    // In PS, request 1 slows request 0 down, so that request 2 would complete first: it preempts request 0,
+    // which SRPT does not as request 0 has less work left
+    let arrivals = [(0., 2.), (0.5, 3.), (1., 1.2)];
+    assert_eq!(exits(&mut MG1SizeBased::new(1., SizePolicy::Fsp, work()), &arrivals), vec![(2.2, 2), (3.2, 0), (6.2, 1)]);
+    assert_eq!(exits(&mut MG1SizeBased::new(1., SizePolicy::Srpt, work()), &arrivals), vec![(2., 0), (3.2, 2), (6.2, 1)]);
+}
+
+#[test]
+fn las_shares_the_server_among_the_least_served() {
+    // Request 0 attained 1 when request 1 arrives: request 1 is served alone until it attained as much at time 2,
+    // then both share the server: request 1 completes at 2 + 2 * 1, and request 0 gets its last 1 alone
+    let arrivals = [(0., 3.), (1., 2.)];
+    assert_eq!(exits(&mut MG1LAS::new(1., work()), &arrivals), vec![(4., 1), (5., 0)]);
+    // Request 2 completes before catching up with the others
+    let arrivals = [(0., 3.), (1., 2.), (1.5, 0.2)];
+    let las = exits(&mut MG1LAS::new(2., work()), &arrivals);
+    assert_eq!(las.iter().map(|&(_, id)| id).collect::<Vec<_>>(), vec![2, 1, 0]);
+    assert!((las[2].0 - 2.6).abs() < 1e-9, "{:?}", las);
+}
+
+#[test]
+fn size_based_from_network_description() {
+    let config: NetworkConfig = toml::from_str(r#"
+        [[queue]]
+        name = "srpt"
+        type = "size_based"
+        policy = "srpt"
+        service = { type = "constant", value = 0.5 }
+
+        [[queue]]
+        name = "las"
+        type = "size_based"
+        policy = "las"
+        service = { type = "exp", rate = 1.0 }
+        capacity = 5
+    "#).unwrap();
+
+    assert!(matches!(config.queues[0].kind, QueueKind::SizeBased { policy: SizePolicyConfig::Srpt, capacity: None, .. }));
+    assert!(matches!(config.queues[1].kind, QueueKind::SizeBased { policy: SizePolicyConfig::Las, capacity: Some(5), .. }));
+    assert!(config.build().is_ok());
+}
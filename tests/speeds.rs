@@ -0,0 +1,75 @@
+// Checks the server selection of MGKFIFO queues with servers of different speeds
+extern crate queue_sim;
+extern crate toml;
+
+mod common;
+
+use common::{exits,work};
+use queue_sim::network_config::{NetworkConfig,QueueKind,SelectionConfig};
+use queue_sim::queues::Queue;
+use queue_sim::queues::mgkfifo::{MGKFIFO,ServerSelection};
+use queue_sim::queues::service::FromAttribute;
+
+fn two_speeds(selection: ServerSelection) -> MGKFIFO<FromAttribute> {
+    let mut q = MGKFIFO::new(2, 1., work());
+    q.set_speeds(&[1., 2.]);
+    q.set_selection(selection);
+    q
+}
+
+#[test]
+fn fastest_free_server_first() {
+    let mut q = two_speeds(ServerSelection::FastestFree);
+    assert_eq!(exits(&mut q, &[(0., 2.), (0., 2.)]), vec![(1., 0), (2., 1)]);
+    // A lone request always gets the fast server
+    assert_eq!(exits(&mut q, &[(2., 4.)]), vec![(4., 0)]);
+}
+
+#[test]
+fn round_robin_cycles_through_servers() {
+    let mut q = two_speeds(ServerSelection::RoundRobin);
+    assert_eq!(exits(&mut q, &[(0., 2.), (0., 2.)]), vec![(1., 1), (2., 0)]);
+    assert_eq!(exits(&mut q, &[(2., 2.)]), vec![(4., 0)]);
+    assert_eq!(exits(&mut q, &[(4., 2.)]), vec![(5., 0)]);
+}
+
+#[test]
+fn random_free_uses_every_server() {
+    let mut q = two_speeds(ServerSelection::RandomFree);
+    q.reseed(1);
+    let mut durations = Vec::new();
+    for i in 0..100 {
+        let time = 10. * i as f64;
+        let (t, _) = exits(&mut q, &[(time, 2.)])[0];
+        durations.push(t - time);
+    }
+    assert!(durations.contains(&1.));
+    assert!(durations.contains(&2.));
+}
+
+#[test]
+fn speeds_from_network_description() {
+    let config: NetworkConfig = toml::from_str(r#"
+        [[queue]]
+        name = "server"
+        type = "mgkfifo"
+        servers = 2
+        speeds = [2.0, 1.0]
+        selection = "random_free"
+        service = { type = "constant", value = 1.0 }
+    "#).unwrap();
+
+    assert!(matches!(config.queues[0].kind, QueueKind::Mgkfifo { servers: 2, selection: SelectionConfig::RandomFree, .. }));
+    assert!(config.build().is_ok());
+
+    // One speed per server
+    let config: NetworkConfig = toml::from_str(r#"
+        [[queue]]
+        name = "server"
+        type = "mgkfifo"
+        servers = 3
+        speeds = [2.0, 1.0]
+        service = { type = "exp", rate = 1.0 }
+    "#).unwrap();
+    assert!(config.build().is_err());
+}
@@ -0,0 +1,45 @@
+// Checks the quantiles and confidence intervals against tabulated values
+extern crate queue_sim;
+
+use queue_sim::helpers::statistics::{Estimate,normal_quantile,student_t_quantile};
+
+fn assert_close(actual: f64, expected: f64, relative: f64) {
+    assert!((actual - expected).abs() <= relative * expected.abs(), "{} instead of {}", actual, expected);
+}
+
+#[test]
+fn normal_quantiles() {
+    assert_eq!(normal_quantile(0.5), 0.);
+    assert_close(normal_quantile(0.975), 1.959964, 1e-6);
+    assert_close(normal_quantile(0.95), 1.644854, 1e-6);
+    assert_close(normal_quantile(0.01), -2.326348, 1e-6);
+    assert_close(normal_quantile(0.9999), 3.719016, 1e-6);
+}
+
+#[test]
+fn student_t_quantiles() {
+    // Two-sided 95% quantiles, for 1 to 5, 10, 30 and 100 degrees of freedom
+    let table = [(1, 12.7062), (2, 4.3027), (3, 3.1824), (4, 2.7764), (5, 2.5706), (10, 2.2281), (30, 2.0423), (100, 1.9840)];
+    for &(dof, t) in table.iter() {
+        assert_close(student_t_quantile(0.975, dof), t, 2e-3);
+    }
+    assert_close(student_t_quantile(0.95, 4), 2.1318, 2e-3);
+    assert_close(student_t_quantile(0.995, 20), 2.8453, 2e-3);
+    // Symmetry
+    assert_close(student_t_quantile(0.025, 7), -student_t_quantile(0.975, 7), 1e-12);
+}
+
+#[test]
+fn estimate_interval() {
+    let estimate = Estimate::from_samples(&[1., 2., 3., 4., 5.], 0.95);
+    assert_eq!(estimate.samples, 5);
+    assert_eq!(estimate.mean, 3.);
+    assert_eq!(estimate.variance, 2.5);
+    // t(0.975, 4) * sqrt(2.5 / 5)
+    assert_close(estimate.half_width, 1.9632, 2e-3);
+
+    // No interval from less than 2 samples
+    let estimate = Estimate::from_samples(&[1.], 0.95);
+    assert_eq!(estimate.half_width, f64::INFINITY);
+    assert!(Estimate::from_samples(&[], 0.95).mean.is_nan());
+}
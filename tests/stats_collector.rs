@@ -0,0 +1,60 @@
+// Checks the utilization measured by statistics collectors when servers fail or take vacations
+extern crate queue_sim;
+
+mod common;
+
+use common::{request,run,work};
+use queue_sim::helpers::distribution::ConstantDistribution;
+use queue_sim::queues::{Queue,sampler_from};
+use queue_sim::queues::breakdowns::{Breakdowns,Interruption};
+use queue_sim::queues::mg1ps::MG1PS;
+use queue_sim::queues::mginf::MGINF;
+use queue_sim::queues::mgkfifo::MGKFIFO;
+use queue_sim::queues::request::Request;
+use queue_sim::queues::stats_collector::StatisticsCollector;
+
+// Up for 10, down for 5, from time 0
+fn breakdowns() -> Breakdowns {
+    Breakdowns::new(sampler_from(ConstantDistribution::new(10.)), sampler_from(ConstantDistribution::new(5.)), Interruption::Resume)
+}
+
+fn utilization(q: &StatisticsCollector, now: f64) -> f64 {
+    q.read_statistics(now).unwrap().utilization
+}
+
+#[test]
+fn failures_stop_the_busy_time() {
+    let mut fifo = MGKFIFO::new(1, 1., work());
+    fifo.set_breakdowns(breakdowns());
+    let mut ps: MG1PS<_, Request> = MG1PS::new(1., work());
+    ps.set_breakdowns(breakdowns());
+    let queues: Vec<Box<dyn Queue>> = vec![Box::new(fifo), Box::new(ps)];
+    for q in queues {
+        // Served from 5 to 10, and from 15 to 18
+        let mut q = StatisticsCollector::new(q, 0.);
+        q.update_time(5.);
+        q.arrival(request(0, 0, 8.));
+        // The failure at 10 is accounted for before the queue is updated again
+        assert!((utilization(&q, 12.) - 5. / 12.).abs() < 1e-9, "{}", utilization(&q, 12.));
+        assert_eq!(run(&mut q, &[]), vec![(18., 0, false)]);
+        assert!((utilization(&q, 20.) - 8. / 20.).abs() < 1e-9, "{}", utilization(&q, 20.));
+    }
+
+    // The server of the request arriving at 3 is down from 13 to 18
+    let mut delay = MGINF::new(1., work());
+    delay.set_breakdowns(breakdowns());
+    let mut q = StatisticsCollector::new(Box::new(delay), 0.);
+    assert_eq!(run(&mut q, &[(3., 0, 12.)]), vec![(20., 0, false)]);
+    // Average number of busy servers
+    assert!((utilization(&q, 20.) - 12. / 20.).abs() < 1e-9, "{}", utilization(&q, 20.));
+}
+
+#[test]
+fn vacations_delay_the_busy_time() {
+    // Back from vacation at 3, the server serves the request arrived at 1 until 5
+    let mut fifo = MGKFIFO::new(1, 1., work());
+    fifo.set_vacation(sampler_from(ConstantDistribution::new(3.)));
+    let mut q = StatisticsCollector::new(Box::new(fifo), 0.);
+    assert_eq!(run(&mut q, &[(1., 0, 2.)]), vec![(5., 0, false)]);
+    assert!((utilization(&q, 10.) - 0.2).abs() < 1e-9, "{}", utilization(&q, 10.));
+}
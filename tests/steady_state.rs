@@ -0,0 +1,52 @@
+// Checks the truncation point detection of the warm-up period
+extern crate queue_sim;
+
+use queue_sim::helpers::steady_state::{mser5_truncation,welch_truncation};
+
+// 'transient' observations at 10, then 'steady' ones alternating between 1 and 1.2
+fn series(transient: usize, steady: usize) -> Vec<f64> {
+    (0..transient).map(|_| 10.).chain((0..steady).map(|i| if i % 2 == 0 { 1. } else { 1.2 })).collect()
+}
+
+#[test]
+fn mser5_deletes_the_transient() {
+    assert_eq!(mser5_truncation(&series(100, 900)), Some(100));
+    // Transients that do not end on a batch boundary are rounded up to the next one
+    assert_eq!(mser5_truncation(&series(98, 902)), Some(100));
+}
+
+#[test]
+fn mser5_keeps_stationary_series() {
+    assert_eq!(mser5_truncation(&series(0, 1000)), Some(0));
+    assert_eq!(mser5_truncation(&[3.; 50]), Some(0));
+}
+
+#[test]
+fn mser5_rejects_series_too_short_or_not_stationary() {
+    // Less than 2 batches
+    assert_eq!(mser5_truncation(&[1., 2., 3., 4., 5., 6., 7., 8., 9.]), None);
+    assert_eq!(mser5_truncation(&[]), None);
+    // The optimum would delete more than half of the series
+    let trend: Vec<f64> = (0..1000).map(|i| i as f64).collect();
+    assert_eq!(mser5_truncation(&trend), None);
+}
+
+#[test]
+fn welch_deletes_the_transient() {
+    // The moving average at i covers i-5..=i+5: it stays close to the mean of the second half from 105 on
+    assert_eq!(welch_truncation(&series(100, 900), 5, 0.05), Some(105));
+    // A tolerance below the ripple of the moving average (about 0.9%) is never met
+    assert_eq!(welch_truncation(&series(100, 900), 5, 0.001), None);
+}
+
+#[test]
+fn welch_keeps_stationary_series() {
+    // The first point of the smoothed series is at 'window'
+    assert_eq!(welch_truncation(&series(0, 1000), 5, 0.05), Some(5));
+}
+
+#[test]
+fn welch_rejects_series_too_short_or_not_stationary() {
+    assert_eq!(welch_truncation(&series(0, 21), 5, 0.05), None);
+    assert_eq!(welch_truncation(&series(600, 400), 5, 0.05), None);
+}
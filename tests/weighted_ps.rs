@@ -0,0 +1,97 @@
+// Checks the shares of weighted processor sharing queues
+extern crate queue_sim;
+extern crate toml;
+
+mod common;
+
+use common::{class_exits,exits,request,work};
+use queue_sim::network_config::{NetworkConfig,QueueKind};
+use queue_sim::queues::Queue;
+use queue_sim::queues::weighted_ps::{MG1DPS,MG1GPS};
+
+#[test]
+fn dps_shares_by_weight() {
+    // Request 0 gets 2/3 of the server until it completes at 3, request 1 then has 1 left
+    assert_eq!(class_exits(&mut MG1DPS::new(1., vec![2., 1.], work()), &[(0., 0, 2.), (0., 1, 2.)]), vec![(3., 0), (4., 1)]);
+    // With equal weights, DPS is PS
+    assert_eq!(class_exits(&mut MG1DPS::new(1., vec![1., 1.], work()), &[(0., 0, 1.), (0., 0, 1.), (0., 1, 3.)]), vec![(3., 0), (3., 1), (5., 2)]);
+}
+
+#[test]
+fn gps_shares_by_class() {
+    // Class 0 gets half of the server whatever its number of requests, and serves them one at a time
+    assert_eq!(class_exits(&mut MG1GPS::new(1., vec![1., 1.], work()), &[(0., 0, 1.), (0., 0, 1.), (0., 1, 3.)]), vec![(2., 0), (4., 1), (5., 2)]);
+    assert_eq!(class_exits(&mut MG1GPS::new(2., vec![3., 1.], work()), &[(0., 0, 3.), (0., 1, 1.5)]), vec![(2., 0), (2.25, 1)]);
+}
+
+// Requests (0, 1) and (1, 3) share the server equally until 2, when the first one is blocked.
+// Request 1 keeps half of the server while the first one is held
+fn blocked_at_2(q: &mut dyn Queue, stop_dispatch: bool) {
+    q.arrival(request(0, 0, 1.));
+    q.arrival(request(1, 1, 3.));
+    q.update_time(2.);
+    assert!(q.block_next_exit(stop_dispatch));
+    assert_eq!(q.read_next_exit().map(|(t, r)| (t, r.get_id())), Some((6., 1)));
+    assert_eq!(q.read_load(), 2);
+}
+
+#[test]
+fn blocked_requests_keep_their_share() {
+    let queues: Vec<Box<dyn Queue>> = vec![Box::new(MG1DPS::new(1., vec![1., 1.], work())), Box::new(MG1GPS::new(1., vec![1., 1.], work()))];
+    for mut q in queues {
+        blocked_at_2(&mut *q, false);
+        // Request 2 gets a third of the server from 2.5, and half of it once request 0 is released at 3.5,
+        // when it has 1/6 left and request 1 has 1.25
+        q.update_time(2.5);
+        q.arrival(request(2, 0, 0.5));
+        q.update_time(3.5);
+        assert_eq!(q.unblock_exit().map(|r| r.get_id()), Some(0));
+        assert_eq!(exits(&mut *q, &[]), vec![(3.5 + 1. / 3., 2), (3.5 + 1. / 3. + 1.25, 1)]);
+    }
+}
+
+#[test]
+fn blocked_requests_can_stop_the_dispatch() {
+    let queues: Vec<Box<dyn Queue>> = vec![Box::new(MG1DPS::new(1., vec![1., 1.], work())), Box::new(MG1GPS::new(1., vec![1., 1.], work()))];
+    for mut q in queues {
+        blocked_at_2(&mut *q, true);
+        // Request 2 waits for the release of request 0
+        q.update_time(2.5);
+        q.arrival(request(2, 0, 1.));
+        assert_eq!(q.read_load(), 3);
+        q.update_time(3.);
+        assert_eq!(q.unblock_exit().map(|r| r.get_id()), Some(0));
+        assert_eq!(exits(&mut *q, &[]), vec![(5., 2), (5.5, 1)]);
+    }
+}
+
+#[test]
+fn weighted_ps_from_network_description() {
+    let config: NetworkConfig = toml::from_str(r#"
+        [[queue]]
+        name = "fog"
+        type = "dps"
+        weights = [2.0, 1.0]
+        service = { type = "constant", value = 0.5 }
+
+        [[queue]]
+        name = "link"
+        type = "gps"
+        weights = [1.0, 1.0, 2.0]
+        service = { type = "constant", value = 0.5 }
+        capacity = 10
+    "#).unwrap();
+
+    assert!(matches!(config.queues[0].kind, QueueKind::Dps { ref weights, .. } if weights.len() == 2));
+    assert!(matches!(config.queues[1].kind, QueueKind::Gps { capacity: Some(10), .. }));
+    assert!(config.build().is_ok());
+
+    let invalid: NetworkConfig = toml::from_str(r#"
+        [[queue]]
+        name = "fog"
+        type = "dps"
+        weights = [1.0, 0.0]
+        service = { type = "exp", rate = 1.0 }
+    "#).unwrap();
+    assert!(invalid.build().is_err());
+}